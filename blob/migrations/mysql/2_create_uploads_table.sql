@@ -0,0 +1,21 @@
+-- 分片上传会话，完成后转为blobs记录并删除
+CREATE TABLE uploads (
+    id BIGINT NOT NULL PRIMARY KEY,
+    -- 上传者用户 ID
+    uid BIGINT NOT NULL,
+    -- 客户端原始文件名
+    name VARCHAR(1024) NOT NULL,
+    -- MIME 类型
+    btype VARCHAR(64) NOT NULL DEFAULT 'application/octet-stream',
+    -- 文件总大小（字节）
+    size BIGINT NOT NULL,
+    -- 已接收的字节数，下一个分片从该位置开始
+    received BIGINT NOT NULL DEFAULT 0,
+    -- 客户端声明的SHA-256，完成时校验
+    hash CHAR(64) NOT NULL,
+    createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+-- 同一用户重复上传同一文件时续传已有会话
+CREATE INDEX idx_uploads_uid_hash ON uploads(uid, hash);
@@ -0,0 +1,9 @@
+-- 按内容去重的物理文件，相同SHA-256的上传共用一份，refs为引用该内容的blobs记录数
+CREATE TABLE objects (
+    hash CHAR(64) NOT NULL PRIMARY KEY,
+    -- 文件大小（字节）
+    size BIGINT NOT NULL DEFAULT 0,
+    -- 引用计数，为0时删除文件
+    refs BIGINT NOT NULL DEFAULT 0,
+    createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
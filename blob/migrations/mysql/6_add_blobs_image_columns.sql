@@ -0,0 +1,7 @@
+-- 图片的宽高（像素），非图片为空
+ALTER TABLE blobs ADD COLUMN width INT NULL;
+ALTER TABLE blobs ADD COLUMN height INT NULL;
+-- 派生文件（如缩略图）对应的原文件 ID
+ALTER TABLE blobs ADD COLUMN parent BIGINT NULL;
+
+CREATE INDEX idx_blobs_parent ON blobs(parent);
@@ -0,0 +1,2 @@
+-- 上传者设置的有效期(天)，为空时使用默认值
+ALTER TABLE uploads ADD COLUMN expire_days INT NULL;
@@ -0,0 +1,3 @@
+-- 内容所在的存储位置：local为本地目录，s3为对象存储
+ALTER TABLE objects ADD COLUMN provider TEXT NOT NULL DEFAULT 'local';
+ALTER TABLE objects ADD COLUMN bucket TEXT NULL;
@@ -0,0 +1,7 @@
+-- 图片的宽高（像素），非图片为空
+ALTER TABLE blobs ADD COLUMN width INTEGER NULL;
+ALTER TABLE blobs ADD COLUMN height INTEGER NULL;
+-- 派生文件（如缩略图）对应的原文件 ID
+ALTER TABLE blobs ADD COLUMN parent INTEGER NULL;
+
+CREATE INDEX idx_blobs_parent ON blobs(parent);
@@ -0,0 +1,8 @@
+-- 后台任务的租约，多实例部署时同一时间只有持有租约的实例执行任务
+CREATE TABLE leases (
+    name VARCHAR(64) NOT NULL PRIMARY KEY,
+    -- 持有租约的实例
+    holder INTEGER NOT NULL DEFAULT 0,
+    -- 租约到期时间戳（毫秒），到期后其他实例可以获取
+    expires_at INTEGER NOT NULL DEFAULT 0
+);
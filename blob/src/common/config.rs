@@ -0,0 +1,16 @@
+use std::sync::OnceLock;
+
+use core::config::BlobConfig;
+
+pub static CONFIG: OnceLock<BlobConfig> = OnceLock::new();
+
+//加载并校验配置，配置不合法时直接退出
+pub fn init() -> &'static BlobConfig {
+    let config = core::config::load_or_exit::<BlobConfig>("blob");
+    core::config::print("blob", &config);
+    CONFIG.get_or_init(|| config)
+}
+
+pub fn get() -> &'static BlobConfig {
+    CONFIG.get().expect("配置未初始化")
+}
@@ -1 +1,2 @@
-pub mod router;
\ No newline at end of file
+pub mod router;
+pub mod config;
\ No newline at end of file
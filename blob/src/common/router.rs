@@ -3,14 +3,17 @@ use tower_http::{cors::{Any, CorsLayer}, validate_request::ValidateRequestHeader
 use anyhow::Result;
 use axum::{error_handling::HandleErrorLayer, routing::any, Router};
 use core::response::{e404, e500};
-use crate::controller::{blob};
+use crate::controller::{blob, gc, upload};
 
 pub fn init() -> Result<Router> {
     let app = Router::new();
     Ok(app
         .route("/index", axum::routing::get(|| async { "Blob Service" }))
         .merge(blob::router())
+        .merge(upload::router())
+        .merge(gc::router())
         .layer(ValidateRequestHeaderLayer::custom(core::auth::AuthHeader{}))
+        .merge(blob::content_router())
         .fallback(any(e404))
         .layer(CorsLayer::new().allow_methods(Any).allow_origin(Any))
         .layer(ServiceBuilder::new()
@@ -1,21 +1,25 @@
 use axum::{
     routing::{get, post},
     extract::{Extension},
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     Router,
 };
 use core::auth::User;
 use core::extract::Multipart;
-use core::extract::{Path};
+use core::extract::{Path, Query};
 use core::response::{ApiResponse, ApiErr};
-use core::api::types::blob::{BlobResponse, BlobUploadResponse};
+use core::api::types::blob::{BlobContentRequest, BlobResponse, BlobUploadRequest, BlobUploadResponse};
 use anyhow::Result;
-use crate::{service::blob::{get_blob, upload_file}};
+use crate::{service::blob::{self, content_disposition, content_size, get_blob, open_content, parse_range, read_content, upload_file, ByteRange}};
 
 pub async fn upload_blob(
     Extension(user): Extension<User>,
+    Query(request): Query<BlobUploadRequest>,
     multipart: Multipart,
 ) -> Result<ApiResponse<BlobUploadResponse>, ApiErr> {
-    Ok(ApiResponse::One(upload_file(user, multipart).await?))
+    Ok(ApiResponse::One(upload_file(user, request.expire_days, multipart).await?))
 }
 
 pub async fn get_blob_by_id(
@@ -24,8 +28,63 @@ pub async fn get_blob_by_id(
     Ok(ApiResponse::One(get_blob(id).await?))
 }
 
+pub async fn delete_blob(
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    blob::delete(user, id).await?;
+    Ok(ApiResponse::One(true))
+}
+
+//按签名链接下载文件内容，支持单区间Range请求和ETag缓存校验
+pub async fn get_blob_content(
+    Path(id): Path<i64>,
+    Query(request): Query<BlobContentRequest>,
+    headers: HeaderMap,
+) -> Result<Response, ApiErr> {
+    let blob = open_content(id, &request.token, request.expire).await?;
+    let size = content_size(&blob).await?;
+    let etag = format!("\"{}\"", blob.hash.clone().unwrap_or_else(|| format!("{:x}-{:x}", blob.id, size)));
+    let header_str = |name| headers.get(name).and_then(|v: &HeaderValue| v.to_str().ok());
+    if header_str(header::IF_NONE_MATCH)
+        .is_some_and(|tags| tags.split(',').any(|t| t.trim() == etag || t.trim() == "*")) {
+        return Ok((StatusCode::NOT_MODIFIED, [(header::ETAG, etag)]).into_response());
+    }
+    //If-Range与当前版本不一致时忽略Range，返回整个文件
+    let range = match header_str(header::IF_RANGE) {
+        Some(tag) if tag != etag => ByteRange::Full,
+        _ => parse_range(header_str(header::RANGE), size),
+    };
+    let (status, start, len) = match range {
+        ByteRange::Full => (StatusCode::OK, 0, size),
+        ByteRange::Partial(start, end) => (StatusCode::PARTIAL_CONTENT, start, end - start + 1),
+        ByteRange::Unsatisfiable => return Ok((StatusCode::RANGE_NOT_SATISFIABLE,
+            [(header::CONTENT_RANGE, format!("bytes */{}", size))]).into_response()),
+    };
+    //按块读取内容，不把整个文件读入内存
+    let body = Body::from_stream(read_content(&blob, start, len).await?);
+    let btype = if blob.btype.contains('/') { blob.btype.as_str() } else { "application/octet-stream" };
+    let mut response = Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, btype)
+        .header(header::CONTENT_LENGTH, len)
+        .header(header::ACCEPT_RANGES, "bytes")
+        .header(header::ETAG, &etag)
+        .header(header::CONTENT_DISPOSITION, content_disposition(&blob.name));
+    if status == StatusCode::PARTIAL_CONTENT {
+        response = response.header(header::CONTENT_RANGE, format!("bytes {}-{}/{}", start, start + len - 1, size));
+    }
+    Ok(response.body(body).map_err(anyhow::Error::from)?)
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/blobs", post(upload_blob))
-        .route("/blobs/{id}", get(get_blob_by_id))
-}
\ No newline at end of file
+        .route("/blobs/{id}", get(get_blob_by_id).delete(delete_blob))
+}
+
+//下载链接自带签名，不需要Authorization请求头
+pub fn content_router() -> Router {
+    Router::new()
+        .route("/blobs/{id}/content", get(get_blob_content))
+}
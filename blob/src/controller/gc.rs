@@ -0,0 +1,24 @@
+use axum::{
+    routing::post,
+    extract::Extension,
+    Router,
+};
+use core::auth::User;
+use core::extract::Query;
+use core::response::{ApiResponse, ApiErr};
+use core::api::types::blob::{GcRequest, GcResponse};
+use anyhow::Result;
+use crate::service::gc;
+
+// 回收过期文件，dry_run=true时只统计可回收的文件数和字节数
+pub async fn collect(
+    Extension(user): Extension<User>,
+    Query(request): Query<GcRequest>,
+) -> Result<ApiResponse<GcResponse>, ApiErr> {
+    Ok(ApiResponse::One(gc::trigger(&user, request.dry_run).await?))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/admin/blobs/gc", post(collect))
+}
@@ -0,0 +1,49 @@
+use axum::{
+    body::Body,
+    routing::{get, post},
+    extract::{Extension},
+    Router,
+};
+use core::auth::User;
+use core::extract::{Json, Path, Query};
+use core::response::{ApiResponse, ApiErr};
+use core::api::types::blob::{BlobUploadResponse, CreateUploadRequest, UploadChunkRequest, UploadResponse};
+use anyhow::Result;
+use crate::service::upload;
+
+pub async fn create_upload(
+    Extension(user): Extension<User>,
+    Json(request): Json<CreateUploadRequest>,
+) -> Result<ApiResponse<UploadResponse>, ApiErr> {
+    Ok(ApiResponse::One(upload::create(user, request).await?))
+}
+
+pub async fn get_upload(
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<UploadResponse>, ApiErr> {
+    Ok(ApiResponse::One(upload::progress(user, id).await?))
+}
+
+pub async fn put_chunk(
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+    Query(request): Query<UploadChunkRequest>,
+    body: Body,
+) -> Result<ApiResponse<UploadResponse>, ApiErr> {
+    Ok(ApiResponse::One(upload::write_chunk(user, id, request.offset, body).await?))
+}
+
+pub async fn complete_upload(
+    Extension(user): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<BlobUploadResponse>, ApiErr> {
+    Ok(ApiResponse::One(upload::complete(user, id).await?))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/blobs/uploads", post(create_upload))
+        .route("/blobs/uploads/{id}", get(get_upload).put(put_chunk))
+        .route("/blobs/uploads/{id}/complete", post(complete_upload))
+}
@@ -9,21 +9,25 @@ pub mod service;
 pub mod repository;
 pub mod controller;
 pub mod common;
+pub mod store;
 pub mod test;
 
 use crate::repository::{db, rdb};
-use crate::common::router;
+use crate::common::{config, router};
 
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     core::log::init(Some(".pubchat_blob.log"));
-    auth::init();
-    rdb::init().await;
-    db::init().await;
+    let config = config::init();
+    auth::init(&config.auth);
+    rdb::init(&config.redis).await;
+    db::init(&config.database).await;
+    store::init(&config.storage, &config.s3);
+    service::gc::init(&config.expiry);
     let app = router::init().expect("路由模块初始化失败");
-    let listener = TcpListener::bind("0.0.0.0:3002").await.unwrap();
+    let listener = TcpListener::bind(&config.server.bind).await.unwrap();
     log::info!("listening on {}", listener.local_addr().unwrap());
     axum::serve(listener, app).await.unwrap();
     Ok(())
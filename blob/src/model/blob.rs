@@ -16,4 +16,7 @@ pub struct Blob {
     pub uid: i64, // Uploader user ID
     pub hash: Option<String>, // Content hash
     pub deleted: bool, // Soft delete flag
+    pub width: Option<i32>, // Image width in pixels
+    pub height: Option<i32>, // Image height in pixels
+    pub parent: Option<i64>, // Source blob of a derived blob such as a thumbnail
 }
\ No newline at end of file
@@ -1 +1,3 @@
-pub mod blob;
\ No newline at end of file
+pub mod blob;
+pub mod upload;
+pub mod object;
\ No newline at end of file
@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Object {
+    pub hash: String, // SHA-256, hex encoded
+    pub size: i64, // File size in bytes
+    pub refs: i64, // Number of blobs referencing this content, -1 while the content is being deleted
+    pub provider: String, // Storage provider (local, s3)
+    pub bucket: Option<String>, // Bucket name for s3
+    pub createtime: DateTime<Utc>,
+}
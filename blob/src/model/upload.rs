@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct Upload {
+    pub id: i64,
+    pub uid: i64, // Uploader user ID
+    pub name: String, // Client original filename
+    pub btype: String, // MIME type
+    pub size: i64, // Declared total size in bytes
+    pub received: i64, // Bytes written so far
+    pub hash: String, // Expected SHA-256, hex encoded
+    pub expire_days: Option<i32>, // Expiry chosen by the uploader
+    pub createtime: DateTime<Utc>,
+    pub updatetime: DateTime<Utc>,
+}
+
+impl Upload {
+    //正在完成的上传，已被一个完成请求占用，不能再写入分片或重复完成
+    pub const COMPLETING: i64 = -1;
+}
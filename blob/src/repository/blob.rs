@@ -1,46 +1,110 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use core::dispatch;
 use crate::model::blob::Blob;
 use crate::repository::db;
 
 
 pub async fn create_blob(req: Blob) -> Result<()> {
-    let mut connection = db::connection().await?;
+    let connection = db::connection().await?;
     // 插入会话记录
-    sqlx::query(r#"
+    dispatch!(connection, r#"
         INSERT INTO blobs (
-            id, name, path, size, btype, provider, bucket, open, exp, createtime, uid, hash, deleted
+            id, name, path, size, btype, provider, bucket, open, exp, createtime, uid, hash, deleted,
+            width, height, parent
         ) VALUES (
-            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
-        )"#)
-        .bind(&req.id)
-        .bind(&req.name)
-        .bind(&req.path)
-        .bind(&req.size)
-        .bind(&req.btype)
-        .bind(&req.provider)
-        .bind(&req.bucket)
-        .bind(&req.open)
-        .bind(&req.exp)
-        .bind(&req.createtime)
-        .bind(&req.uid)
-        .bind(&req.hash)
-        .bind(&req.deleted)
-        .execute(connection.as_mut())
-        .await?;
+            ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?
+        )"#, |sql, conn| {
+        sqlx::query(sql)
+            .bind(req.id)
+            .bind(&req.name)
+            .bind(&req.path)
+            .bind(req.size)
+            .bind(&req.btype)
+            .bind(&req.provider)
+            .bind(&req.bucket)
+            .bind(req.open)
+            .bind(req.exp)
+            .bind(req.createtime)
+            .bind(req.uid)
+            .bind(&req.hash)
+            .bind(req.deleted)
+            .bind(req.width)
+            .bind(req.height)
+            .bind(req.parent)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
 
 pub async fn get_blob_by_id(id: i64) -> Result<Option<Blob>> {
-    let mut connection = db::connection().await?;
-    let result = sqlx::query_as::<_, Blob>(r#"
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, r#"
         SELECT 
             id, name, path, size, btype, provider, bucket, open, exp, 
-            createtime, uid, hash, deleted
+            createtime, uid, hash, deleted, width, height, parent
         FROM blobs 
-        WHERE id = ? AND deleted = false
-        "#)
-        .bind(id)
-        .fetch_optional(connection.as_mut())
-        .await?;
+        WHERE id = ? AND deleted = FALSE
+        "#, |sql, conn| {
+        sqlx::query_as::<_, Blob>(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
     Ok(result)
-}
\ No newline at end of file
+}
+
+
+//逻辑删除，返回是否由本次请求删除
+pub async fn delete_blob(id: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let rows = dispatch!(connection, "UPDATE blobs SET deleted = TRUE WHERE id = ? AND deleted = FALSE", |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+//按ID顺序分页查询已过期且未删除的文件
+pub async fn find_expired(before: DateTime<Utc>, after: i64, limit: u32) -> Result<Vec<Blob>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, r#"
+        SELECT
+            id, name, path, size, btype, provider, bucket, open, exp,
+            createtime, uid, hash, deleted, width, height, parent
+        FROM blobs
+        WHERE deleted = FALSE AND exp < ? AND id > ?
+        ORDER BY id LIMIT ?
+        "#, |sql, conn| {
+        sqlx::query_as::<_, Blob>(sql)
+            .bind(before)
+            .bind(after)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//文件的缩略图等派生文件，按ID顺序
+pub async fn find_derived(parent: i64) -> Result<Vec<Blob>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, r#"
+        SELECT
+            id, name, path, size, btype, provider, bucket, open, exp,
+            createtime, uid, hash, deleted, width, height, parent
+        FROM blobs
+        WHERE parent = ? AND deleted = FALSE
+        ORDER BY id
+        "#, |sql, conn| {
+        sqlx::query_as::<_, Blob>(sql)
+            .bind(parent)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
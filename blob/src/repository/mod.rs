@@ -1,3 +1,5 @@
 pub mod db;
 pub mod rdb;
-pub mod blob;
\ No newline at end of file
+pub mod blob;
+pub mod upload;
+pub mod object;
\ No newline at end of file
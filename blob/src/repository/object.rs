@@ -0,0 +1,102 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::object::Object;
+use crate::repository::db;
+
+pub async fn get_object(hash: &str) -> Result<Option<Object>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM objects WHERE hash = ?", |sql, conn| {
+        sqlx::query_as::<_, Object>(sql)
+            .bind(hash)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//引用计数为DELETING的内容正在从存储中删除，删除完成后才删除记录
+pub const DELETING: i64 = -1;
+
+//内容已存在且未在删除时增加引用计数，返回是否成功
+pub async fn incr_refs(hash: &str) -> Result<bool> {
+    let connection = db::connection().await?;
+    let rows = dispatch!(connection, "UPDATE objects SET refs = refs + 1 WHERE hash = ? AND refs >= 0", |sql, conn| {
+        sqlx::query(sql)
+            .bind(hash)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+//新增内容记录，引用计数为1；并发写入同一内容时返回false
+pub async fn insert(hash: &str, size: i64, provider: &str, bucket: Option<&str>) -> Result<bool> {
+    let connection = db::connection().await?;
+    let sql = db::backend().insert_ignore("INTO objects (hash, size, refs, provider, bucket, createtime) VALUES (?, ?, 1, ?, ?, ?)");
+    let rows = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(hash)
+            .bind(size)
+            .bind(provider)
+            .bind(bucket)
+            .bind(chrono::Utc::now())
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+pub async fn decr_refs(hash: &str) -> Result<bool> {
+    let connection = db::connection().await?;
+    let rows = dispatch!(connection, "UPDATE objects SET refs = refs - 1 WHERE hash = ? AND refs > 0", |sql, conn| {
+        sqlx::query(sql)
+            .bind(hash)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+//将没有引用的内容标记为删除中，返回是否标记成功，标记成功的调用方负责删除文件
+//标记后记录仍然存在，同一内容的上传不会新增记录并写入文件，避免文件被删除
+pub async fn mark_deleting(hash: &str) -> Result<bool> {
+    let connection = db::connection().await?;
+    let rows = dispatch!(connection, "UPDATE objects SET refs = ? WHERE hash = ? AND refs = 0", |sql, conn| {
+        sqlx::query(sql)
+            .bind(DELETING)
+            .bind(hash)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+//文件删除失败时取消标记，内容仍可被再次引用
+pub async fn unmark_deleting(hash: &str) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "UPDATE objects SET refs = 0 WHERE hash = ? AND refs = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(hash)
+            .bind(DELETING)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+//文件删除完成后删除标记为删除中的记录
+pub async fn delete_marked(hash: &str) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "DELETE FROM objects WHERE hash = ? AND refs = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(hash)
+            .bind(DELETING)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
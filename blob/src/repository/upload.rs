@@ -0,0 +1,96 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::upload::Upload;
+use crate::repository::db;
+
+pub async fn create_upload(upload: &Upload) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, r#"
+        INSERT INTO uploads (id, uid, name, btype, size, received, hash, expire_days, createtime, updatetime)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        "#, |sql, conn| {
+        sqlx::query(sql)
+            .bind(upload.id)
+            .bind(upload.uid)
+            .bind(&upload.name)
+            .bind(&upload.btype)
+            .bind(upload.size)
+            .bind(upload.received)
+            .bind(&upload.hash)
+            .bind(upload.expire_days)
+            .bind(upload.createtime)
+            .bind(upload.updatetime)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn get_upload_by_id(id: i64) -> Result<Option<Upload>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM uploads WHERE id = ?", |sql, conn| {
+        sqlx::query_as::<_, Upload>(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//同一用户未完成的同一文件上传，不包括正在完成的上传
+pub async fn find_upload(uid: i64, hash: &str, size: i64) -> Result<Option<Upload>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, r#"
+        SELECT * FROM uploads WHERE uid = ? AND hash = ? AND size = ? AND received >= 0
+        ORDER BY createtime DESC LIMIT 1
+        "#, |sql, conn| {
+        sqlx::query_as::<_, Upload>(sql)
+            .bind(uid)
+            .bind(hash)
+            .bind(size)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//仅当已接收字节数仍为from时更新，并发写入同一位置时只有一个成功
+pub async fn update_received(id: i64, from: i64, to: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let rows = dispatch!(connection, "UPDATE uploads SET received = ?, updatetime = ? WHERE id = ? AND received = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(to)
+            .bind(chrono::Utc::now())
+            .bind(id)
+            .bind(from)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+pub async fn delete_upload(id: i64) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "DELETE FROM uploads WHERE id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+//超过before没有写入的未完成上传，按id分批查询
+pub async fn find_stale(before: chrono::DateTime<chrono::Utc>, after: i64, limit: u32) -> Result<Vec<Upload>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM uploads WHERE updatetime < ? AND id > ? ORDER BY id LIMIT ?", |sql, conn| {
+        sqlx::query_as::<_, Upload>(sql)
+            .bind(before)
+            .bind(after)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
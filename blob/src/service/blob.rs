@@ -1,80 +1,182 @@
 use anyhow::Result;
+use chrono::{DateTime, Utc};
 use base64::engine::general_purpose;
 use base64::Engine;
-use chrono::{Datelike};
 use core::extract::Multipart;
 use core::response::ApiErr;
-use std::path::{Path};
 use tokio::fs::{self, File};
 use tokio::io::AsyncWriteExt;
 use crate::model::blob::Blob;
-use crate::repository::blob::{create_blob, get_blob_by_id};
+use crate::repository::blob::{create_blob, delete_blob, find_derived, get_blob_by_id};
+use crate::service::object::{self, Content};
+use crate::service::thumbnail::{self, Image};
+use crate::service::upload::temp_path;
+use crate::store::{self, BlobStore, ByteStream, Store};
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
 use core::api::types::blob::{BlobResponse, BlobUploadResponse};
 use core::auth::User;
-
-
-#[cfg(target_os="linux")]
-const STORE_PATH: &str = "/home/data/images/";
-#[cfg(target_os="windows")]
-const STORE_PATH: &str = "D:/tmp/";
-
-//该路径对应nginx中location /blobs {}
-const ACCESS_PATH: &str = "/blobs/";
+use crate::common::config;
 
 pub async fn upload_file(
-    claims: User, mut multipart: Multipart
+    claims: User, expire_days: Option<u32>, mut multipart: Multipart
 ) -> Result<BlobUploadResponse> {
-    if let Some(field) = multipart.inner.next_field().await? {
+    let exp = expire_at(expire_days)?;
+    if let Some(mut field) = multipart.inner.next_field().await? {
         let origin_name = field.file_name();
         if origin_name.is_none(){
             return Err(ApiErr::Bad(400, "文件名缺失".to_string()).into());
         }
-        let now = chrono::Utc::now();
         let ctype = field.content_type().unwrap_or("unknown").to_string();
         let name = origin_name.unwrap().to_string();
-        let id = snowflaker::next_id()?;
-        let relative_path = format!("{}/{}/{}/", now.year(), now.month(), now.day());
-        //文件存储目录
-        let dir = Path::new(STORE_PATH)
-            .join(&relative_path);
-        // Create uploads directory if it doesn't exist
-        fs::create_dir_all(&dir).await?;
-        //最终文件存储路径
-        let abpath = dir.join(id.to_string());
-        let data = field.bytes().await?;
-        let size = data.len() as i64;
-        let mut file = File::create(&abpath).await?;
-        file.write_all(data.as_ref()).await?;
-        file.flush().await?;
-        log::info!("Length of `{}` is {} bytes", &abpath.display(), size);
-        let exp = now + chrono::Duration::days(7);
-        // Save blob metadata to database
-        let create_req = Blob {
-            id: id as i64,
-            name: name.clone(),
-            path: format!("{}{}{}", ACCESS_PATH, relative_path, id),
-            size,
-            btype: ctype,
-            provider: "local".to_string(),
-            bucket: None,
-            open: false,
-            exp: Some(exp),
-            uid: claims.id,
-            hash: None, // In a real implementation, we would calculate a hash
-            deleted: false,
-            createtime: now,
+        let id = snowflaker::next_id()? as i64;
+        //先写入临时文件，边写边计算SHA-256，不把整个文件读入内存
+        let temp = temp_path(id);
+        if let Some(dir) = temp.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        //写入或存储失败时删除临时文件，存储成功后临时文件已被移走
+        let stored = async {
+            let mut file = File::create(&temp).await?;
+            let mut hasher = Sha256::new();
+            let mut size = 0i64;
+            while let Some(chunk) = field.chunk().await? {
+                file.write_all(chunk.as_ref()).await?;
+                hasher.update(&chunk);
+                size += chunk.len() as i64;
+            }
+            file.flush().await?;
+            let hash = object::hex(hasher);
+            log::info!("Length of `{}` is {} bytes, sha256 {}", name, size, hash);
+            let image = thumbnail::inspect(&temp).await;
+            anyhow::Ok((object::store(&temp, hash, size).await?, image))
+        }.await;
+        let (content, image) = match stored {
+            Ok(stored) => stored,
+            Err(e) => {
+                if let Err(e) = object::remove_file(&temp).await {
+                    log::error!("Failed to remove temp file {}: {}", temp.display(), e);
+                }
+                return Err(e);
+            }
         };
-        create_blob(create_req).await?;
-        return Ok(BlobUploadResponse{
-            id: id as i64, 
-            name: name,
-            exp: Some(format!("{}", exp.format("%Y-%m-%d"))),
-            size: size as i64,
-        });
+        return save_blob(claims.id, id, name, ctype, content, exp, image).await;
     }
     Err(ApiErr::Bad(400, "文件信息缺失".to_string()).into())
 }
 
+//文件的过期时间，未指定天数时使用默认值，超出允许范围时返回400
+pub fn expire_at(days: Option<u32>) -> Result<DateTime<Utc>> {
+    let expiry = &config::get().expiry;
+    let days = days.unwrap_or(expiry.days);
+    if days == 0 || days > expiry.max_days {
+        return Err(ApiErr::Bad(400, format!("有效期应为1到{}天", expiry.max_days)).into());
+    }
+    Ok(Utc::now() + chrono::Duration::days(days as i64))
+}
+
+//内容已存为对象后，保存文件信息；图片记录宽高并保存缩略图，类型以识别结果为准
+pub(crate) async fn save_blob(
+    uid: i64, id: i64, name: String, btype: String, content: Content, exp: DateTime<Utc>, image: Option<Image>,
+) -> Result<BlobUploadResponse> {
+    let btype = image.as_ref().map(|i| i.mime.to_string()).unwrap_or(btype);
+    let hash = content.hash.clone();
+    let mut blob = new_blob(uid, id, name, btype, content, Some(exp));
+    if let Some(image) = &image {
+        blob.width = Some(image.width as i32);
+        blob.height = Some(image.height as i32);
+    }
+    //记录写入失败时释放存储内容时增加的引用
+    if let Err(e) = create_blob(blob.clone()).await {
+        if let Err(e) = object::release(&hash).await {
+            log::error!("Failed to release object {}: {}", hash, e);
+        }
+        return Err(e);
+    }
+    let thumbnails = match image {
+        Some(image) => thumbnail::save(&blob, image.thumbnails).await,
+        None => vec![],
+    };
+    Ok(BlobUploadResponse{
+        id,
+        name: blob.name,
+        exp: Some(format!("{}", exp.format("%Y-%m-%d"))),
+        size: blob.size,
+        width: blob.width.map(|w| w as u32),
+        height: blob.height.map(|h| h as u32),
+        thumbnails,
+    })
+}
+
+//已存为对象的内容对应的文件记录
+pub(crate) fn new_blob(
+    uid: i64, id: i64, name: String, btype: String, content: Content, exp: Option<DateTime<Utc>>,
+) -> Blob {
+    let storage = &config::get().storage;
+    let Content { hash, size, store } = content;
+    Blob {
+        id,
+        name,
+        path: match store {
+            //访问路径对应nginx中location /blobs {}
+            Store::Local(_) => format!("{}{}", storage.access_path, object::relative_path(&hash)),
+            Store::S3(_) => object::relative_path(&hash),
+        },
+        size,
+        btype,
+        provider: store.provider().to_string(),
+        bucket: store.bucket().map(String::from),
+        open: false,
+        exp,
+        uid,
+        hash: Some(hash),
+        deleted: false,
+        createtime: Utc::now(),
+        width: None,
+        height: None,
+        parent: None,
+    }
+}
+
+//删除自己上传的文件，内容的最后一个引用删除时才删除内容
+pub async fn delete(claims: User, id: i64) -> Result<()> {
+    let b = get_blob_by_id(id).await?
+        .ok_or(ApiErr::Bad(404, "文件不存在".to_string()))?;
+    if b.uid != claims.id {
+        return Err(ApiErr::Bad(403, "只能删除自己上传的文件".to_string()).into());
+    }
+    remove(&b).await?;
+    Ok(())
+}
+
+//标记删除文件及其缩略图并释放内容，返回删除的文件数和释放的字节数；已被删除的文件不计入
+pub(crate) async fn remove(b: &Blob) -> Result<(u64, u64)> {
+    if !delete_blob(b.id).await? {
+        return Ok((0, 0));
+    }
+    let (mut blobs, mut bytes) = (1, release(b).await?);
+    for derived in find_derived(b.id).await? {
+        if delete_blob(derived.id).await? {
+            blobs += 1;
+            bytes += release(&derived).await?;
+        }
+    }
+    Ok((blobs, bytes))
+}
+
+//释放已标记删除的文件的内容，返回释放的字节数
+async fn release(b: &Blob) -> Result<u64> {
+    match &b.hash {
+        Some(hash) => object::release(hash).await,
+        //去重之前上传的文件独占存储的内容
+        None => {
+            let (store, key) = locate(b)?;
+            store.delete(&key).await?;
+            Ok(b.size as u64)
+        }
+    }
+}
+
 pub async fn get_blob(id: i64) -> Result<BlobResponse> {
     let opt: Option<Blob> = get_blob_by_id(id).await?;
     if opt.is_none() {
@@ -84,24 +186,141 @@ pub async fn get_blob(id: i64) -> Result<BlobResponse> {
     if b.exp.is_some() && b.exp.unwrap() < chrono::Utc::now() {
         return Err(ApiErr::Bad(410, "文件已过期".to_string()).into());
     }
-    let key = dotenv::var("ACCESS_PRIVATE_KEY")?;
+    let storage = &config::get().storage;
     let now = chrono::Utc::now();
-    let exp = now + chrono::Duration::minutes(30);
-    let exp = exp.timestamp();
-    let path = b.path;
-    let sign_content = format!("{}{}{}", exp, path, key);
-    log::info!("sign content: {}", sign_content);
+    let exp = now + chrono::Duration::seconds(storage.link_ttl_secs as i64);
+    //存储支持预签名时直接从存储下载，否则通过blob服务签名下载
+    let (store, key) = locate(&b)?;
+    let path = match store.presign(&key, std::time::Duration::from_secs(storage.link_ttl_secs)) {
+        Some(url) => url,
+        None => {
+            let exp = exp.timestamp();
+            let path = content_path(b.id);
+            let token = sign(&path, exp, &storage.access_key);
+            format!("{}?token={}&expire={}", path, token, exp)
+        }
+    };
+    let thumbnails = find_derived(b.id).await?.iter().map(|t| t.id).collect();
+    Ok(BlobResponse{id: b.id, name: b.name, size: b.size, 
+        exp: b.exp.map(|e| format!("{}", e.format("%Y-%m-%d"))),
+        path: path,
+        hash: b.hash,
+        width: b.width.map(|w| w as u32),
+        height: b.height.map(|h| h as u32),
+        thumbnails,
+    })
+}
+
+//文件内容的下载路径，相对于blob服务地址
+fn content_path(id: i64) -> String {
+    format!("/blobs/{}/content", id)
+}
+
+//与nginx secure_link一致的签名：base64url(md5(expire + path + key))
+fn sign(path: &str, expire: i64, key: &str) -> String {
+    let sign_content = format!("{}{}{}", expire, path, key);
     let md5_bytes = md5::compute(sign_content.as_bytes());
     // Base64 URL 安全编码
     let b64 = general_purpose::STANDARD.encode(md5_bytes.as_slice());
-    let token = b64
+    b64
         .replace('+', "-")
         .replace('/', "_")
         .trim_end_matches('=')
-        .to_string();
-    let path = format!("{}?token={}&expire={}", path, token, exp);
-    Ok(BlobResponse{id: b.id, name: b.name, size: b.size, 
-        exp: b.exp.map(|e| format!("{}", e.format("%Y-%m-%d"))),
-        path: path
-    })
-}
\ No newline at end of file
+        .to_string()
+}
+
+//校验下载链接的签名和有效期，返回文件信息
+pub async fn open_content(id: i64, token: &str, expire: i64) -> Result<Blob> {
+    let storage = &config::get().storage;
+    //按固定时间比较，避免通过响应时间逐字节猜出签名
+    let expected = sign(&content_path(id), expire, &storage.access_key);
+    if !bool::from(expected.as_bytes().ct_eq(token.as_bytes())) {
+        return Err(ApiErr::Bad(403, "下载链接签名无效".to_string()).into());
+    }
+    if expire < chrono::Utc::now().timestamp() {
+        return Err(ApiErr::Bad(403, "下载链接已过期".to_string()).into());
+    }
+    let b = get_blob_by_id(id).await?
+        .ok_or(ApiErr::Bad(404, "文件不存在".to_string()))?;
+    if b.exp.is_some_and(|e| e < chrono::Utc::now()) {
+        return Err(ApiErr::Bad(410, "文件已过期".to_string()).into());
+    }
+    Ok(b)
+}
+
+//存储中文件内容的实际字节数，Range请求按该长度计算区间
+pub async fn content_size(b: &Blob) -> Result<u64> {
+    let (store, key) = locate(b)?;
+    store.size(&key).await
+}
+
+//读取文件内容中从start开始的len个字节
+pub async fn read_content(b: &Blob, start: u64, len: u64) -> Result<ByteStream> {
+    let (store, key) = locate(b)?;
+    store.range(&key, start, len).await
+}
+
+//文件内容所在的存储及key，本地存储的path为access_path下的访问路径，去掉前缀即为存储目录下的相对路径
+fn locate(b: &Blob) -> Result<(Store, String)> {
+    let store = store::get(&b.provider, b.bucket.as_deref())?;
+    let key = match store {
+        Store::Local(_) => b.path.strip_prefix(&config::get().storage.access_path).unwrap_or(&b.path),
+        Store::S3(_) => &b.path,
+    };
+    Ok((store, key.to_string()))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    //返回整个文件
+    Full,
+    //闭区间[start, end]
+    Partial(u64, u64),
+    //起始位置超出文件大小
+    Unsatisfiable,
+}
+
+//解析Range请求头，只支持单个字节区间，多区间或格式错误时按整个文件返回
+pub fn parse_range(header: Option<&str>, size: u64) -> ByteRange {
+    let spec = match header.and_then(|h| h.trim().strip_prefix("bytes=")) {
+        Some(spec) if !spec.contains(',') => spec.trim(),
+        _ => return ByteRange::Full,
+    };
+    let Some((start, end)) = spec.split_once('-') else {
+        return ByteRange::Full;
+    };
+    let (start, end) = (start.trim(), end.trim());
+    if start.is_empty() {
+        //bytes=-N，最后N个字节
+        return match end.parse::<u64>() {
+            Ok(0) => ByteRange::Unsatisfiable,
+            Ok(_) if size == 0 => ByteRange::Unsatisfiable,
+            Ok(n) => ByteRange::Partial(size.saturating_sub(n), size - 1),
+            Err(_) => ByteRange::Full,
+        };
+    }
+    let Ok(start) = start.parse::<u64>() else {
+        return ByteRange::Full;
+    };
+    let end = if end.is_empty() {
+        u64::MAX
+    } else {
+        match end.parse::<u64>() {
+            Ok(end) if end >= start => end,
+            _ => return ByteRange::Full,
+        }
+    };
+    if start >= size {
+        return ByteRange::Unsatisfiable;
+    }
+    ByteRange::Partial(start, end.min(size - 1))
+}
+
+//下载文件名，filename为ASCII回退，filename*为UTF-8编码的原始文件名
+pub fn content_disposition(name: &str) -> String {
+    let fallback: String = name.chars()
+        .map(|c| if (c.is_ascii_graphic() && c != '"' && c != '\\') || c == ' ' { c } else { '_' })
+        .collect();
+    let encoded = percent_encoding::utf8_percent_encode(name, percent_encoding::NON_ALPHANUMERIC);
+    format!("attachment; filename=\"{}\"; filename*=UTF-8''{}", fallback, encoded)
+}
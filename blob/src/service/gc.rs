@@ -0,0 +1,117 @@
+//! 过期文件回收
+//!
+//! 上传时设置的有效期到期后，后台任务按批将blobs记录标记为删除并释放内容引用，
+//! 内容的最后一个引用释放时删除存储中的内容；同时删除放弃的分片上传。管理员也可以手动触发回收或试运行统计。
+
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use core::api::types::blob::GcResponse;
+use core::auth::{User, SUPER_ADMIN};
+use core::config::ExpiryConfig;
+use core::lease;
+use core::response::ApiErr;
+use tokio::sync::Mutex;
+use crate::common::config;
+use crate::repository::blob::find_expired;
+use crate::repository::db;
+use crate::repository::object::get_object;
+use crate::service::blob::remove;
+use crate::service::upload::purge_stale;
+
+//回收任务的租约名称，多实例部署时同一时间只有一个实例执行回收
+const GC_LEASE: &str = "blob_gc";
+
+//租约的持有者按进程区分，同一进程内的定时任务和手动触发由互斥锁保证不同时执行
+static RUNNING: Mutex<()> = Mutex::const_new(());
+
+//启动后台回收任务
+pub fn init(config: &'static ExpiryConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        loop {
+            interval.tick().await;
+            match exclusive(config, false).await {
+                Ok(Some(_)) => {}
+                Ok(None) => log::debug!("blob gc is running on another instance, skipped"),
+                Err(e) => log::error!("Failed to collect expired blobs: {}", e),
+            }
+        }
+    });
+}
+
+//管理员手动回收，dry_run为true时只统计
+pub async fn trigger(claims: &User, dry_run: bool) -> Result<GcResponse> {
+    if claims.id != SUPER_ADMIN {
+        return Err(ApiErr::Bad(403, "只有超级管理员可以回收过期文件".to_string()).into());
+    }
+    exclusive(&config::get().expiry, dry_run).await?
+        .ok_or(ApiErr::Bad(409, "其他实例正在回收过期文件，请稍后再试".to_string()).into())
+}
+
+//持有租约时执行回收，执行完成后释放租约，其他实例正在回收时返回None
+async fn exclusive(config: &ExpiryConfig, dry_run: bool) -> Result<Option<GcResponse>> {
+    let _running = RUNNING.lock().await;
+    let pool = db::get().await?;
+    //实例在回收过程中退出时，租约到期后其他实例可以继续回收
+    let ttl = Duration::from_secs(config.interval_secs.saturating_mul(2));
+    if !lease::acquire(pool, GC_LEASE, ttl).await? {
+        return Ok(None);
+    }
+    let report = collect(config, dry_run).await;
+    if let Err(e) = lease::release(pool, GC_LEASE).await {
+        log::error!("Failed to release blob gc lease: {}", e);
+    }
+    report.map(Some)
+}
+
+//分批删除过期文件并释放内容，返回删除的文件数和释放的字节数
+pub async fn collect(config: &ExpiryConfig, dry_run: bool) -> Result<GcResponse> {
+    let start = Instant::now();
+    let now = Utc::now();
+    let mut report = GcResponse { dry_run, blobs: 0, bytes: 0, uploads: 0 };
+    //试运行时按内容统计过期的引用数，最后与引用计数比较
+    let mut expired: HashMap<String, i64> = HashMap::new();
+    let mut after = 0;
+    loop {
+        let blobs = find_expired(now, after, config.batch_size).await?;
+        let Some(last) = blobs.last() else {
+            break;
+        };
+        after = last.id;
+        for b in &blobs {
+            if dry_run {
+                report.blobs += 1;
+                match &b.hash {
+                    Some(hash) => *expired.entry(hash.clone()).or_default() += 1,
+                    None => report.bytes += b.size as u64,
+                }
+                continue;
+            }
+            //单个文件失败不影响其他文件，下次回收时重试
+            match remove(b).await {
+                Ok((blobs, bytes)) => {
+                    report.blobs += blobs;
+                    report.bytes += bytes;
+                }
+                Err(e) => log::error!("Failed to remove expired blob {}: {}", b.id, e),
+            }
+        }
+        if (blobs.len() as u32) < config.batch_size {
+            break;
+        }
+    }
+    for (hash, count) in expired {
+        if let Some(o) = get_object(&hash).await? && o.refs <= count {
+            report.bytes += o.size as u64;
+        }
+    }
+    let (uploads, bytes) = purge_stale(config.batch_size, dry_run).await?;
+    report.uploads = uploads;
+    report.bytes += bytes;
+    log::info!("blob gc finished: dry_run={} blobs={} uploads={} bytes={} elapsed={:?}",
+        dry_run, report.blobs, report.uploads, report.bytes, start.elapsed());
+    Ok(report)
+}
@@ -1 +1,5 @@
-pub mod blob;
\ No newline at end of file
+pub mod blob;
+pub mod upload;
+pub mod object;
+pub mod gc;
+pub mod thumbnail;
\ No newline at end of file
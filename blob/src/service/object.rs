@@ -0,0 +1,123 @@
+//! 按内容去重的文件存储
+//!
+//! 上传的文件按SHA-256存放在存储的`objects/<前两位>/<hash>`，相同内容只保存一份，
+//! 每次上传仍各自保存一条blobs记录。objects表记录内容被引用的次数，以及内容所在的存储位置，最后一个引用删除时才删除内容。
+//! 删除内容时先将记录标记为删除中，文件删除后再删除记录；期间上传的相同内容等待删除完成后重新写入。
+
+use anyhow::Result;
+use core::response::ApiErr;
+use sha2::{Digest, Sha256};
+use std::path::Path;
+use std::time::Duration;
+use tokio::fs::{self, File};
+use tokio::io::AsyncReadExt;
+use crate::repository::object;
+use crate::store::{self, BlobStore, Store};
+
+//等待同一内容删除完成的轮询间隔及次数
+const DELETING_POLL: Duration = Duration::from_millis(100);
+const DELETING_RETRIES: u32 = 50;
+
+//内容在存储中的key
+pub(crate) fn relative_path(hash: &str) -> String {
+    format!("objects/{}/{}", &hash[..2], hash)
+}
+
+//内容所在的存储
+async fn location(hash: &str) -> Result<Store> {
+    match object::get_object(hash).await? {
+        Some(o) => store::get(&o.provider, o.bucket.as_deref()),
+        None => store::current(),
+    }
+}
+
+//已保存的内容及其所在的存储
+pub(crate) struct Content {
+    pub hash: String,
+    pub size: i64,
+    pub store: Store,
+}
+
+//将已写完的临时文件存为内容对象，内容已存在时只增加引用计数并删除临时文件
+pub(crate) async fn store(temp: &Path, hash: String, size: i64) -> Result<Content> {
+    let mut retries = 0;
+    loop {
+        if object::incr_refs(&hash).await? {
+            fs::remove_file(temp).await?;
+            let store = location(&hash).await?;
+            return Ok(Content { hash, size, store });
+        }
+        //相同内容正在删除，写入的文件可能被删除，等待删除完成
+        if !deleting(&hash).await? {
+            break;
+        }
+        retries += 1;
+        if retries > DELETING_RETRIES {
+            return Err(ApiErr::Bad(409, "相同内容正在删除，请稍后重新上传".to_string()).into());
+        }
+        tokio::time::sleep(DELETING_POLL).await;
+    }
+    let store = store::current()?;
+    store.put(&relative_path(&hash), temp, size as u64).await?;
+    if object::insert(&hash, size, store.provider(), store.bucket()).await? {
+        return Ok(Content { hash, size, store });
+    }
+    //并发上传同一内容时另一个请求已写入记录，内容相同，直接引用
+    if object::incr_refs(&hash).await? {
+        let store = location(&hash).await?;
+        return Ok(Content { hash, size, store });
+    }
+    //写入文件后该记录又被标记删除，写入的文件可能已被删除
+    Err(ApiErr::Bad(409, "相同内容正在删除，请稍后重新上传".to_string()).into())
+}
+
+async fn deleting(hash: &str) -> Result<bool> {
+    Ok(object::get_object(hash).await?.is_some_and(|o| o.refs == object::DELETING))
+}
+
+//释放一个引用，没有引用时删除内容，返回释放的字节数
+pub(crate) async fn release(hash: &str) -> Result<u64> {
+    let Some(o) = object::get_object(hash).await? else {
+        return Ok(0);
+    };
+    if !(object::decr_refs(hash).await? && object::mark_deleting(hash).await?) {
+        return Ok(0);
+    }
+    let deleted = async {
+        store::get(&o.provider, o.bucket.as_deref())?.delete(&relative_path(hash)).await
+    }.await;
+    if let Err(e) = deleted {
+        object::unmark_deleting(hash).await?;
+        return Err(e);
+    }
+    object::delete_marked(hash).await?;
+    Ok(o.size as u64)
+}
+
+//删除文件，文件不存在时忽略
+pub(crate) async fn remove_file(path: &Path) -> Result<()> {
+    match fs::remove_file(path).await {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+//十六进制的SHA-256
+pub(crate) fn hex(hasher: Sha256) -> String {
+    hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+//分块读取计算文件的SHA-256
+pub(crate) async fn sha256_file(path: &Path) -> Result<String> {
+    let mut file = File::open(path).await?;
+    let mut hasher = Sha256::new();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        let n = file.read(&mut buf).await?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buf[..n]);
+    }
+    Ok(hex(hasher))
+}
@@ -0,0 +1,136 @@
+//! 图片识别与缩略图
+//!
+//! 上传的文件按内容识别真实的图片类型并记录宽高，再按`thumbnail.sizes`生成缩略图。
+//! 缩略图作为派生文件保存为独立的blobs记录(parent为原图ID)，与原图同时过期，删除原图时一并删除。
+
+use std::io::Cursor;
+use std::path::Path;
+
+use anyhow::Result;
+use core::config::ThumbnailConfig;
+use image::metadata::Orientation;
+use image::{DynamicImage, ImageDecoder, ImageFormat, ImageReader};
+use sha2::{Digest, Sha256};
+use tokio::fs;
+use crate::common::config;
+use crate::model::blob::Blob;
+use crate::repository::blob::create_blob;
+use crate::service::blob::new_blob;
+use crate::service::object;
+use crate::service::upload::temp_path;
+
+//识别出的图片
+pub(crate) struct Image {
+    pub mime: &'static str,
+    //按EXIF方向旋转后的宽高
+    pub width: u32,
+    pub height: u32,
+    //按尺寸从小到大排列
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+pub(crate) struct Thumbnail {
+    pub edge: u32,
+    pub width: u32,
+    pub height: u32,
+    pub format: ImageFormat,
+    pub data: Vec<u8>,
+}
+
+//识别图片并生成缩略图，不是图片或无法解码时返回None
+pub(crate) async fn inspect(path: &Path) -> Option<Image> {
+    let config = config::get().thumbnail.clone();
+    let path = path.to_path_buf();
+    //解码和缩放占用CPU，不在异步线程中执行
+    match tokio::task::spawn_blocking(move || decode(&path, &config)).await {
+        Ok(Ok(image)) => image,
+        Ok(Err(e)) => {
+            log::warn!("Failed to decode image: {}", e);
+            None
+        }
+        Err(e) => {
+            log::error!("Image decoding task failed: {}", e);
+            None
+        }
+    }
+}
+
+pub(crate) fn decode(path: &Path, config: &ThumbnailConfig) -> Result<Option<Image>> {
+    //按文件头识别类型，不信任客户端声明的类型和扩展名
+    let reader = ImageReader::open(path)?.with_guessed_format()?;
+    let Some(format) = reader.format().filter(|f| f.reading_enabled()) else {
+        return Ok(None);
+    };
+    let mut decoder = reader.into_decoder()?;
+    let orientation = decoder.orientation().unwrap_or(Orientation::NoTransforms);
+    let (mut width, mut height) = decoder.dimensions();
+    if matches!(orientation, Orientation::Rotate90 | Orientation::Rotate270
+        | Orientation::Rotate90FlipH | Orientation::Rotate270FlipH) {
+        std::mem::swap(&mut width, &mut height);
+    }
+    let mut image = Image { mime: format.to_mime_type(), width, height, thumbnails: vec![] };
+    let mut edges: Vec<u32> = config.sizes.iter().copied().filter(|&e| e < width.max(height)).collect();
+    edges.sort_unstable();
+    edges.dedup();
+    if edges.is_empty() || width as u64 * height as u64 > config.max_pixels {
+        return Ok(Some(image));
+    }
+    let mut source = DynamicImage::from_decoder(decoder)?;
+    source.apply_orientation(orientation);
+    //有透明通道的图片用PNG，其他用JPEG
+    let format = if source.color().has_alpha() { ImageFormat::Png } else { ImageFormat::Jpeg };
+    for edge in edges {
+        let thumbnail = source.thumbnail(edge, edge);
+        let thumbnail = match format {
+            ImageFormat::Png => DynamicImage::ImageRgba8(thumbnail.to_rgba8()),
+            _ => DynamicImage::ImageRgb8(thumbnail.to_rgb8()),
+        };
+        let mut data = Cursor::new(Vec::new());
+        thumbnail.write_to(&mut data, format)?;
+        image.thumbnails.push(Thumbnail {
+            edge,
+            width: thumbnail.width(),
+            height: thumbnail.height(),
+            format,
+            data: data.into_inner(),
+        });
+    }
+    Ok(Some(image))
+}
+
+//保存原图的缩略图，返回保存成功的缩略图ID；缩略图失败不影响原图上传
+pub(crate) async fn save(source: &Blob, thumbnails: Vec<Thumbnail>) -> Vec<i64> {
+    let mut ids = vec![];
+    for thumbnail in thumbnails {
+        let edge = thumbnail.edge;
+        match save_one(source, thumbnail).await {
+            Ok(id) => ids.push(id),
+            Err(e) => log::warn!("Failed to save {}px thumbnail of blob {}: {}", edge, source.id, e),
+        }
+    }
+    ids
+}
+
+async fn save_one(source: &Blob, thumbnail: Thumbnail) -> Result<i64> {
+    let id = snowflaker::next_id()? as i64;
+    let temp = temp_path(id);
+    if let Some(dir) = temp.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    fs::write(&temp, &thumbnail.data).await?;
+    let hash = object::hex(Sha256::new_with_prefix(&thumbnail.data));
+    let content = object::store(&temp, hash, thumbnail.data.len() as i64).await?;
+    let name = name(&source.name, thumbnail.edge, thumbnail.format);
+    let mut blob = new_blob(source.uid, id, name, thumbnail.format.to_mime_type().to_string(), content, source.exp);
+    blob.width = Some(thumbnail.width as i32);
+    blob.height = Some(thumbnail.height as i32);
+    blob.parent = Some(source.id);
+    create_blob(blob).await?;
+    Ok(id)
+}
+
+//缩略图文件名，如photo.heic的128像素缩略图为photo_128.jpg
+pub fn name(source: &str, edge: u32, format: ImageFormat) -> String {
+    let stem = Path::new(source).file_stem().and_then(|s| s.to_str()).unwrap_or("thumbnail");
+    format!("{}_{}.{}", stem, edge, format.extensions_str()[0])
+}
@@ -0,0 +1,201 @@
+//! 分片上传
+//!
+//! 客户端先创建上传并声明文件大小和SHA-256，再按偏移量顺序PUT分片，分片直接追加写入临时文件；
+//! 连接中断后查询已接收的字节数从该位置继续上传。全部接收后校验SHA-256，通过则转存为正式文件。
+//! 完成时先将已接收字节数改为`Upload::COMPLETING`占用上传，并发的完成请求只有一个成功，失败时恢复。
+//! 超过`upload.stale_hours`没有写入的上传视为放弃，由回收任务删除记录及临时文件。
+
+use anyhow::Result;
+use axum::body::Body;
+use core::api::types::blob::{BlobUploadResponse, CreateUploadRequest, UploadResponse};
+use core::auth::User;
+use core::response::ApiErr;
+use futures::StreamExt;
+use std::path::{Path, PathBuf};
+use tokio::fs::{self, File, OpenOptions};
+use tokio::io::{AsyncSeekExt, AsyncWriteExt};
+use crate::common::config;
+use crate::model::upload::Upload;
+use crate::repository::upload::{create_upload, delete_upload, find_stale, find_upload, get_upload_by_id, update_received};
+use crate::service::blob::{expire_at, save_blob};
+use crate::service::object::{self, sha256_file};
+use crate::service::thumbnail;
+
+impl From<Upload> for UploadResponse {
+    fn from(u: Upload) -> Self {
+        UploadResponse { id: u.id, name: u.name, size: u.size, received: u.received }
+    }
+}
+
+//未完成上传的临时文件
+pub(crate) fn temp_path(id: i64) -> PathBuf {
+    PathBuf::from(&config::get().storage.path).join("uploads").join(id.to_string())
+}
+
+pub async fn create(claims: User, req: CreateUploadRequest) -> Result<UploadResponse> {
+    if req.name.trim().is_empty() {
+        return Err(ApiErr::Bad(400, "文件名缺失".to_string()).into());
+    }
+    if req.size < 0 {
+        return Err(ApiErr::Bad(400, "文件大小不合法".to_string()).into());
+    }
+    let max_size = config::get().upload.max_size;
+    if req.size as u64 > max_size {
+        return Err(ApiErr::Bad(413, format!("文件大小不能超过{}字节", max_size)).into());
+    }
+    if req.hash.len() != 64 || !req.hash.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(ApiErr::Bad(400, "hash应为十六进制的SHA-256".to_string()).into());
+    }
+    let hash = req.hash.to_ascii_lowercase();
+    expire_at(req.expire_days)?;
+    //同一文件的未完成上传直接续传
+    if let Some(upload) = find_upload(claims.id, &hash, req.size).await? {
+        return Ok(upload.into());
+    }
+    let now = chrono::Utc::now();
+    let upload = Upload {
+        id: snowflaker::next_id()? as i64,
+        uid: claims.id,
+        name: req.name,
+        btype: req.btype.unwrap_or("application/octet-stream".to_string()),
+        size: req.size,
+        received: 0,
+        hash,
+        expire_days: req.expire_days.map(|d| d as i32),
+        createtime: now,
+        updatetime: now,
+    };
+    let path = temp_path(upload.id);
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).await?;
+    }
+    File::create(&path).await?;
+    create_upload(&upload).await?;
+    Ok(upload.into())
+}
+
+//只能访问自己创建的上传
+async fn get_own(claims: &User, id: i64) -> Result<Upload> {
+    match get_upload_by_id(id).await? {
+        Some(upload) if upload.uid == claims.id => Ok(upload),
+        _ => Err(ApiErr::Bad(404, "上传不存在".to_string()).into()),
+    }
+}
+
+pub async fn progress(claims: User, id: i64) -> Result<UploadResponse> {
+    Ok(get_own(&claims, id).await?.into())
+}
+
+//将请求体作为分片写入offset处，写入过程中连接中断时保留已写入的部分
+pub async fn write_chunk(claims: User, id: i64, offset: i64, body: Body) -> Result<UploadResponse> {
+    let upload = get_own(&claims, id).await?;
+    if upload.received == Upload::COMPLETING {
+        return Err(ApiErr::Bad(409, "上传正在完成".to_string()).into());
+    }
+    if offset != upload.received {
+        return Err(ApiErr::Bad(409, format!("分片偏移量应为{}", upload.received)).into());
+    }
+    let mut file = OpenOptions::new().write(true).open(temp_path(id)).await?;
+    //丢弃上次中断时写入但未记录的字节
+    file.set_len(offset as u64).await?;
+    file.seek(std::io::SeekFrom::Start(offset as u64)).await?;
+    let mut received = offset;
+    let mut stream = body.into_data_stream();
+    let mut interrupted = None;
+    while let Some(chunk) = stream.next().await {
+        let chunk = match chunk {
+            Ok(chunk) => chunk,
+            Err(e) => {
+                interrupted = Some(e);
+                break;
+            }
+        };
+        if received + chunk.len() as i64 > upload.size {
+            file.set_len(offset as u64).await?;
+            return Err(ApiErr::Bad(400, "分片超出文件大小".to_string()).into());
+        }
+        file.write_all(&chunk).await?;
+        received += chunk.len() as i64;
+    }
+    file.flush().await?;
+    if !update_received(id, offset, received).await? {
+        return Err(ApiErr::Bad(409, "分片正在被其他请求写入".to_string()).into());
+    }
+    if let Some(e) = interrupted {
+        log::warn!("上传{}在{}字节处中断: {}", id, received, e);
+        return Err(e.into());
+    }
+    Ok(UploadResponse { id, name: upload.name, size: upload.size, received })
+}
+
+//校验SHA-256后存为内容对象，校验失败时清空已上传内容以便重新上传
+pub async fn complete(claims: User, id: i64) -> Result<BlobUploadResponse> {
+    let upload = get_own(&claims, id).await?;
+    if upload.received == Upload::COMPLETING {
+        return Err(ApiErr::Bad(409, "上传正在完成".to_string()).into());
+    }
+    if upload.received != upload.size {
+        return Err(ApiErr::Bad(400, format!("文件未上传完成，已接收{}/{}字节", upload.received, upload.size)).into());
+    }
+    if !update_received(id, upload.size, Upload::COMPLETING).await? {
+        return Err(ApiErr::Bad(409, "上传正在完成".to_string()).into());
+    }
+    let (path, size) = (temp_path(id), upload.size);
+    let result = finish(upload, &path).await;
+    if result.is_err() {
+        //临时文件仍完整时可以直接重试完成，否则需要重新上传
+        let received = match fs::metadata(&path).await {
+            Ok(meta) if meta.len() == size as u64 => size,
+            _ => {
+                File::create(&path).await?;
+                0
+            }
+        };
+        update_received(id, Upload::COMPLETING, received).await?;
+    }
+    result
+}
+
+//已占用的上传存为内容对象及文件记录，成功后删除上传记录
+async fn finish(upload: Upload, path: &Path) -> Result<BlobUploadResponse> {
+    if sha256_file(path).await? != upload.hash {
+        File::create(path).await?;
+        return Err(ApiErr::Bad(422, "文件校验失败，请重新上传".to_string()).into());
+    }
+    //创建上传后允许的最长天数可能已调小，按当前上限截断
+    let days = upload.expire_days.map(|d| (d.max(1) as u32).min(config::get().expiry.max_days));
+    let exp = expire_at(days)?;
+    let image = thumbnail::inspect(path).await;
+    let content = object::store(path, upload.hash, upload.size).await?;
+    let blob_id = snowflaker::next_id()? as i64;
+    let response = save_blob(upload.uid, blob_id, upload.name, upload.btype, content, exp, image).await?;
+    delete_upload(upload.id).await?;
+    Ok(response)
+}
+
+//删除放弃的上传及其临时文件，dry_run为true时只统计，返回上传数及临时文件的字节数
+pub(crate) async fn purge_stale(batch_size: u32, dry_run: bool) -> Result<(u64, u64)> {
+    let before = chrono::Utc::now() - chrono::Duration::hours(config::get().upload.stale_hours as i64);
+    let (mut count, mut bytes) = (0, 0);
+    let mut after = 0;
+    loop {
+        let uploads = find_stale(before, after, batch_size).await?;
+        let Some(last) = uploads.last() else {
+            break;
+        };
+        after = last.id;
+        for upload in &uploads {
+            //先删除记录，之后写入分片的请求找不到上传
+            if !dry_run {
+                delete_upload(upload.id).await?;
+                object::remove_file(&temp_path(upload.id)).await?;
+            }
+            count += 1;
+            bytes += upload.received as u64;
+        }
+        if (uploads.len() as u32) < batch_size {
+            break;
+        }
+    }
+    Ok((count, bytes))
+}
@@ -0,0 +1,71 @@
+use std::{io::{ErrorKind, SeekFrom}, path::{Path, PathBuf}, time::Duration};
+
+use anyhow::Result;
+use tokio::fs::{self, File};
+use tokio::io::{AsyncReadExt, AsyncSeekExt};
+use tokio_util::io::ReaderStream;
+
+use super::{not_found, BlobStore, ByteStream};
+
+//存放在本地目录，key为目录下的相对路径
+#[derive(Debug, Clone)]
+pub struct LocalStore {
+    root: PathBuf,
+}
+
+impl LocalStore {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    pub fn path(&self, key: &str) -> PathBuf {
+        self.root.join(key)
+    }
+
+    async fn open(&self, key: &str) -> Result<File> {
+        match File::open(self.path(key)).await {
+            Ok(file) => Ok(file),
+            Err(e) if e.kind() == ErrorKind::NotFound => Err(not_found()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+impl BlobStore for LocalStore {
+    //临时文件与存储目录在同一文件系统，直接移动
+    async fn put(&self, key: &str, file: &Path, _size: u64) -> Result<()> {
+        let path = self.path(key);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).await?;
+        }
+        fs::rename(file, &path).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream> {
+        Ok(Box::pin(ReaderStream::new(self.open(key).await?)))
+    }
+
+    async fn size(&self, key: &str) -> Result<u64> {
+        Ok(self.open(key).await?.metadata().await?.len())
+    }
+
+    async fn range(&self, key: &str, start: u64, len: u64) -> Result<ByteStream> {
+        let mut file = self.open(key).await?;
+        if start > 0 {
+            file.seek(SeekFrom::Start(start)).await?;
+        }
+        Ok(Box::pin(ReaderStream::new(file.take(len))))
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match fs::remove_file(self.path(key)).await {
+            Err(e) if e.kind() != ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    fn presign(&self, _key: &str, _ttl: Duration) -> Option<String> {
+        None
+    }
+}
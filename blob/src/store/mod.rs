@@ -0,0 +1,149 @@
+//! 文件内容存储
+//!
+//! 文件内容按key存放在[`BlobStore`]中，key为相对路径，如`objects/ab/<hash>`。
+//! [`LocalStore`]存放在`storage.path`目录下，[`S3Store`]存放在S3兼容的对象存储中。
+//! 新上传的内容存放在`storage.provider`指定的位置，objects表记录每份内容所在的位置，切换配置后已有文件仍从原位置读取。
+
+use std::{future::Future, io, path::Path, pin::Pin, sync::OnceLock, time::Duration};
+
+use anyhow::Result;
+use bytes::Bytes;
+use core::config::{S3Config, StorageConfig};
+use core::response::ApiErr;
+use futures::Stream;
+
+pub mod local;
+pub mod s3;
+
+pub use local::LocalStore;
+pub use s3::S3Store;
+
+//按块读取的文件内容
+pub type ByteStream = Pin<Box<dyn Stream<Item = io::Result<Bytes>> + Send>>;
+
+pub trait BlobStore {
+    //将已写完的临时文件存为key，成功后不再保留临时文件
+    fn put(&self, key: &str, file: &Path, size: u64) -> impl Future<Output = Result<()>> + Send;
+    //读取全部内容
+    fn get(&self, key: &str) -> impl Future<Output = Result<ByteStream>> + Send;
+    //内容的实际字节数
+    fn size(&self, key: &str) -> impl Future<Output = Result<u64>> + Send;
+    //读取从start开始的len个字节
+    fn range(&self, key: &str, start: u64, len: u64) -> impl Future<Output = Result<ByteStream>> + Send;
+    //删除内容，不存在时忽略
+    fn delete(&self, key: &str) -> impl Future<Output = Result<()>> + Send;
+    //有效期为ttl的直接下载地址，不支持时返回None，由blob服务签名下载
+    fn presign(&self, key: &str, ttl: Duration) -> Option<String>;
+}
+
+#[derive(Debug, Clone)]
+pub enum Store {
+    Local(LocalStore),
+    S3(S3Store),
+}
+
+impl Store {
+    pub fn provider(&self) -> &'static str {
+        match self {
+            Store::Local(_) => StorageConfig::LOCAL,
+            Store::S3(_) => StorageConfig::S3,
+        }
+    }
+
+    pub fn bucket(&self) -> Option<&str> {
+        match self {
+            Store::Local(_) => None,
+            Store::S3(store) => Some(store.bucket()),
+        }
+    }
+}
+
+impl BlobStore for Store {
+    async fn put(&self, key: &str, file: &Path, size: u64) -> Result<()> {
+        match self {
+            Store::Local(store) => store.put(key, file, size).await,
+            Store::S3(store) => store.put(key, file, size).await,
+        }
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream> {
+        match self {
+            Store::Local(store) => store.get(key).await,
+            Store::S3(store) => store.get(key).await,
+        }
+    }
+
+    async fn size(&self, key: &str) -> Result<u64> {
+        match self {
+            Store::Local(store) => store.size(key).await,
+            Store::S3(store) => store.size(key).await,
+        }
+    }
+
+    async fn range(&self, key: &str, start: u64, len: u64) -> Result<ByteStream> {
+        match self {
+            Store::Local(store) => store.range(key, start, len).await,
+            Store::S3(store) => store.range(key, start, len).await,
+        }
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        match self {
+            Store::Local(store) => store.delete(key).await,
+            Store::S3(store) => store.delete(key).await,
+        }
+    }
+
+    fn presign(&self, key: &str, ttl: Duration) -> Option<String> {
+        match self {
+            Store::Local(store) => store.presign(key, ttl),
+            Store::S3(store) => store.presign(key, ttl),
+        }
+    }
+}
+
+struct Stores {
+    local: LocalStore,
+    s3: Option<S3Store>,
+    provider: String,
+}
+
+static STORES: OnceLock<Stores> = OnceLock::new();
+
+pub fn init(storage: &StorageConfig, s3: &S3Config) {
+    STORES.get_or_init(|| Stores {
+        local: LocalStore::new(&storage.path),
+        s3: s3.is_configured().then(|| S3Store::new(s3)),
+        provider: storage.provider.clone(),
+    });
+}
+
+fn stores() -> &'static Stores {
+    STORES.get().expect("文件存储未初始化")
+}
+
+//新上传的内容使用的存储
+pub fn current() -> Result<Store> {
+    get(&stores().provider, None)
+}
+
+//内容所在的存储，bucket为空时使用配置中的bucket
+pub fn get(provider: &str, bucket: Option<&str>) -> Result<Store> {
+    let stores = stores();
+    match provider {
+        StorageConfig::LOCAL => Ok(Store::Local(stores.local.clone())),
+        StorageConfig::S3 => {
+            let store = stores.s3.as_ref()
+                .ok_or(ApiErr::Error("文件存放在S3，但未配置[s3]".to_string()))?;
+            Ok(Store::S3(match bucket {
+                Some(bucket) if bucket != store.bucket() => store.with_bucket(bucket),
+                _ => store.clone(),
+            }))
+        }
+        _ => Err(ApiErr::Error(format!("不支持的存储位置: {}", provider)).into()),
+    }
+}
+
+pub(crate) fn not_found() -> anyhow::Error {
+    ApiErr::Bad(404, "文件内容不存在".to_string()).into()
+}
@@ -0,0 +1,228 @@
+use std::{path::Path, time::Duration};
+
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use core::config::S3Config;
+use core::response::ApiErr;
+use futures::TryStreamExt;
+use hmac::{Hmac, Mac};
+use percent_encoding::{utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use reqwest::{header, Body, Client, Method, RequestBuilder, Response, StatusCode};
+use sha2::{Digest, Sha256};
+use tokio::fs::{self, File};
+use tokio_util::io::ReaderStream;
+
+use super::{not_found, BlobStore, ByteStream};
+
+const ALGORITHM: &str = "AWS4-HMAC-SHA256";
+//请求体不参与签名，大文件上传时不需要预先计算哈希
+const UNSIGNED_PAYLOAD: &str = "UNSIGNED-PAYLOAD";
+//预签名链接的最长有效期为7天
+const MAX_PRESIGN_SECS: u64 = 7 * 24 * 3600;
+
+//URI编码时保留的字符，见AWS Signature Version 4文档
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC.remove(b'-').remove(b'.').remove(b'_').remove(b'~');
+const PATH: &AsciiSet = &UNRESERVED.remove(b'/');
+
+//S3兼容的对象存储，请求使用AWS Signature Version 4签名
+#[derive(Debug, Clone)]
+pub struct S3Store {
+    client: Client,
+    scheme: String,
+    //host[:port]，省略默认端口以便与请求中的Host头一致
+    authority: String,
+    region: String,
+    bucket: String,
+    access_key: String,
+    secret_key: String,
+    path_style: bool,
+}
+
+impl S3Store {
+    pub fn new(config: &S3Config) -> Self {
+        let (scheme, authority) = config.endpoint.split_once("://").unwrap_or(("https", &config.endpoint));
+        let authority = authority.trim_end_matches('/');
+        let authority = match scheme {
+            "http" => authority.strip_suffix(":80"),
+            _ => authority.strip_suffix(":443"),
+        }.unwrap_or(authority);
+        Self {
+            client: Client::new(),
+            scheme: scheme.to_string(),
+            authority: authority.to_string(),
+            region: config.region.clone(),
+            bucket: config.bucket.clone(),
+            access_key: config.access_key.clone(),
+            secret_key: config.secret_key.clone(),
+            path_style: config.path_style,
+        }
+    }
+
+    pub fn bucket(&self) -> &str {
+        &self.bucket
+    }
+
+    pub fn with_bucket(&self, bucket: &str) -> Self {
+        Self { bucket: bucket.to_string(), ..self.clone() }
+    }
+
+    //对象的地址，返回(url, host, 编码后的路径)
+    fn locate(&self, key: &str) -> (String, String, String) {
+        let key = utf8_percent_encode(key.trim_start_matches('/'), PATH).to_string();
+        let (host, path) = if self.path_style {
+            (self.authority.clone(), format!("/{}/{}", self.bucket, key))
+        } else {
+            (format!("{}.{}", self.bucket, self.authority), format!("/{}", key))
+        };
+        (format!("{}://{}{}", self.scheme, host, path), host, path)
+    }
+
+    fn scope(&self, now: &DateTime<Utc>) -> String {
+        format!("{}/{}/s3/aws4_request", now.format("%Y%m%d"), self.region)
+    }
+
+    //计算签名，path为编码后的路径，query为未编码的查询参数，headers为参与签名的请求头(名称小写)
+    pub(crate) fn sign(
+        &self, now: &DateTime<Utc>, method: &str, path: &str,
+        query: &[(&str, String)], headers: &[(&str, String)], payload: &str,
+    ) -> String {
+        let mut headers = headers.to_vec();
+        headers.sort_by(|a, b| a.0.cmp(b.0));
+        let canonical_headers: String = headers.iter()
+            .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+            .collect();
+        let signed_headers = signed_headers(&headers);
+        let canonical_request = format!("{}\n{}\n{}\n{}\n{}\n{}",
+            method, path, canonical_query(query), canonical_headers, signed_headers, payload);
+        let string_to_sign = format!("{}\n{}\n{}\n{}", ALGORITHM, now.format("%Y%m%dT%H%M%SZ"),
+            self.scope(now), hex(&Sha256::digest(canonical_request.as_bytes())));
+        let date = now.format("%Y%m%d").to_string();
+        let key = hmac(format!("AWS4{}", self.secret_key).as_bytes(), date.as_bytes());
+        let key = hmac(&key, self.region.as_bytes());
+        let key = hmac(&key, b"s3");
+        let key = hmac(&key, b"aws4_request");
+        hex(&hmac(&key, string_to_sign.as_bytes()))
+    }
+
+    //签名放在Authorization请求头中的请求，extra为额外参与签名的请求头
+    fn request(&self, method: Method, key: &str, extra: &[(&str, String)]) -> RequestBuilder {
+        let now = Utc::now();
+        let (url, host, path) = self.locate(key);
+        let mut headers = vec![
+            ("host", host),
+            ("x-amz-content-sha256", UNSIGNED_PAYLOAD.to_string()),
+            ("x-amz-date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+        ];
+        headers.extend_from_slice(extra);
+        let signature = self.sign(&now, method.as_str(), &path, &[], &headers, UNSIGNED_PAYLOAD);
+        let mut sorted = headers.clone();
+        sorted.sort_by(|a, b| a.0.cmp(b.0));
+        let authorization = format!("{} Credential={}/{},SignedHeaders={},Signature={}",
+            ALGORITHM, self.access_key, self.scope(&now), signed_headers(&sorted), signature);
+        //Host由reqwest按url填写
+        headers.into_iter()
+            .filter(|(name, _)| *name != "host")
+            .fold(self.client.request(method, url), |builder, (name, value)| builder.header(name, value))
+            .header(header::AUTHORIZATION, authorization)
+    }
+
+    //有效期为ttl的预签名下载地址
+    pub(crate) fn presign_at(&self, key: &str, ttl: Duration, now: &DateTime<Utc>) -> String {
+        let (url, host, path) = self.locate(key);
+        let query = [
+            ("X-Amz-Algorithm", ALGORITHM.to_string()),
+            ("X-Amz-Credential", format!("{}/{}", self.access_key, self.scope(now))),
+            ("X-Amz-Date", now.format("%Y%m%dT%H%M%SZ").to_string()),
+            ("X-Amz-Expires", ttl.as_secs().clamp(1, MAX_PRESIGN_SECS).to_string()),
+            ("X-Amz-SignedHeaders", "host".to_string()),
+        ];
+        let signature = self.sign(now, "GET", &path, &query, &[("host", host)], UNSIGNED_PAYLOAD);
+        format!("{}?{}&X-Amz-Signature={}", url, canonical_query(&query), signature)
+    }
+
+    async fn read(&self, key: &str, extra: &[(&str, String)]) -> Result<ByteStream> {
+        let response = check(self.request(Method::GET, key, extra).send().await?).await?;
+        Ok(Box::pin(response.bytes_stream().map_err(std::io::Error::other)))
+    }
+}
+
+impl BlobStore for S3Store {
+    //按块上传文件内容，上传成功后删除临时文件
+    async fn put(&self, key: &str, file: &Path, size: u64) -> Result<()> {
+        let body = Body::wrap_stream(ReaderStream::new(File::open(file).await?));
+        check(self.request(Method::PUT, key, &[])
+            .header(header::CONTENT_LENGTH, size)
+            .body(body)
+            .send()
+            .await?).await?;
+        fs::remove_file(file).await?;
+        Ok(())
+    }
+
+    async fn get(&self, key: &str) -> Result<ByteStream> {
+        self.read(key, &[]).await
+    }
+
+    async fn size(&self, key: &str) -> Result<u64> {
+        let response = check(self.request(Method::HEAD, key, &[]).send().await?).await?;
+        response.headers().get(header::CONTENT_LENGTH)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse().ok())
+            .ok_or(ApiErr::Error("S3响应缺少Content-Length".to_string()).into())
+    }
+
+    async fn range(&self, key: &str, start: u64, len: u64) -> Result<ByteStream> {
+        if len == 0 {
+            return Ok(Box::pin(futures::stream::empty()));
+        }
+        self.read(key, &[("range", format!("bytes={}-{}", start, start + len - 1))]).await
+    }
+
+    async fn delete(&self, key: &str) -> Result<()> {
+        let response = self.request(Method::DELETE, key, &[]).send().await?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(());
+        }
+        check(response).await?;
+        Ok(())
+    }
+
+    fn presign(&self, key: &str, ttl: Duration) -> Option<String> {
+        Some(self.presign_at(key, ttl, &Utc::now()))
+    }
+}
+
+//对象不存在时返回404，其他失败附带S3返回的错误信息
+async fn check(response: Response) -> Result<Response> {
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status == StatusCode::NOT_FOUND {
+        return Err(not_found());
+    }
+    let body = response.text().await.unwrap_or_default();
+    Err(ApiErr::Error(format!("S3请求失败: {} {}", status, body)).into())
+}
+
+fn canonical_query(query: &[(&str, String)]) -> String {
+    let mut pairs: Vec<(String, String)> = query.iter()
+        .map(|(k, v)| (utf8_percent_encode(k, UNRESERVED).to_string(), utf8_percent_encode(v, UNRESERVED).to_string()))
+        .collect();
+    pairs.sort();
+    pairs.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("&")
+}
+
+fn signed_headers(sorted: &[(&str, String)]) -> String {
+    sorted.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";")
+}
+
+fn hmac(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC可以使用任意长度的密钥");
+    mac.update(data);
+    mac.finalize().into_bytes().to_vec()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
@@ -0,0 +1,2 @@
+ALTER TABLE `messages` ADD COLUMN `payload` TEXT;
+ALTER TABLE `messages` ADD COLUMN `version` INTEGER NOT NULL DEFAULT 0;
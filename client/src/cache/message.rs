@@ -40,8 +40,8 @@ impl Cache {
         }
 
         // 2. 检查SQLite数据库
-        match self.get_from_sqlite(session_id, page){
-            Ok(messages) => {
+        match self.get_from_sqlite(session_id, &page){
+            Ok(messages) if !messages.is_empty() => {
                 // 将结果存入内存缓存
                 {
                     let mut cache = self.memory_cache.write().unwrap();
@@ -49,13 +49,15 @@ impl Cache {
                 }
                 return Ok(messages);
             }
+            // 本地没有记录时从远程服务器拉取
+            Ok(_) => {}
             Err(e) => {
                 log::error!("从SQLite获取消息失败: {}", e);
             }
         }
 
         // 3. 从远程服务器获取
-        match self.get_from_remote(session_id, token){
+        match self.get_from_remote(session_id, token, page.ps){
             Ok(messages) => {
                 // 将结果存入内存缓存和SQLite数据库
                 {
@@ -81,7 +83,7 @@ impl Cache {
     }
 
     /// 从SQLite数据库获取消息
-    fn get_from_sqlite(&self, session_id: i64, page: Page) -> Result<Vec<Message>> {
+    fn get_from_sqlite(&self, session_id: i64, page: &Page) -> Result<Vec<Message>> {
         let (messages, _total) = crate::asynrt::get().block_on(select_messages(session_id, page))?;
         Ok(messages.iter().map(|m| m.into()).collect())
     }
@@ -92,10 +94,10 @@ impl Cache {
     }
 
     /// 从远程服务器获取消息
-    fn get_from_remote(&self, session: i64, token: &str) -> Result<Vec<Message>> {
+    fn get_from_remote(&self, session: i64, token: &str, limit: u32) -> Result<Vec<Message>> {
         // 注意：这里需要将会话ID映射到房间ID
         // 在实际实现中，您可能需要一个映射机制
-        let remote_messages = get_session_messages(token, session)?;
+        let remote_messages = get_session_messages(token, session, limit)?;
         Ok(remote_messages)
     }
 
@@ -153,7 +153,11 @@ impl Cache {
             let mut cache = self.memory_cache.write().unwrap();
             let message = SessionResponse{
                 id: payload.id,
-                name: payload.name.clone()
+                name: payload.name.clone(),
+                kind: payload.kind.unwrap_or_default(),
+                unread: 0,
+                last_message: None,
+                settings: Default::default(),
             };
             cache.push(message);
         }
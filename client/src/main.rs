@@ -5,11 +5,12 @@ use crossterm::{
 };
 use ratatui::Terminal;
 use ratatui::prelude::CrosstermBackend;
-use std::io::stdout;
+use std::io::{stdout, Write};
 use std::sync::Arc;
 use anyhow::Result;
 use tokio::sync::{Mutex};
 use core::api::client;
+use core::config::ClientConfig;
 
 mod ui;
 mod repository;
@@ -18,7 +19,7 @@ mod cache;
 mod asynrt;
 mod ext;
 
-use crate::{ui::{models::Me}};
+use crate::{ui::{models::{Incoming, Me}}};
 
 use crate::{repository::db, ui::models::App};
 
@@ -27,9 +28,11 @@ fn main() -> Result<()> {
     dotenv::dotenv().ok();
     // 初始化日志
     core::log::init(Some(".pubchat_client.log"));
+    let config = core::config::load_or_exit::<ClientConfig>("client");
+    core::config::print("client", &config);
     asynrt::init();
     db::init();
-    client::init();
+    client::init(&config.api);
     cache::init();
     ext::init();
     
@@ -51,7 +54,7 @@ fn main() -> Result<()> {
     // 使用token建立TCP连接
     let (stream, user_id, user_name) = asynrt::get().block_on(remote::connection::connect_with_token(&token))?;
     let (reader, writer) = stream.into_split();
-    let (sx, mut rx) = tokio::sync::mpsc::channel::<core::api::types::message::Message>(100);
+    let (sx, mut rx) = tokio::sync::mpsc::channel::<Incoming>(100);
     // 开启接收消息任务
     asynrt::get().block_on(remote::connection::receive_messages(reader, sx));
     show_main_screen(&mut terminal, token, Me {id: user_id, name: user_name}, writer, &mut rx)?;
@@ -67,12 +70,17 @@ fn show_main_screen(
     token: String,
     me: Me,
     writer: tokio::net::tcp::OwnedWriteHalf,
-    rx: &mut tokio::sync::mpsc::Receiver<core::api::types::message::Message>,
+    rx: &mut tokio::sync::mpsc::Receiver<Incoming>,
 ) -> Result<()> {
     
     let shared_writer = Arc::new(Mutex::new(writer));
     // 登录成功后，创建应用状态
     let mut app = App::new(token, me, shared_writer);
+    // 拉取离线期间发布且仍有效的公告
+    match client::notice::get_notices(&app.token) {
+        Ok(notices) => notices.into_iter().for_each(|n| app.chat.chat.add_notice(n.into())),
+        Err(e) => log::error!("Failed to fetch notices: {}", e),
+    }
     // 主事件循环
     loop {
         terminal.draw(|frame| app.render(frame))?;
@@ -91,9 +99,47 @@ fn show_main_screen(
                 app.handle(key);
             }
         }
-        if let Ok(message) = rx.try_recv() {
-            // 接收到消息，将消息添加到缓存中
-            app.chat.chat.messages.push(crate::ui::models::Message::new(message.uname, message.content, false));
+        match rx.try_recv() {
+            Ok(Incoming::Chat { message, silent }) => {
+                // 接收到当前会话的消息直接展示，其他会话的消息按通知设置计入未读并响铃
+                let current = app.chat.chat.session.as_ref().is_some_and(|s| s.id == message.session);
+                let text = message.text();
+                let notify = !silent && app.chat.sessions.notify(message.session, &text, &app.me.name);
+                app.chat.sessions.receive(message.session, format!("{}: {}", message.uname, text), !current && notify);
+                if !current && notify {
+                    bell();
+                }
+                if current {
+                    // 置顶变更等操作会以系统消息通知，收到后刷新置顶消息
+                    if message.mtype == pubchat::core::message::ChatType::System as i32 {
+                        app.chat.chat.load_pins();
+                    }
+                    app.chat.chat.messages.push(crate::ui::models::Message::new(message.uname, text, false));
+                    // 当前会话的消息已展示，同步推进服务端的已读位置
+                    app.chat.sessions.mark_read(&app.token, message.session);
+                }
+            }
+            Ok(Incoming::Notice(notice)) => app.chat.chat.add_notice(notice),
+            Ok(Incoming::Rejected { session, reason }) => {
+                let name = app.chat.sessions.sessions.iter()
+                    .find(|s| s.id == session)
+                    .map(|s| s.name.clone())
+                    .unwrap_or_default();
+                app.chat.chat.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(),
+                    format!("Message to {} was not sent: {}", name, reason),
+                    true
+                ));
+            }
+            Err(_) => {}
         }
     }
-}
\ No newline at end of file
+}
+
+//终端响铃提醒新消息
+fn bell() {
+    let mut out = stdout();
+    if let Err(e) = out.write_all(b"\x07").and_then(|_| out.flush()) {
+        log::error!("Failed to ring the bell: {}", e);
+    }
+}
@@ -6,7 +6,7 @@ use tokio::io::AsyncWriteExt;
 use core::{response::ApiErr};
 use pubchat::core::codec::{encode, decode};
 use core::api::client::connection_host;
-use crate::{cache};
+use crate::{cache, ui::models::Incoming};
 use pubchat::core::message::{Cort, Message, Type}; 
 
 
@@ -48,7 +48,7 @@ pub async fn connect_with_token(token: &str) -> Result<(TcpStream, u64, String)>
 }
 
 // 接收消息的异步任务
-pub async fn receive_messages(mut reader: tokio::net::tcp::OwnedReadHalf, sx: tokio::sync::mpsc::Sender<core::api::types::message::Message>) {
+pub async fn receive_messages(mut reader: tokio::net::tcp::OwnedReadHalf, sx: tokio::sync::mpsc::Sender<Incoming>) {
     // 启动接收消息的任务
     tokio::spawn(async move {
         loop {
@@ -72,9 +72,11 @@ pub async fn receive_messages(mut reader: tokio::net::tcp::OwnedReadHalf, sx: to
                                         content: format!("{}", m),
                                         timestamp: chat.ts as i64,
                                         uname: chat.uname.clone(),
+                                        payload: Some(m),
+                                        version: core::api::types::message::PAYLOAD_VERSION,
                                     };
                                     cache::message_cache().async_add_message(chat.session as i64, msg.clone()).await;
-                                    let _ = sx.send(msg).await;
+                                    let _ = sx.send(Incoming::Chat { message: msg, silent: chat.silent }).await;
                                 }else{
                                     error!("Invalid chat message");
                                 }
@@ -83,6 +85,20 @@ pub async fn receive_messages(mut reader: tokio::net::tcp::OwnedReadHalf, sx: to
                                 error!("Invalid chat message2");
                             }
                         }
+                        t if t == Type::Noti as i32 => {
+                            if let Some(pubchat::core::message::message::Content::Notice(notice)) = msg.content {
+                                let _ = sx.send(Incoming::Notice(notice.into())).await;
+                            }else{
+                                error!("Invalid notice message");
+                            }
+                        }
+                        t if t == Type::Rjct as i32 => {
+                            if let Some(pubchat::core::message::message::Content::Reject(reject)) = msg.content {
+                                let _ = sx.send(Incoming::Rejected { session: reject.session as i64, reason: reject.reason }).await;
+                            }else{
+                                error!("Invalid reject message");
+                            }
+                        }
                         _ => {
                             // 其他类型消息暂不处理
                             error!("Unhandled message type: {}", msg.mtype);
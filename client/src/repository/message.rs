@@ -15,6 +15,11 @@ pub struct Message {
     pub content: String,
     pub timestamp: i64,
     pub uname: String,
+    //结构化消息内容(JSON)
+    #[sqlx(default)]
+    pub payload: Option<String>,
+    #[sqlx(default)]
+    pub version: i32,
 }
 
 impl From<&core::api::types::message::Message> for Message {
@@ -28,6 +33,8 @@ impl From<&core::api::types::message::Message> for Message {
             content: message.content.clone(),
             timestamp: message.timestamp,
             uname: message.uname.clone(),
+            payload: message.payload.as_ref().and_then(|p| serde_json::to_string(p).ok()),
+            version: message.version,
         }
     }
 }
@@ -40,6 +47,8 @@ impl From<core::api::types::message::Message> for Message {
             receiver: message.receiver,
             session: message.session,
             mtype: message.mtype,
+            payload: message.payload.as_ref().and_then(|p| serde_json::to_string(p).ok()),
+            version: message.version,
             content: message.content,
             timestamp: message.timestamp,
             uname: message.uname,
@@ -58,12 +67,14 @@ impl Into<core::api::types::message::Message> for &Message {
             content: self.content.clone(),
             timestamp: self.timestamp,
             uname: self.uname.clone(),
+            payload: self.payload.as_ref().and_then(|p| serde_json::from_str(p).ok()),
+            version: self.version,
         }
     }
 }
 
 //查询指定聊天室的最新的n条消息
-pub(crate) async fn select_messages(sid: i64, page: Page) -> Result<(Vec<Message>, i64)>{
+pub(crate) async fn select_messages(sid: i64, page: &Page) -> Result<(Vec<Message>, i64)>{
     let mut connection = db::connection().await?;
     let mut builder: QueryBuilder<Sqlite> = QueryBuilder::new(
         "SELECT *
@@ -94,7 +105,7 @@ pub(crate) async fn select_messages(sid: i64, page: Page) -> Result<(Vec<Message
 
 pub async fn save(message: &Message) -> Result<()> {
     let mut connection = db::connection().await?;
-    sqlx::query("INSERT INTO messages (id, sender, receiver, session, mtype, content, timestamp, uname) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
+    sqlx::query("INSERT INTO messages (id, sender, receiver, session, mtype, content, timestamp, uname, payload, version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)")
         .bind(&message.id)
         .bind(&message.sender)
         .bind(&message.receiver)
@@ -103,6 +114,8 @@ pub async fn save(message: &Message) -> Result<()> {
         .bind(&message.content)
         .bind(&message.timestamp)
         .bind(&message.uname)
+        .bind(&message.payload)
+        .bind(message.version)
         .execute(connection.as_mut())
         .await?;
     Ok(())
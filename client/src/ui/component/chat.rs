@@ -1,7 +1,8 @@
 
+use core::api::types::message::PinResponse;
 use core::request::Page;
 
-use crate::{cache, ui::{models::{Message, Mode, Session}, screen::chat::Focus}};
+use crate::{cache, ui::{models::{Message, Mode, Notice, Session}, screen::chat::Focus}};
 use ratatui::{
     prelude::*,
     widgets::{Block, Borders, List, ListItem, Paragraph},
@@ -15,11 +16,17 @@ pub struct ChatComponent {
     pub mode: Mode,
     pub input: String,
     pub token: String,
+    //收到的系统公告
+    pub notices: Vec<Notice>,
+    //当前会话的置顶消息，按置顶时间倒序
+    pub pins: Vec<PinResponse>,
+    //需要从会话列表中移除的会话，如退订的频道
+    pub left: Option<i64>,
 }
 
 impl ChatComponent {
     pub fn new(token: &str) -> Self {
-        Self { session: None, messages: vec![], mode: Mode::Normal, input: String::new(), token: token.to_string() }
+        Self { session: None, messages: vec![], mode: Mode::Normal, input: String::new(), token: token.to_string(), notices: vec![], pins: vec![], left: None }
     }
 
     pub fn change_session(&mut self, session: Option<Session>) {
@@ -31,7 +38,7 @@ impl ChatComponent {
                 Ok(messages) => {
                     self.messages = messages
                     .iter()
-                    .map(|m| Message::new(m.uname.clone(), m.content.clone(), false))
+                    .map(|m| Message::new(m.uname.clone(), m.text(), false))
                     .collect();
                 },
                 Err(err) => {
@@ -39,11 +46,62 @@ impl ChatComponent {
                     self.messages = vec![];
                 }
             }
+            // 公告显示在会话消息之前
+            let notices: Vec<Message> = self.notices.iter()
+                .filter(|n| n.active() && n.visible_in(session.id))
+                .map(|n| n.to_message())
+                .collect();
+            self.messages.splice(0..0, notices);
         }else{
             self.messages = vec![];
         }
+        self.load_pins();
+    }
+
+    //重新加载当前会话的置顶消息
+    pub fn load_pins(&mut self) {
+        self.pins = match &self.session {
+            Some(session) => core::api::client::message::get_pins(&self.token, session.id).unwrap_or_else(|err| {
+                log::error!("Error fetching pins: {}", err);
+                vec![]
+            }),
+            None => vec![],
+        };
+    }
+
+    //在消息区域列出全部置顶消息
+    pub fn show_pins(&mut self) {
+        if self.session.is_none() {
+            return;
+        }
+        if self.pins.is_empty() {
+            self.messages.push(Message::new("SYSTEM".to_string(), "No pinned messages".to_string(), true));
+            return;
+        }
+        self.messages.push(Message::new("SYSTEM".to_string(), format!("Pinned messages ({}):", self.pins.len()), true));
+        let lines: Vec<Message> = self.pins.iter()
+            .map(|pin| Message::new("SYSTEM".to_string(), pin_text(pin), true))
+            .collect();
+        self.messages.extend(lines);
     }
     
+    pub fn add_notice(&mut self, notice: Notice) {
+        if !notice.active() || self.notices.iter().any(|n| n.id == notice.id) {
+            return;
+        }
+        if self.session.as_ref().is_some_and(|s| notice.visible_in(s.id)) {
+            self.messages.push(notice.to_message());
+        }
+        self.notices.push(notice);
+    }
+
+    //当前需要横幅展示的公告，优先级别最高、最新发布的
+    pub fn banner(&self) -> Option<&Notice> {
+        self.notices.iter()
+            .filter(|n| n.active() && n.banner())
+            .max_by_key(|n| (n.severity, n.id))
+    }
+
     pub fn change_mode(&mut self, mode: Mode) {
         self.mode = mode;
     }
@@ -108,9 +166,14 @@ impl ChatComponent {
         }).collect();
 
         let title = if let Some(session) = &self.session {
-            format!("Chat with {} {}", 
+            let mut title = format!("Chat with {} {}", 
                 session.name,
-                "[M] (Press 'm' to switch maximized)" )
+                "[M] (Press 'm' to switch maximized)" );
+            // 标题中展示最新的置顶消息，按'p'查看全部
+            if let Some(pin) = self.pins.first() {
+                title.push_str(&format!(" | Pinned: {} (Press 'p' to list {})", pin_summary(pin), self.pins.len()));
+            }
+            title
         } else {
             "Messages".to_string()
         };
@@ -143,4 +206,22 @@ impl ChatComponent {
             );
         }
     }
-}
\ No newline at end of file
+}
+//置顶消息摘要的最大字符数
+const PIN_SUMMARY_LEN: usize = 30;
+
+pub fn pin_summary(pin: &PinResponse) -> String {
+    let text = pin.message.text();
+    let mut summary: String = text.chars().take(PIN_SUMMARY_LEN).collect();
+    if text.chars().count() > PIN_SUMMARY_LEN {
+        summary.push_str("...");
+    }
+    format!("<{}> {}", pin.message.uname, summary)
+}
+
+pub fn pin_text(pin: &PinResponse) -> String {
+    let at = chrono::DateTime::from_timestamp_millis(pin.pinned_at)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default();
+    format!("<{}> {} (pinned by {} at {})", pin.message.uname, pin.message.text(), pin.pinned_name, at)
+}
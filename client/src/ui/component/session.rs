@@ -1,6 +1,7 @@
+use core::api::types::session::SessionSettings;
 use core::request::Page;
 
-use ratatui::{Frame, layout::Rect, style, widgets::{Block, Borders, List, ListItem}};
+use ratatui::{Frame, layout::Rect, style, text::{Line, Span}, widgets::{Block, Borders, List, ListItem}};
 
 use crate::{cache, ui::{models::Session, screen::chat::Focus}};
 
@@ -28,15 +29,34 @@ impl SessionListComponent {
     }
     
     pub fn render(&self, frame: &mut Frame, area: Rect, focus: &Focus) {
+        let now = chrono::Utc::now().timestamp_millis();
         let sessions: Vec<ListItem> = self.sessions
             .iter()
             .enumerate()
             .map(|(i, session)| {
+                let mut title = vec![];
+                if session.kind == Session::CHANNEL {
+                    title.push(Span::styled("# ", style::Style::default().fg(style::Color::Cyan)));
+                }
+                title.push(Span::raw(session.name.to_string()));
+                if session.settings.muted(now) {
+                    // 静音的会话不显示未读角标
+                    title.push(Span::styled(" [muted]", style::Style::default().fg(style::Color::DarkGray)));
+                } else if session.unread > 0 {
+                    // 未读角标
+                    let badge = if session.unread > 99 { "99+".to_string() } else { session.unread.to_string() };
+                    title.push(Span::raw(" "));
+                    title.push(Span::styled(format!("({})", badge), style::Style::default().fg(style::Color::Red).add_modifier(style::Modifier::BOLD)));
+                }
+                let mut lines = vec![Line::from(title)];
+                if let Some(preview) = &session.preview {
+                    lines.push(Line::from(Span::styled(preview.to_string(), style::Style::default().fg(style::Color::DarkGray))));
+                }
                 if i == self.index {
-                    ListItem::new(format!("{}", session.name))
+                    ListItem::new(lines)
                         .style(style::Style::default().bg(style::Color::Blue).fg(style::Color::White))
                 } else {
-                    ListItem::new(format!("{}", session.name))
+                    ListItem::new(lines)
                 }
             })
             .collect();
@@ -66,6 +86,57 @@ impl SessionListComponent {
         self.sessions.get(self.index)
     }
 
+    //标记会话已读，清除未读角标
+    pub fn mark_read(&mut self, token: &str, id: i64) {
+        if let Err(e) = core::api::client::session::mark_read(token, id) {
+            log::error!("Failed to mark session {} read: {}", id, e);
+        }
+        if let Some(session) = self.sessions.iter_mut().find(|s| s.id == id) {
+            session.unread = 0;
+        }
+    }
+
+    //收到消息时更新预览并将会话移到列表顶部，非当前会话的消息计入未读，选中项保持不变
+    pub fn receive(&mut self, id: i64, preview: String, unread: bool) {
+        let Some(pos) = self.sessions.iter().position(|s| s.id == id) else {
+            return;
+        };
+        let mut session = self.sessions.remove(pos);
+        if unread {
+            session.unread += 1;
+        }
+        session.preview = Some(preview);
+        self.sessions.insert(0, session);
+        if self.index == pos {
+            self.index = 0;
+        } else if self.index < pos {
+            self.index += 1;
+        }
+    }
+
+    //按会话的通知设置判断收到的消息是否需要提醒，uname为当前用户昵称
+    pub fn notify(&self, id: i64, text: &str, uname: &str) -> bool {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.sessions.iter()
+            .find(|s| s.id == id)
+            .is_none_or(|s| s.settings.notify(text, uname, now))
+    }
+
+    pub fn update_settings(&mut self, id: i64, settings: SessionSettings) {
+        if let Some(session) = self.sessions.iter_mut().find(|s| s.id == id) {
+            session.settings = settings;
+        }
+    }
+
+    pub fn remove_session(&mut self, id: i64) {
+        if let Some(pos) = self.sessions.iter().position(|s| s.id == id) {
+            self.sessions.remove(pos);
+            if self.index >= pos && self.index > 0 {
+                self.index -= 1;
+            }
+        }
+    }
+
     pub fn add_session(&mut self, session: Option<Session>) {
         if let Some(session) = session {
             if !self.sessions.iter().any(|s| s.id == session.id) {
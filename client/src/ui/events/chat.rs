@@ -76,6 +76,9 @@ impl ChatComponent {
             KeyCode::Char('h') => {
                 return EventResult::Nav2SessionList;
             }
+            KeyCode::Char('p') => {
+                self.show_pins();
+            }
             _ => {}
         }
         EventResult::None
@@ -67,6 +67,7 @@ impl ContactListScreen {
                         avatar: None,
                     }
                 ],
+                kind: None,
             };
             match cache::session_cache().add_session(&self.token, request){
             // 创建会话
@@ -77,7 +78,11 @@ impl ContactListScreen {
                         members: vec![Contact{id: selected.id, 
                             name: selected.name.clone(), 
                             avatar: None,
-                            status: Status::Online}]
+                            status: Status::Online}],
+                        unread: 0,
+                        preview: None,
+                        settings: Default::default(),
+                        kind: 0,
                      })
                 }
                 Err(e) => {
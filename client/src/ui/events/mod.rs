@@ -29,12 +29,16 @@ impl App {
             EventResult::CreateSession(session) => {
                 // 切换到聊天页面
                 self.change_view(View::Chat);
+                let settings = session.settings;
                 let session = cache::session_cache().get_session(&self.token, session.id);
                 log::info!("Create session event: {:?}", session);
                 let session = session
-                    .map(|s| Session::from(s))
+                    .map(|s| Session { settings, ..Session::from(s) })
                     .ok();
                 self.chat.sessions.add_session(session.clone());
+                if let Some(session) = &session {
+                    self.chat.sessions.mark_read(&self.token, session.id);
+                }
                 self.chat.chat.change_session(session);
                 self.chat.focus = Focus::Chat;
             },
@@ -45,6 +49,14 @@ impl App {
             EventResult::SendMessage() => {
                 // 发送消息
                 self.chat.chat.send_message(&self.me, &self.stream);
+                // 命令可能切换到了新加入的会话、退出了会话或修改了当前会话的通知设置，同步到会话列表
+                if let Some(id) = self.chat.chat.left.take() {
+                    self.chat.sessions.remove_session(id);
+                }
+                if let Some(session) = &self.chat.chat.session {
+                    self.chat.sessions.add_session(Some(session.clone()));
+                    self.chat.sessions.update_settings(session.id, session.settings);
+                }
             },
             EventResult::None => {}
             _ => {}
@@ -1,4 +1,8 @@
 use core::{api::client::blob::{download_file, upload_file}};
+use core::api::{client::message::{cancel_scheduled, get_scheduled, schedule_message, search_messages}, types::message::{ScheduleRequest, SearchMessageRequest}};
+use core::api::client::session::{export_session, get_channels, join_invite, subscribe, update_settings};
+use core::api::types::session::SessionSettings;
+use core::api::types::message::{Payload, PAYLOAD_VERSION};
 use std::sync::Arc;
 
 use crate::{cache, ui::{component::chat::ChatComponent, models::Me}};
@@ -32,10 +36,7 @@ impl ChatComponent {
                 content: Some(pubchat::core::message::message::Content::Chrt(Chrt{
                     sender: me.id, // 使用真实的用户ID
                     session: session_id as u64,
-                    receivers: session.members.iter()
-                        .map(|m| m.id as u64)
-                        .filter(|id| *id != me.id)
-                        .collect(),
+                    receivers: session.receivers(me.id),
                     ctype: ChatType::Text as i32,
                     message: Some(pubchat::core::message::chrt::Message::Text(Text{ text: content.clone() })),
                     ts: std::time::SystemTime::now()
@@ -43,6 +44,7 @@ impl ChatComponent {
                         .unwrap()
                         .as_millis() as u64,
                     uname: me.name.to_string(), // 使用真实的用户名
+                    flag: None,
                 })),
             };
             self.messages.push(crate::ui::models::Message::new(me.name.to_string(), content.clone(), false));
@@ -53,6 +55,8 @@ impl ChatComponent {
                 uname: me.name.to_string(),
                 session: session_id,
                 mtype: Type::Chrt as i32,
+                payload: Some(Payload::Text(Text{ text: content.clone() })),
+                version: PAYLOAD_VERSION,
                 content: content,
                 timestamp: chat_request.ts as i64,
             };
@@ -118,6 +122,46 @@ impl ChatComponent {
                     "/download <file_id> <save_path> - Download a file".to_string(), 
                     true
                 ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/search <keywords> - Search message history".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/export <path> - Export current session history (.json, .md or .html)".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/join <token> - Join a group session with an invite token".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/channels - List channels of your organization; /subscribe <id> and /unsubscribe to follow or leave one".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/mute [minutes] - Mute current session, forever if minutes omitted; /unmute to undo".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/notify <all|mentions> - Notify on all messages or only when @mentioned".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "/schedule <+10m|+2h|+1d|HH:MM|YYYY-MM-DDTHH:MM> <text> - Send a message later; /scheduled to list, /unschedule <id> to cancel".to_string(), 
+                    true
+                ));
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(), 
+                    "p (normal mode) - List pinned messages".to_string(), 
+                    true
+                ));
                 self.messages.push(crate::ui::models::Message::new(
                     "SYSTEM".to_string(), 
                     "/friends - Open friends list".to_string(), 
@@ -217,6 +261,107 @@ impl ChatComponent {
                     ));
                 }
             }
+            "/search" => {
+                if parts.len() >= 2 {
+                    self.search(&parts[1..].join(" "));
+                } else {
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /search <keywords>".to_string(),
+                        true
+                    ));
+                }
+            }
+            "/export" => {
+                if let Some(path) = parts.get(1) {
+                    self.export(path);
+                } else {
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /export <path>".to_string(),
+                        true
+                    ));
+                }
+            }
+            "/join" => {
+                if let Some(token) = parts.get(1) {
+                    self.join(token);
+                } else {
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /join <token>".to_string(),
+                        true
+                    ));
+                }
+            }
+            "/channels" => {
+                self.channels();
+            }
+            "/subscribe" => {
+                match parts.get(1).map(|id| id.parse::<i64>()) {
+                    Some(Ok(id)) => self.subscribe(id),
+                    _ => self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /subscribe <channel id>".to_string(),
+                        true
+                    )),
+                }
+            }
+            "/unsubscribe" => {
+                self.unsubscribe();
+            }
+            "/mute" => {
+                match parts.get(1).map(|m| m.parse::<i64>()) {
+                    None => self.update_settings(|s| s.muted_until = Some(i64::MAX)),
+                    Some(Ok(minutes)) if minutes > 0 => {
+                        let until = chrono::Utc::now().timestamp_millis().saturating_add(minutes.saturating_mul(60_000));
+                        self.update_settings(|s| s.muted_until = Some(until));
+                    }
+                    Some(_) => self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /mute [minutes]".to_string(),
+                        true
+                    )),
+                }
+            }
+            "/unmute" => {
+                self.update_settings(|s| s.muted_until = None);
+            }
+            "/notify" => {
+                match parts.get(1).copied() {
+                    Some("all") => self.update_settings(|s| s.notify = SessionSettings::NOTIFY_ALL),
+                    Some("mentions") => self.update_settings(|s| s.notify = SessionSettings::NOTIFY_MENTIONS),
+                    _ => self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /notify <all|mentions>".to_string(),
+                        true
+                    )),
+                }
+            }
+            "/schedule" => {
+                let when = parts.get(1).and_then(|w| parse_when(w, chrono::Local::now()));
+                match when {
+                    Some(send_at) if parts.len() >= 3 => self.schedule(send_at, &parts[2..].join(" ")),
+                    _ => self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /schedule <+10m|+2h|+1d|HH:MM|YYYY-MM-DDTHH:MM> <text>".to_string(),
+                        true
+                    )),
+                }
+            }
+            "/scheduled" => {
+                self.scheduled();
+            }
+            "/unschedule" => {
+                match parts.get(1).map(|id| id.parse::<i64>()) {
+                    Some(Ok(id)) => self.unschedule(id),
+                    _ => self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        "Usage: /unschedule <id>".to_string(),
+                        true
+                    )),
+                }
+            }
             "/contacts" => {
                 self.messages.push(crate::ui::models::Message::new(
                     "SYSTEM".to_string(),
@@ -275,6 +420,231 @@ impl ChatComponent {
         should_exit
     }
 
+    /// Search message history in all sessions of current user
+    fn search(&mut self, q: &str) {
+        let request = SearchMessageRequest { q: q.to_string(), ..Default::default() };
+        match search_messages(&self.token, &request) {
+            Ok(hits) => {
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(),
+                    format!("{} result(s) for \"{}\":", hits.len(), q),
+                    true
+                ));
+                for hit in hits {
+                    let time = chrono::DateTime::from_timestamp_millis(hit.timestamp)
+                        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                        .unwrap_or_default();
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        format!("{} {}: {}", time, hit.uname, hit.snippet),
+                        true
+                    ));
+                }
+            }
+            Err(e) => {
+                self.messages.push(crate::ui::models::Message::new(
+                    "SYSTEM".to_string(),
+                    format!("Failed to search messages: {}", e),
+                    true
+                ));
+            }
+        }
+    }
+
+    /// Export the history of current session to a local file, format is chosen by file extension
+    fn export(&mut self, path: &str) {
+        let Some(session) = &self.session else {
+            self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                "No session selected".to_string(),
+                true
+            ));
+            return;
+        };
+        let format = match std::path::Path::new(path).extension().and_then(|e| e.to_str()) {
+            Some("md") | Some("markdown") => "md",
+            Some("html") | Some("htm") => "html",
+            _ => "json",
+        };
+        let message = match export_session(&self.token, session.id, format, path) {
+            Ok(size) => format!("Exported {} bytes to {}", size, path),
+            Err(e) => format!("Failed to export session: {}", e),
+        };
+        self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), message, true));
+    }
+
+    /// Join a group session with an invite token and switch to it
+    fn join(&mut self, token: &str) {
+        match join_invite(&self.token, token) {
+            Ok(session) => self.enter(session),
+            Err(e) => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                format!("Failed to join session: {}", e),
+                true
+            )),
+        }
+    }
+
+    /// Switch to a session the user just joined or subscribed to
+    fn enter(&mut self, session: core::api::types::session::SessionResponse) {
+        let name = session.name.clone();
+        let session = cache::session_cache().get_session(&self.token, session.id)
+            .map(crate::ui::models::Session::from)
+            .unwrap_or_else(|_| crate::ui::models::Session::from(session));
+        self.change_session(Some(session));
+        self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), format!("Joined session {}", name), true));
+    }
+
+    /// Schedule a text message in current session
+    fn schedule(&mut self, send_at: i64, text: &str) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let request = ScheduleRequest { text: Some(text.to_string()), send_at: Some(send_at) };
+        let text = match schedule_message(&self.token, session.id, &request) {
+            Ok(scheduled) => format!("Message #{} scheduled at {}", scheduled.id, local_time(scheduled.send_at)),
+            Err(e) => format!("Failed to schedule message: {}", e),
+        };
+        self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), text, true));
+    }
+
+    /// List pending scheduled messages of current user in current session
+    fn scheduled(&mut self) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        match get_scheduled(&self.token, session.id) {
+            Ok(list) if list.is_empty() => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                "No scheduled messages".to_string(),
+                true
+            )),
+            Ok(list) => {
+                for scheduled in list {
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        format!("#{} {} {}", scheduled.id, local_time(scheduled.send_at), scheduled.content),
+                        true
+                    ));
+                }
+            }
+            Err(e) => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                format!("Failed to get scheduled messages: {}", e),
+                true
+            )),
+        }
+    }
+
+    /// Cancel a pending scheduled message
+    fn unschedule(&mut self, id: i64) {
+        let Some(session) = &self.session else {
+            return;
+        };
+        let text = match cancel_scheduled(&self.token, session.id, id) {
+            Ok(_) => format!("Scheduled message #{} cancelled", id),
+            Err(e) => format!("Failed to cancel scheduled message: {}", e),
+        };
+        self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), text, true));
+    }
+
+    /// List channels of the user's organization
+    fn channels(&mut self) {
+        match get_channels(&self.token) {
+            Ok(channels) if channels.is_empty() => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                "No channels in your organization".to_string(),
+                true
+            )),
+            Ok(channels) => {
+                for channel in channels {
+                    let subscribed = if channel.subscribed { " [subscribed]" } else { "" };
+                    self.messages.push(crate::ui::models::Message::new(
+                        "SYSTEM".to_string(),
+                        format!("#{} {} ({} subscribers){}", channel.id, channel.name, channel.subscribers, subscribed),
+                        true
+                    ));
+                }
+            }
+            Err(e) => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                format!("Failed to get channels: {}", e),
+                true
+            )),
+        }
+    }
+
+    /// Subscribe to a channel and switch to it
+    fn subscribe(&mut self, id: i64) {
+        match subscribe(&self.token, id, true) {
+            Ok(Some(session)) => self.enter(session),
+            Ok(None) => {}
+            Err(e) => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                format!("Failed to subscribe: {}", e),
+                true
+            )),
+        }
+    }
+
+    /// Unsubscribe from the current channel
+    fn unsubscribe(&mut self) {
+        let Some(session) = self.session.as_ref().filter(|s| s.kind == crate::ui::models::Session::CHANNEL) else {
+            self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                "Current session is not a channel".to_string(),
+                true
+            ));
+            return;
+        };
+        let id = session.id;
+        match subscribe(&self.token, id, false) {
+            Ok(_) => {
+                self.left = Some(id);
+                self.change_session(None);
+                self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), "Unsubscribed".to_string(), true));
+            }
+            Err(e) => self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                format!("Failed to unsubscribe: {}", e),
+                true
+            )),
+        }
+    }
+
+    /// Change notification settings of current session and save them to the server
+    fn update_settings<F: FnOnce(&mut SessionSettings)>(&mut self, change: F) {
+        let Some(session) = &mut self.session else {
+            self.messages.push(crate::ui::models::Message::new(
+                "SYSTEM".to_string(),
+                "No session selected".to_string(),
+                true
+            ));
+            return;
+        };
+        let mut settings = session.settings;
+        change(&mut settings);
+        let message = match update_settings(&self.token, session.id, &settings) {
+            Ok(settings) => {
+                session.settings = settings;
+                let now = chrono::Utc::now().timestamp_millis();
+                let notify = if settings.notify == SessionSettings::NOTIFY_MENTIONS { "mentions only" } else { "all messages" };
+                match settings.muted_until {
+                    Some(i64::MAX) => format!("Session muted, notify on {}", notify),
+                    Some(until) if until > now => {
+                        let until = chrono::DateTime::from_timestamp_millis(until)
+                            .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+                            .unwrap_or_default();
+                        format!("Session muted until {}, notify on {}", until, notify)
+                    }
+                    _ => format!("Session not muted, notify on {}", notify),
+                }
+            }
+            Err(e) => format!("Failed to update session settings: {}", e),
+        };
+        self.messages.push(crate::ui::models::Message::new("SYSTEM".to_string(), message, true));
+    }
+
     /// Send a file to the current session
     fn send_file(&mut self, me: &Me, stream: &Arc<Mutex<OwnedWriteHalf>>, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
         if let Some(session) = &self.session {
@@ -294,10 +664,7 @@ impl ChatComponent {
                 content: Some(pubchat::core::message::message::Content::Chrt(Chrt{
                     sender: me.id,
                     session: session_id as u64,
-                    receivers: session.members.iter()
-                        .map(|m| m.id as u64)
-                        .filter(|id| *id != me.id)
-                        .collect(),
+                    receivers: session.receivers(me.id),
                     ctype: ChatType::File as i32,
                     message: Some(pubchat::core::message::chrt::Message::Blob(Blob{
                         id: upload_result.id as u64,
@@ -310,6 +677,7 @@ impl ChatComponent {
                         .unwrap()
                         .as_millis() as u64,
                     uname: me.name.to_string(),
+                    flag: None,
                 })),
             };
             
@@ -330,6 +698,13 @@ impl ChatComponent {
                 mtype: Type::Chrt as i32,
                 content: format!("{}", upload_result),
                 timestamp: chat_request.ts as i64,
+                payload: Some(Payload::Blob(Blob{
+                    id: upload_result.id as u64,
+                    name: upload_result.name.clone(),
+                    size: upload_result.size.to_string(),
+                    exp: upload_result.exp.clone(),
+                })),
+                version: PAYLOAD_VERSION,
             };
             cache::message_cache().add_message(session.id, message);
             // Send message
@@ -342,4 +717,37 @@ impl ChatComponent {
             Err(anyhow::anyhow!("No session selected").into())
         }
     }
-}
\ No newline at end of file
+}
+
+fn local_time(ts: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(ts)
+        .map(|t| t.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M").to_string())
+        .unwrap_or_default()
+}
+
+/// Parse the time of /schedule into a timestamp in milliseconds.
+/// Accepts a relative offset (+10m, +2h, +1d), a time of day (HH:MM, next occurrence)
+/// or a local date time (YYYY-MM-DDTHH:MM).
+fn parse_when(when: &str, now: chrono::DateTime<chrono::Local>) -> Option<i64> {
+    use chrono::{Duration, NaiveDateTime, NaiveTime, TimeZone};
+    if let Some(offset) = when.strip_prefix('+') {
+        let (n, unit) = offset.split_at(offset.len().checked_sub(1)?);
+        let n: i64 = n.parse().ok().filter(|n| *n > 0)?;
+        let delta = match unit {
+            "m" => Duration::try_minutes(n)?,
+            "h" => Duration::try_hours(n)?,
+            "d" => Duration::try_days(n)?,
+            _ => return None,
+        };
+        return now.checked_add_signed(delta).map(|t| t.timestamp_millis());
+    }
+    if let Ok(time) = NaiveTime::parse_from_str(when, "%H:%M") {
+        let mut at = now.date_naive().and_time(time);
+        if at <= now.naive_local() {
+            at += Duration::days(1);
+        }
+        return chrono::Local.from_local_datetime(&at).earliest().map(|t| t.timestamp_millis());
+    }
+    let at = NaiveDateTime::parse_from_str(when, "%Y-%m-%dT%H:%M").ok()?;
+    chrono::Local.from_local_datetime(&at).earliest().map(|t| t.timestamp_millis())
+}
@@ -5,6 +5,7 @@ use tokio::sync::Mutex;
 
 use crate::ui::screen::chat::ChatScreen;
 use crate::ui::screen::contact::ContactListScreen;
+use pubchat::core::message::{Scope, Severity};
 
 
 #[derive(Debug, Clone)]
@@ -28,6 +29,78 @@ impl Message {
 
 }
 
+//连接服务推送到界面的消息
+#[derive(Debug, Clone)]
+pub enum Incoming {
+    //silent为连接服务按接收者通知设置标记的静默消息
+    Chat { message: core::api::types::message::Message, silent: bool },
+    Notice(Notice),
+    //发送的消息被服务器审核拒绝
+    Rejected { session: i64, reason: String },
+}
+
+//系统公告
+#[derive(Debug, Clone, PartialEq)]
+pub struct Notice {
+    pub id: i64,
+    pub severity: i32,
+    pub scope: i32,
+    pub target: i64,
+    pub text: String,
+    pub exp: Option<i64>,
+}
+
+impl Notice {
+    pub fn active(&self) -> bool {
+        self.exp.is_none_or(|exp| exp > chrono::Utc::now().timestamp_millis())
+    }
+
+    //WARN及以上级别的公告以横幅形式展示
+    pub fn banner(&self) -> bool {
+        self.severity >= Severity::Warn as i32
+    }
+
+    //会话公告只在对应会话中展示
+    pub fn visible_in(&self, session: i64) -> bool {
+        self.scope != Scope::Session as i32 || self.target == session
+    }
+
+    pub fn severity_name(&self) -> &'static str {
+        Severity::try_from(self.severity).unwrap_or(Severity::Info).as_str_name()
+    }
+
+    //转换为聊天窗口中的系统消息
+    pub fn to_message(&self) -> Message {
+        Message::new("SYSTEM".to_string(), format!("[{}] {}", self.severity_name(), self.text), false)
+    }
+}
+
+impl From<core::api::types::notice::NoticeResponse> for Notice {
+    fn from(notice: core::api::types::notice::NoticeResponse) -> Self {
+        Self {
+            id: notice.id,
+            severity: notice.severity,
+            scope: notice.scope,
+            target: notice.target,
+            text: notice.text,
+            exp: notice.exp,
+        }
+    }
+}
+
+impl From<pubchat::core::message::Notice> for Notice {
+    fn from(notice: pubchat::core::message::Notice) -> Self {
+        Self {
+            id: notice.id as i64,
+            severity: notice.severity,
+            scope: notice.scope,
+            target: notice.target as i64,
+            text: notice.text,
+            exp: notice.exp.map(|e| e as i64),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub struct Contact {
     pub id: i64,
@@ -53,6 +126,29 @@ pub struct Session {
     pub id: i64,
     pub name: String,
     pub members: Vec<Contact>,
+    //未读消息数
+    pub unread: i64,
+    //最后一条消息预览
+    pub preview: Option<String>,
+    //通知设置
+    pub settings: core::api::types::session::SessionSettings,
+    //会话类型: 0-单聊, 1-群聊, 2-频道
+    pub kind: i8,
+}
+
+impl Session {
+    pub const CHANNEL: i8 = 2;
+
+    //聊天消息携带的接收者，频道的订阅者由连接服务按同步的成员投递，不在消息中携带
+    pub fn receivers(&self, me: u64) -> Vec<u64> {
+        if self.kind == Self::CHANNEL {
+            return vec![];
+        }
+        self.members.iter()
+            .map(|m| m.id as u64)
+            .filter(|id| *id != me)
+            .collect()
+    }
 }
 
 impl From<core::api::types::session::SessionResponse> for Session {
@@ -61,6 +157,10 @@ impl From<core::api::types::session::SessionResponse> for Session {
             id: session.id,
             name: session.name,
             members: vec![],
+            unread: session.unread,
+            preview: session.last_message.map(|m| format!("{}: {}", m.uname, m.content)),
+            settings: session.settings,
+            kind: session.kind,
         }
     }
 }
@@ -75,6 +175,10 @@ impl From<core::api::types::session::SessionDetailResponse> for Session {
             id: session.id,
             name: session.name,
             members: members,
+            unread: 0,
+            preview: None,
+            settings: Default::default(),
+            kind: session.kind,
         }
     }
 }
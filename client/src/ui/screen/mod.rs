@@ -1,4 +1,5 @@
-use ratatui::Frame;
+use ratatui::{prelude::*, widgets::Paragraph, Frame};
+use pubchat::core::message::Severity;
 
 use crate::ui::{models::{App, View}};
 
@@ -8,7 +9,18 @@ pub mod auth;
 
 impl App {
     pub fn render(&self, frame: &mut Frame) {
-        let size = frame.area();
+        let mut size = frame.area();
+        if let Some(notice) = self.chat.chat.banner() {
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Length(1), Constraint::Min(1)])
+                .split(size);
+            let color = if notice.severity >= Severity::Critical as i32 { Color::Red } else { Color::Yellow };
+            let banner = Paragraph::new(format!(" {} {}", notice.severity_name(), notice.text))
+                .style(Style::default().fg(Color::Black).bg(color));
+            frame.render_widget(banner, chunks[0]);
+            size = chunks[1];
+        }
         match &self.view {
             View::Chat => {
                 self.chat.render(frame, size)
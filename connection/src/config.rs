@@ -0,0 +1,16 @@
+use std::sync::OnceLock;
+
+use core::config::ConnectionConfig;
+
+pub static CONFIG: OnceLock<ConnectionConfig> = OnceLock::new();
+
+//加载并校验配置，配置不合法时直接退出
+pub fn init() -> &'static ConnectionConfig {
+    let config = core::config::load_or_exit::<ConnectionConfig>("connection");
+    core::config::print("connection", &config);
+    CONFIG.get_or_init(|| config)
+}
+
+pub fn get() -> &'static ConnectionConfig {
+    CONFIG.get().expect("配置未初始化")
+}
@@ -1,49 +1,230 @@
 use anyhow::Result;
 use tokio::{io::AsyncWriteExt, sync::Mutex};
 use log::{info, warn, error};
-use pubchat::core::message::Message;
-use std::{collections::HashMap, sync::OnceLock};
+use pubchat::core::message::{Chrs, Chrt, Members, Message, Moderation, SessionKind, message::Content};
+use std::{collections::{HashMap, HashSet}, sync::OnceLock};
 use tokio::net::tcp::OwnedWriteHalf;
 use tokio::net::TcpStream;
 use pubchat::core::message::Type;
 use pubchat::core::codec::decode;
 use std::sync::Arc;
 use crate::handlers;
+use core::api::types::session::SessionSettings;
+use core::api::types::moderation::Moderator;
 
 
 // 存储所有连接的客户端
 #[derive(Debug)]
 pub struct Client {
     pub uid: u64,
+    //昵称，用于判断消息是否提及该用户
+    pub uname: String,
+    //所在组织id，用于投递组织公告
+    pub oid: u64,
     pub writer: Arc<Mutex<OwnedWriteHalf>>,
 }
 
 pub static CLIENTS: OnceLock<Mutex<HashMap<u64, Client>>> = OnceLock::new();
+//会话成员，频道的订阅者可能很多，使用集合判断接收者及发言权限
+#[derive(Debug, Default)]
+pub struct SessionMembers {
+    pub kind: i32,
+    //会话所属组织，用于选择内容审核规则
+    pub oid: u64,
+    pub members: HashSet<u64>,
+    pub admins: HashSet<u64>,
+}
+
+impl SessionMembers {
+    //频道只有管理员可以发言，其余会话的成员都可以发言
+    pub fn can_post(&self, uid: u64) -> bool {
+        self.members.contains(&uid) && (self.kind != SessionKind::Channel as i32 || self.admins.contains(&uid))
+    }
+}
+
+//会话成员缓存，由会话服务在成员变更时同步，未命中时(如服务重启后)从会话服务加载
+pub static MEMBERS: OnceLock<Mutex<HashMap<u64, Arc<SessionMembers>>>> = OnceLock::new();
+//用户的会话通知设置缓存，键为(会话id, 用户id)，由会话服务在设置变更时同步，首次投递会话的消息前从会话服务加载
+pub static SETTINGS: OnceLock<Mutex<HashMap<(u64, u64), SessionSettings>>> = OnceLock::new();
+//已从会话服务加载过成员及通知设置的会话，成员变更只同步成员列表，不能据此判断通知设置是否已加载
+pub static LOADED: OnceLock<Mutex<HashSet<u64>>> = OnceLock::new();
+//组织的内容审核规则缓存，键为组织id，由会话服务在规则变更时同步
+pub static RULES: OnceLock<Mutex<HashMap<u64, Arc<Moderator>>>> = OnceLock::new();
 
 pub async fn init() {
     CLIENTS.set(Mutex::new(HashMap::new())).expect("初始化客户端列表失败");
+    MEMBERS.set(Mutex::new(HashMap::new())).expect("初始化会话成员缓存失败");
+    SETTINGS.set(Mutex::new(HashMap::new())).expect("初始化会话通知设置缓存失败");
+    LOADED.set(Mutex::new(HashSet::new())).expect("初始化已加载会话列表失败");
+    RULES.set(Mutex::new(HashMap::new())).expect("初始化内容审核规则缓存失败");
 }
 
-pub async fn add_client(uid: u64, client: Client) {
-    let mut lock = CLIENTS.get().expect("获取客户端列表失败").lock().await;
-    lock.insert(uid, client);
+//规则在会话服务写入时已校验，这里跳过无法编译的规则
+pub async fn set_rules(moderation: &Moderation) {
+    let moderator = Moderator::lenient(&moderation.rules);
+    let mut lock = RULES.get().expect("获取内容审核规则缓存失败").lock().await;
+    if moderator.is_empty() {
+        lock.remove(&moderation.oid);
+    } else {
+        lock.insert(moderation.oid, Arc::new(moderator));
+    }
 }
 
-//向特定用户发送消息
-pub async fn send_message(uid: u64, message: &Message) -> Result<()> {
-    let lock = CLIENTS.get().expect("获取客户端列表失败").lock().await;
-    if let Some(client) = lock.get(&uid) {
+//获取会话所属组织的审核规则，与定时消息一致按会话而不是发送者选择规则
+//会话未同步过或组织没有配置规则时返回None
+pub async fn moderator(session: u64) -> Option<Arc<Moderator>> {
+    let oid = members(session).await?.oid;
+    let lock = RULES.get().expect("获取内容审核规则缓存失败").lock().await;
+    lock.get(&oid).cloned()
+}
+
+//向在线用户发送消息，用户不在线时忽略；释放客户端列表的锁后再写入
+pub async fn send(uid: u64, message: &Message) -> Result<()> {
+    let writer = CLIENTS.get().expect("获取客户端列表失败").lock().await.get(&uid).map(|c| c.writer.clone());
+    if let Some(writer) = writer {
         let encoded = pubchat::core::codec::encode(message)?;
-        let mut writer = client.writer.lock().await;
+        let mut writer = writer.lock().await;
         writer.write_all(&encoded).await?;
         writer.flush().await?;
-        info!("Message sent to client {}", uid);
-    } else {
-        warn!("Client {} not found, message not sent", uid);
     }
     Ok(())
 }
 
+pub async fn set_setting(session: u64, uid: u64, settings: SessionSettings) {
+    let mut lock = SETTINGS.get().expect("获取会话通知设置缓存失败").lock().await;
+    if settings == SessionSettings::default() {
+        lock.remove(&(session, uid));
+    } else {
+        lock.insert((session, uid), settings);
+    }
+}
+
+impl From<&Members> for SessionMembers {
+    fn from(members: &Members) -> Self {
+        Self {
+            kind: members.kind,
+            oid: members.oid,
+            members: members.members.iter().copied().collect(),
+            admins: members.admins.iter().copied().collect(),
+        }
+    }
+}
+
+pub async fn set_members(members: &Members) {
+    let mut lock = MEMBERS.get().expect("获取会话成员缓存失败").lock().await;
+    lock.insert(members.session, Arc::new(members.into()));
+}
+
+//获取缓存的会话成员，未同步过的会话返回None
+pub async fn members(session: u64) -> Option<Arc<SessionMembers>> {
+    let lock = MEMBERS.get().expect("获取会话成员缓存失败").lock().await;
+    lock.get(&session).cloned()
+}
+
+//获取会话成员，会话未加载过时从会话服务加载该会话的成员、通知设置及所属组织的审核规则，会话不存在时返回None
+//加载期间收到的变更更新，已写入缓存的成员及设置不被覆盖
+pub async fn load_members(session: u64) -> Result<Option<Arc<SessionMembers>>> {
+    let loaded = LOADED.get().expect("获取已加载会话列表失败").lock().await.contains(&session);
+    if loaded && let Some(members) = members(session).await {
+        return Ok(Some(members));
+    }
+    let Some(state) = crate::session::load(session).await? else {
+        return Ok(None);
+    };
+    set_rules(&state.moderation).await;
+    {
+        let mut lock = SETTINGS.get().expect("获取会话通知设置缓存失败").lock().await;
+        for setting in &state.settings {
+            lock.entry((session, setting.uid)).or_insert_with(|| setting.into());
+        }
+    }
+    let members = {
+        let mut lock = MEMBERS.get().expect("获取会话成员缓存失败").lock().await;
+        lock.entry(session).or_insert_with(|| Arc::new((&state.members).into())).clone()
+    };
+    LOADED.get().expect("获取已加载会话列表失败").lock().await.insert(session);
+    info!("Loaded members and settings of session {} from session service", session);
+    Ok(Some(members))
+}
+
+pub async fn add_client(uid: u64, client: Client) {
+    let mut lock = CLIENTS.get().expect("获取客户端列表失败").lock().await;
+    lock.insert(uid, client);
+}
+
+//向在线的接收者投递聊天消息，返回成功投递的数量
+//接收者按引用遍历，不为每条消息复制成员列表；接收者静音了会话或消息未提及接收者时标记为静默
+//持锁时只取出接收者的连接及是否静默，释放锁后并发写入，慢连接不阻塞其他会话及新连接
+pub async fn deliver<'a, I: Iterator<Item = &'a u64>>(message: &Message, chat: &Chrt, receivers: I) -> usize {
+    let text = chat.message.as_ref().map(|m| m.to_string()).unwrap_or_default();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64;
+    let targets: Vec<(u64, Arc<Mutex<OwnedWriteHalf>>, bool)> = {
+        let settings = SETTINGS.get().expect("获取会话通知设置缓存失败").lock().await;
+        let clients = CLIENTS.get().expect("获取客户端列表失败").lock().await;
+        receivers.filter_map(|uid| clients.get(uid))
+            .map(|client| {
+                let silent = settings.get(&(chat.session, client.uid))
+                    .is_some_and(|s| !s.notify(&text, &client.uname, now));
+                (client.uid, client.writer.clone(), silent)
+            })
+            .collect()
+    };
+    let results = futures::future::join_all(targets.into_iter().map(|(uid, writer, silent)| async move {
+        let response = Message {
+            id: message.id,
+            ts: message.ts,
+            mtype: Type::Chrs as i32,
+            content: Some(Content::Chrs(Chrs {
+                sender: chat.sender,
+                receiver: uid,
+                session: chat.session,
+                ctype: chat.ctype,
+                message: chat.message.as_ref().map(pubchat::core::message::chrs::Message::from),
+                ts: chat.ts,
+                uname: chat.uname.clone(),
+                silent,
+            })),
+        };
+        let result = async {
+            let encoded = pubchat::core::codec::encode(&response)?;
+            let mut writer = writer.lock().await;
+            writer.write_all(&encoded).await?;
+            writer.flush().await
+        }.await;
+        if let Err(e) = &result {
+            warn!("Failed to send message to client {}: {}", uid, e);
+        }
+        result.is_ok()
+    })).await;
+    results.into_iter().filter(|sent| *sent).count()
+}
+
+//向满足条件的所有在线用户发送消息，返回成功发送的数量
+//与deliver一样持锁时只取出连接，释放锁后并发写入
+pub async fn broadcast<F: Fn(&Client) -> bool>(message: &Message, filter: F) -> Result<usize> {
+    let encoded = pubchat::core::codec::encode(message)?;
+    let targets: Vec<(u64, Arc<Mutex<OwnedWriteHalf>>)> = CLIENTS.get().expect("获取客户端列表失败").lock().await
+        .values()
+        .filter(|c| filter(c))
+        .map(|c| (c.uid, c.writer.clone()))
+        .collect();
+    let encoded = &encoded;
+    let results = futures::future::join_all(targets.into_iter().map(|(uid, writer)| async move {
+        let mut writer = writer.lock().await;
+        let result = async {
+            writer.write_all(encoded).await?;
+            writer.flush().await
+        }.await;
+        if let Err(e) = &result {
+            warn!("Failed to send message to client {}: {}", uid, e);
+        }
+        result.is_ok()
+    })).await;
+    Ok(results.into_iter().filter(|sent| *sent).count())
+}
 
 pub async fn handle_client(
     socket: TcpStream,
@@ -55,8 +236,11 @@ pub async fn handle_client(
     let message = decode::<Message, _>(&mut reader).await?;
     info!("Received message type: {:?}", message.mtype);
 
+    //连接的用户，只有完成连接初始化的用户可以发送聊天消息
+    let mut uid = None;
     if message.mtype == Type::Cort as i32 {
         let client: Client = handlers::connect::handle(&message, writer).await?;
+        uid = Some(client.uid);
         add_client(client.uid, client).await;
     }
     loop {
@@ -71,7 +255,7 @@ pub async fn handle_client(
             }
         }
         if message.mtype == Type::Chrt as i32 {
-            let r = handlers::chat::handle(message).await;
+            let r = handlers::chat::handle(uid, message).await;
             if let Err(e) = r {
                 error!("Failed to handle chat: {}", e);
             }
@@ -1,19 +1,56 @@
 use anyhow::{Ok, Result};
-use log::{info, error};
-use pubchat::core::message::Message;
-use pubchat::core::message::message;
-use crate::queue;
-pub async fn handle(message: Message) -> Result<()> { 
-    if let Some(message::Content::Chrt(chat_req)) = &message.content {
+use log::{info, warn, error};
+use pubchat::core::message::{ChatType, Message, Reject, Type};
+use pubchat::core::message::{chrt, message};
+use core::response::ApiErr;
+use crate::{connection, queue};
+
+//uid为连接的用户，发送者必须是连接的用户，且有权在会话中发言(频道只有管理员可以发言)；客户端指定的接收者不可信，发布前清空
+//文本消息按会话所属组织的审核规则审核：拒绝的消息不发布并告知发送者原因，屏蔽的消息发布屏蔽后的文本，需要人工审核的消息标记原因后正常发布
+pub async fn handle(uid: Option<u64>, mut message: Message) -> Result<()> { 
+    if let Some(message::Content::Chrt(chat_req)) = &mut message.content {
+        if uid != Some(chat_req.sender) {
+            return Err(ApiErr::Bad(401, format!("sender {} does not match connection user {:?}", chat_req.sender, uid)).into());
+        }
+        //系统消息只能由会话服务发布
+        if chat_req.ctype == ChatType::System as i32 {
+            return Err(ApiErr::Bad(403, format!("user {} cannot send system messages", chat_req.sender)).into());
+        }
+        //接收者以会话服务同步的成员为准，缓存未命中时先从会话服务加载
+        let Some(members) = connection::load_members(chat_req.session).await? else {
+            return Err(ApiErr::Bad(404, format!("session {} does not exist", chat_req.session)).into());
+        };
+        if !members.can_post(chat_req.sender) {
+            return Err(ApiErr::Bad(403, format!("user {} cannot post in session {}", chat_req.sender, chat_req.session)).into());
+        }
+        chat_req.receivers.clear();
+        if let Some(moderator) = connection::moderator(chat_req.session).await
+            && let Some(chrt::Message::Text(text)) = &mut chat_req.message {
+            let verdict = moderator.check(&text.text);
+            if let Some(reason) = verdict.rejected {
+                warn!("Message {} from user {} rejected: {}", message.id, chat_req.sender, reason);
+                let reject = Message {
+                    id: message.id,
+                    ts: message.ts,
+                    mtype: Type::Rjct as i32,
+                    content: Some(message::Content::Reject(Reject { id: message.id, session: chat_req.session, reason })),
+                };
+                return connection::send(chat_req.sender, &reject).await;
+            }
+            if let Some(masked) = verdict.masked {
+                text.text = masked;
+            }
+            chat_req.flag = verdict.flagged;
+        }
         info!("Processing ChatRequest from user {}: session={}, message='{:?}', timestamp={}",
                 chat_req.sender, chat_req.session, chat_req.message, chat_req.ts);
         // Publish message to RabbitMQ
         if let Err(e) = queue::publish(&message).await {
             error!("Failed to publish message to RabbitMQ: {}", e);
-        }else{
+        }else if let Some(message::Content::Chrt(chat_req)) = &message.content {
             info!("Broadcast ChatResponse for user {} in room {}", 
                 chat_req.sender, chat_req.session);
         }
     }
     Ok(())
-}
\ No newline at end of file
+}
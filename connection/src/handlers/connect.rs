@@ -39,6 +39,8 @@ pub async fn handle(message: &Message, mut writer: OwnedWriteHalf) -> Result<Cli
             // 注册客户端到连接管理器
             let client = Client {
                 uid: uid,
+                uname: user.name.to_string(),
+                oid: user.oid as u64,
                 writer: Arc::new(Mutex::new(writer)),
             };
             return Ok(client);
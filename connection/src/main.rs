@@ -5,19 +5,23 @@ use dotenv::dotenv;
 mod connection;
 mod queue;
 mod handlers;
+mod config;
+mod session;
+mod test;
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     core::log::init(Some(".pubchat_connection.log"));
+    let config = config::init();
+    core::auth::init(&config.auth);
     // Initialize RabbitMQ
-    core::auth::init();
-    queue::init().await?;
+    queue::init(&config.rabbitmq).await?;
     connection::init().await;
     
     // Bind the listener to the address
-    let listener = TcpListener::bind("127.0.0.1:8080").await?;
-    info!("Connection service listening on 127.0.0.1:8080");
+    let listener = TcpListener::bind(&config.server.bind).await?;
+    info!("Connection service listening on {}", config.server.bind);
 
     loop {
         let (socket, _) = listener.accept().await?;
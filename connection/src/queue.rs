@@ -1,36 +1,39 @@
-use anyhow::Result;
+use anyhow::{anyhow, Result};
+use core::queue::{can_retry, ATTEMPTS, EXCHANGE, FAILED_AT, REASON, RETRY_BACKOFF, ROUTING_KEY, SOURCE};
 use deadpool::{managed::Pool, Runtime};
 use deadpool_lapin::{Config, Manager};
 use futures::StreamExt;
 use lapin::{
     options::*, BasicProperties
 };
-use log::{info, error};
-use pubchat::core::message::{Chrs, Message, Type, message::Content};
+use lapin::message::Delivery;
+use lapin::types::AMQPValue;
+use log::{info, warn, error};
+use pubchat::core::message::{ChatType, Chrt, Message, Scope, Type, message::Content};
 use std::{sync::OnceLock};
-use crate::connection;
+use core::config::RabbitConfig;
+use crate::{config, connection};
 
 
 pub static POOL: OnceLock<Pool<Manager>> = OnceLock::new();
 
-pub async fn init() -> Result<()> {
-    let connstring = dotenv::var("RABBITMQ_ADDR").expect("请设置RabbitMQ连接地址RABBITMQ_ADDR");
-    let session_message_queue_name = dotenv::var("RABBITMQ_SESSION_MESSAGE_QUEUE_NAME").expect("请设置RabbitMQ队列名称RABBITMQ_SESSION_MESSAGE_QUEUE_NAME");
-    let exc_name = dotenv::var("RABBITMQ_MESSAGE_EXCHANGE").expect("请设置RabbitMQ交换机名称RABBITMQ_MESSAGE_EXCHANGE");
+pub async fn init(config: &RabbitConfig) -> Result<()> {
+    let session_message_queue_name = &config.queue;
+    let exc_name = &config.exchange;
     let mut cfg = Config::default();
-    cfg.url = Some(connstring.into());
+    cfg.url = Some(config.addr.clone());
     let pool = cfg.create_pool(Some(Runtime::Tokio1))?;
     let channel = pool.get().await?.create_channel().await?;
     let _queue = channel
             .queue_declare(
-                &session_message_queue_name,
+                session_message_queue_name,
                 QueueDeclareOptions::default(),
                 Default::default(),
             )
             .await?; 
     let _exchange = channel
             .exchange_declare(
-                &exc_name,
+                exc_name,
                 lapin::ExchangeKind::Topic,
                 ExchangeDeclareOptions::default(),
                 Default::default(),
@@ -38,11 +41,28 @@ pub async fn init() -> Result<()> {
             .await?;
     channel
             .queue_bind(
-                &session_message_queue_name, 
-                &exc_name, 
+                session_message_queue_name, 
+                exc_name, 
                 "#", 
                 QueueBindOptions::default(), 
                 Default::default()).await?;
+    // 死信交换机及死信队列
+    channel
+            .exchange_declare(
+                &config.dead_letter_exchange,
+                lapin::ExchangeKind::Direct,
+                ExchangeDeclareOptions::default(),
+                Default::default(),
+            )
+            .await?;
+    channel.queue_declare(&config.dead_letter_queue, QueueDeclareOptions::default(), Default::default()).await?;
+    channel
+            .queue_bind(
+                &config.dead_letter_queue,
+                &config.dead_letter_exchange,
+                &config.dead_letter_queue,
+                QueueBindOptions::default(),
+                Default::default()).await?;
     // 启动RabbitMQ消息消费
     tokio::spawn(async move {
         if let Err(e) = receive().await {
@@ -56,7 +76,7 @@ pub async fn init() -> Result<()> {
 
 
 pub async fn receive() -> Result<()> {
-    let session_message_queue_name = dotenv::var("RABBITMQ_SESSION_MESSAGE_QUEUE_NAME").expect("请设置RabbitMQ队列名称RABBITMQ_SESSION_MESSAGE_QUEUE_NAME");
+    let session_message_queue_name = &config::get().rabbitmq.queue;
     let pool = POOL.get();
     let pool = pool.expect("");
     let conn = pool.get().await?;
@@ -64,7 +84,7 @@ pub async fn receive() -> Result<()> {
     // 创建消费者
     let mut consumer = channel
         .basic_consume(
-            &session_message_queue_name,
+            session_message_queue_name,
             "connection_consumer",
             lapin::options::BasicConsumeOptions::default(),
             Default::default(),
@@ -79,53 +99,30 @@ pub async fn receive() -> Result<()> {
             match serde_json::from_slice::<Message>(&delivery.data) {
                 std::result::Result::Ok(message) => {
                     info!("Received message from RabbitMQ: type={:?}", message.mtype);
-                    // 确定消息接收者
-                    match message.mtype {
-                        t if t == Type::Chrt as i32 => {
-                            if let Some(Content::Chrt(resp)) = &message.content {
-                                // 如果有目标用户，则发送消息到客户端
-                                for receiver in &resp.receivers {
-                                    let response = Message {
-                                        id: message.id,
-                                        ts: message.ts,
-                                        mtype: Type::Chrs as i32,
-                                        content: Some(Content::Chrs(Chrs{
-                                            sender: resp.sender,
-                                            receiver: *receiver,
-                                            session: resp.session,
-                                            ctype: resp.ctype,
-                                            message: resp.message
-                                                .as_ref()
-                                                .map(|m|pubchat::core::message::chrs::Message::from(m)),
-                                            ts: resp.ts,
-                                            uname: resp.uname.clone(),
-
-                                        }))
-                                    };
-                                    if let Err(e) = connection::send_message(*receiver , &response).await {
-                                        error!("Failed to send message to client {}: {}", receiver, e);
-                                    }
-                                }
+                    match handle(&message).await {
+                        // 确认消息处理完成
+                        std::result::Result::Ok(()) => {
+                            if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+                                error!("Failed to acknowledge message: {}", e);
+                            }
+                        }
+                        // 处理失败的消息带上失败次数重试，超过次数后转入死信队列
+                        Err(e) => {
+                            let attempts = attempts(&delivery) + 1;
+                            if can_retry(attempts, config::get().rabbitmq.max_retries) {
+                                warn!("Failed to handle message {} (attempt {}), retrying: {}", message.id, attempts, e);
+                                retry(channel.clone(), delivery, attempts);
                             } else {
-                                error!("Invalid chat message");
+                                error!("Failed to handle message {} after {} attempts: {}", message.id, attempts, e);
+                                reject(&channel, &delivery, attempts, &e.to_string()).await;
                             }
-                        },
-                        _ => {
-                            error!("Unhandled message type: {}", message.mtype);
-                        },
-                    };
-                    
-                    // 确认消息处理完成
-                    if let Err(e) = delivery.ack(lapin::options::BasicAckOptions::default()).await {
-                        error!("Failed to acknowledge message: {}", e);
+                        }
                     }
                 },
                 Err(e) => {
                     error!("Failed to parse message from RabbitMQ: {}", e);
-                    // 即使解析失败也确认消息，避免消息堆积
-                    if let Err(e) = delivery.ack(lapin::options::BasicAckOptions::default()).await {
-                        error!("Failed to acknowledge message: {}", e);
-                    }
+                    // 无法解析的消息重试也不会成功，直接转入死信队列
+                    reject(&channel, &delivery, 1, &format!("消息解析失败: {}", e)).await;
                 }
             }
         }
@@ -134,10 +131,167 @@ pub async fn receive() -> Result<()> {
     Ok(())
 }
 
+//按消息类型处理，返回错误时由调用方重试
+async fn handle(message: &Message) -> Result<()> {
+    match message.mtype {
+        t if t == Type::Chrt as i32 => {
+            let Some(Content::Chrt(chat)) = &message.content else {
+                return Err(anyhow!("Invalid chat message"));
+            };
+            let sent = dispatch_chat(message, chat).await?;
+            info!("Chat message {} delivered to {} clients", message.id, sent);
+        },
+        t if t == Type::Memb as i32 => {
+            let Some(Content::Members(members)) = &message.content else {
+                return Err(anyhow!("Invalid members message"));
+            };
+            connection::set_members(members).await;
+        },
+        t if t == Type::Sett as i32 => {
+            let Some(Content::Setting(setting)) = &message.content else {
+                return Err(anyhow!("Invalid setting message"));
+            };
+            connection::set_setting(setting.session, setting.uid, setting.into()).await;
+        },
+        t if t == Type::Modr as i32 => {
+            let Some(Content::Moderation(moderation)) = &message.content else {
+                return Err(anyhow!("Invalid moderation message"));
+            };
+            connection::set_rules(moderation).await;
+        },
+        t if t == Type::Noti as i32 => {
+            let Some(Content::Notice(notice)) = &message.content else {
+                return Err(anyhow!("Invalid notice message"));
+            };
+            dispatch_notice(message, notice).await?;
+        },
+        _ => {
+            error!("Unhandled message type: {}", message.mtype);
+        },
+    }
+    Ok(())
+}
+
+//消息此前已失败的次数，记录在消息头中
+fn attempts(delivery: &Delivery) -> u32 {
+    match delivery.properties.headers().as_ref().and_then(|h| h.inner().get(ATTEMPTS)) {
+        Some(AMQPValue::LongUInt(n)) => *n,
+        _ => 0,
+    }
+}
+
+//等待后带上失败次数重新发布到当前服务的队列并确认原消息，发布失败时原消息重新入队
+fn retry(channel: lapin::Channel, delivery: Delivery, attempts: u32) {
+    tokio::spawn(async move {
+        tokio::time::sleep(RETRY_BACKOFF * attempts).await;
+        let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+        headers.insert(ATTEMPTS.into(), AMQPValue::LongUInt(attempts));
+        let properties = delivery.properties.clone().with_headers(headers);
+        let published = async {
+            channel.basic_publish("", &config::get().rabbitmq.queue, BasicPublishOptions::default(), &delivery.data, properties)
+                .await?
+                .await?;
+            anyhow::Ok(())
+        }.await;
+        let result = match published {
+            std::result::Result::Ok(()) => delivery.ack(BasicAckOptions::default()).await,
+            Err(e) => {
+                error!("Failed to republish message for retry: {}", e);
+                delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await
+            }
+        };
+        if let Err(e) = result {
+            error!("Failed to settle retried message: {}", e);
+        }
+    });
+}
+
+//转入死信队列并确认原消息，转入失败时重新入队
+async fn reject(channel: &lapin::Channel, delivery: &Delivery, attempts: u32, reason: &str) {
+    let result = match dead_letter(channel, delivery, attempts, reason).await {
+        std::result::Result::Ok(_) => delivery.ack(BasicAckOptions::default()).await,
+        Err(e) => {
+            error!("Failed to dead-letter message: {}", e);
+            delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await
+        }
+    };
+    if let Err(e) = result {
+        error!("Failed to settle rejected message: {}", e);
+    }
+}
+
+//将消息发布到死信交换机，消息头与会话服务的死信格式一致，可由会话服务的管理接口查看和重放
+async fn dead_letter(channel: &lapin::Channel, delivery: &Delivery, attempts: u32, reason: &str) -> Result<()> {
+    let rabbitmq = &config::get().rabbitmq;
+    let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+    headers.insert(SOURCE.into(), AMQPValue::LongString(rabbitmq.queue.as_str().into()));
+    headers.insert(EXCHANGE.into(), AMQPValue::LongString(delivery.exchange.as_str().into()));
+    headers.insert(ROUTING_KEY.into(), AMQPValue::LongString(delivery.routing_key.as_str().into()));
+    headers.insert(REASON.into(), AMQPValue::LongString(reason.into()));
+    headers.insert(ATTEMPTS.into(), AMQPValue::LongUInt(attempts));
+    headers.insert(FAILED_AT.into(), AMQPValue::LongLongInt(std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as i64));
+    channel.basic_publish(
+            &rabbitmq.dead_letter_exchange,
+            &rabbitmq.dead_letter_queue,
+            BasicPublishOptions::default(),
+            &delivery.data,
+            delivery.properties.clone().with_headers(headers),
+        )
+        .await?
+        .await?;
+    Ok(())
+}
+
+//投递聊天消息，接收者以会话成员为准，缓存未命中时从会话服务加载，加载失败时返回错误由调用方重试
+//已不存在的会话只投递会话服务发布的系统消息
+//系统消息另外投递给会话服务指定的接收者(如被移出的成员)，频道的系统消息只携带这部分接收者
+async fn dispatch_chat(message: &Message, chat: &Chrt) -> Result<usize> {
+    let system = chat.ctype == ChatType::System as i32;
+    let sent = match connection::load_members(chat.session).await? {
+        Some(members) if !system && !members.can_post(chat.sender) => {
+            warn!("User {} cannot post in session {}, message dropped", chat.sender, chat.session);
+            0
+        }
+        Some(members) => {
+            let extra = chat.receivers.iter().filter(|r| system && !members.members.contains(r));
+            let receivers = members.members.iter().chain(extra).filter(|r| **r != chat.sender);
+            connection::deliver(message, chat, receivers).await
+        }
+        None if system => connection::deliver(message, chat, chat.receivers.iter()).await,
+        None => {
+            warn!("Session {} does not exist, message {} dropped", chat.session, message.id);
+            0
+        }
+    };
+    Ok(sent)
+}
+
+//按公告范围投递给在线用户，已过期的公告直接丢弃
+async fn dispatch_notice(message: &Message, notice: &pubchat::core::message::Notice) -> Result<()> {
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    if notice.expired(now) {
+        info!("Notice {} expired, skipped", notice.id);
+        return Ok(());
+    }
+    let sent = match notice.scope() {
+        Scope::All => connection::broadcast(message, |_| true).await,
+        Scope::Org => connection::broadcast(message, |c| c.oid == notice.target).await,
+        Scope::Session => connection::broadcast(message, |c| notice.receivers.contains(&c.uid)).await,
+    }?;
+    info!("Notice {} delivered to {} clients", notice.id, sent);
+    Ok(())
+}
 
 pub async fn publish(message: &Message) -> Result<()> {
-    let queue_name = dotenv::var("RABBITMQ_SESSION_MESSAGE_QUEUE_NAME").expect("请设置RabbitMQ队列名称RABBITMQ_SESSION_MESSAGE_QUEUE_NAME");
-    let exc_name = dotenv::var("RABBITMQ_MESSAGE_EXCHANGE").expect("请设置RabbitMQ交换机名称RABBITMQ_MESSAGE_EXCHANGE");
+    let rabbitmq = &config::get().rabbitmq;
+    let queue_name = &rabbitmq.queue;
+    let exc_name = &rabbitmq.exchange;
     let payload = serde_json::to_vec(message)?;
     let pool = POOL.get();
     let pool = pool.expect("");
@@ -145,8 +299,8 @@ pub async fn publish(message: &Message) -> Result<()> {
     let channel= conn.create_channel().await?;
     
     let _confirm = channel.basic_publish(
-            &exc_name, // default exchange
-            &queue_name,
+            exc_name, // default exchange
+            queue_name,
             lapin::options::BasicPublishOptions::default(),
             &payload,
             BasicProperties::default(),
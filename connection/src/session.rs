@@ -0,0 +1,34 @@
+use anyhow::{anyhow, Result};
+use core::api::types::session::SessionState;
+use core::auth::SUPER_ADMIN;
+use core::response::ApiResult;
+use reqwest::StatusCode;
+use std::{sync::OnceLock, time::Duration};
+use crate::config;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+static CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
+
+//从会话服务加载会话的成员及所属组织的审核规则，会话不存在时返回None
+pub async fn load(session: u64) -> Result<Option<SessionState>> {
+    //会话服务只允许超级管理员加载会话状态
+    let token = core::auth::issue(SUPER_ADMIN, "connection".to_string(), 0)?;
+    let client = CLIENT.get_or_init(reqwest::Client::new);
+    let response = client
+        .get(format!("{}/admin/sessions/{}/state", config::get().api.session, session))
+        .bearer_auth(token)
+        .timeout(TIMEOUT)
+        .send()
+        .await?;
+    let status = response.status();
+    if status == StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    let text = response.text().await?;
+    match serde_json::from_str::<ApiResult<SessionState>>(&text) {
+        Ok(ApiResult { ok: true, data: Some(state), .. }) => Ok(Some(state)),
+        Ok(result) => Err(anyhow!("Failed to load session {}: {} - {}", session, status, result.message.unwrap_or_default())),
+        Err(_) => Err(anyhow!("Failed to load session {}: {} - {}", session, status, text)),
+    }
+}
@@ -0,0 +1,21 @@
+#[cfg(test)]
+pub mod tests{
+    use pubchat::core::message::{Members, SessionKind};
+    use crate::connection::SessionMembers;
+
+    #[test]
+    pub fn test_can_post(){
+        let members = |kind: SessionKind| SessionMembers::from(&Members {
+            session: 1, members: vec![10, 11], kind: kind as i32, admins: vec![10], oid: 0,
+        });
+        //群聊的成员都可以发言，非成员不能发言
+        let group = members(SessionKind::Group);
+        assert!(group.can_post(10) && group.can_post(11));
+        assert!(!group.can_post(12));
+        //频道只有管理员可以发言，订阅者的消息被拒绝
+        let channel = members(SessionKind::Channel);
+        assert!(channel.can_post(10));
+        assert!(!channel.can_post(11));
+        assert!(!channel.can_post(12));
+    }
+}
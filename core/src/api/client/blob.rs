@@ -1,76 +1,162 @@
 use anyhow::Result;
 use reqwest;
-use crate::{api::types::blob::{BlobResponse, BlobUploadResponse}, response::{ApiErr, ApiResult}};
-use std::{path::Path};
+use crate::{api::types::blob::{BlobResponse, BlobUploadResponse, CreateUploadRequest, UploadResponse}, response::{ApiErr, ApiResult}};
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
+use std::{fs::File, io::{Read, Seek, SeekFrom}, path::Path, time::Duration};
 
 use crate::api::client::blob_host;
 
 
-/// Upload a file to the blob service
+/// Chunk size for resumable uploads
+const CHUNK_SIZE: u64 = 1024 * 1024;
+/// Retries of a single chunk before giving up
+const MAX_RETRIES: u32 = 5;
+
+/// Upload a file to the blob service in chunks.
+///
+/// The file is sent as a resumable upload: a failed chunk is retried from the offset the
+/// server reports, and uploading the same file again resumes an unfinished upload.
 pub fn upload_file(token: &str, file_path: &str) -> Result<BlobUploadResponse> {
-    let client = reqwest::blocking::Client::new();
-    
-    // Read the file
-    let file_data = std::fs::read(file_path)?;
+    let client = reqwest::blocking::Client::builder()
+        .timeout(None)
+        .build()?;
+    let mut file = File::open(file_path)?;
+    let size = file.metadata()?.len();
     let file_name = Path::new(file_path)
         .file_name()
         .and_then(|name| name.to_str())
         .unwrap_or("unknown")
         .to_string();
-    
-    // Create multipart form
-    let form = reqwest::blocking::multipart::Form::new()
-        .part("file", reqwest::blocking::multipart::Part::bytes(file_data)
-            .file_name(file_name));
-    
-    // Send upload request
-    let response = client
-        .post(&format!("{}/blobs", blob_host()))
+    let request = CreateUploadRequest {
+        name: file_name,
+        size: size as i64,
+        btype: None,
+        hash: sha256(&mut file)?,
+        expire_days: None,
+    };
+    let upload: UploadResponse = parse(client
+        .post(format!("{}/blobs/uploads", blob_host()))
         .header("Authorization", format!("Bearer {}", token))
-        .multipart(form)
-        .send()?;
-    
-    if response.status().is_success() {
-        let result: ApiResult<BlobUploadResponse> = response.json()?;
-        if result.ok {
-            return Ok(result.data.unwrap());
-        }else{
-            return Err(ApiErr::Error(result.message.unwrap()).into());
+        .json(&request)
+        .send()?)?;
+    if upload.received > 0 {
+        log::info!("Resuming upload {} at {} of {} bytes", upload.id, upload.received, size);
+    }
+    let mut received = upload.received as u64;
+    let mut retries = 0;
+    while received < size {
+        let mut chunk = Vec::with_capacity(CHUNK_SIZE.min(size - received) as usize);
+        file.seek(SeekFrom::Start(received))?;
+        (&mut file).take(CHUNK_SIZE).read_to_end(&mut chunk)?;
+        let result = client
+            .put(format!("{}/blobs/uploads/{}?offset={}", blob_host(), upload.id, received))
+            .header("Authorization", format!("Bearer {}", token))
+            .body(chunk)
+            .send()
+            .map_err(anyhow::Error::from)
+            .and_then(parse::<UploadResponse>);
+        match result {
+            Ok(progress) => {
+                received = progress.received as u64;
+                retries = 0;
+            }
+            Err(e) if retries < MAX_RETRIES => {
+                retries += 1;
+                log::warn!("Chunk at {} failed ({}), retry {}/{}", received, e, retries, MAX_RETRIES);
+                std::thread::sleep(Duration::from_secs(1 << retries));
+                // Continue from whatever the server actually stored
+                if let Ok(progress) = get_upload(&client, token, upload.id) {
+                    received = progress.received as u64;
+                }
+            }
+            Err(e) => return Err(e),
         }
-    } else {
-        let status = response.status();
-        let error_text = response.text()?;
-        Err(anyhow::anyhow!("Failed to upload file: {} - {}", status, error_text))
     }
+    parse(client
+        .post(format!("{}/blobs/uploads/{}/complete", blob_host(), upload.id))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?)
 }
 
-/// Download a file from the blob service
-pub fn download_file(token: &str, file_id: i64, save_path: &str) -> Result<()> {
+fn get_upload(client: &reqwest::blocking::Client, token: &str, id: i64) -> Result<UploadResponse> {
+    parse(client
+        .get(format!("{}/blobs/uploads/{}", blob_host(), id))
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?)
+}
+
+/// Hex encoded SHA-256 of the whole file
+fn sha256(file: &mut File) -> Result<String> {
+    let mut hasher = Sha256::new();
+    std::io::copy(file, &mut hasher)?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Unwrap an `ApiResult` response, using the server message on failure
+fn parse<T: Serialize + DeserializeOwned>(response: reqwest::blocking::Response) -> Result<T> {
+    let status = response.status();
+    let text = response.text()?;
+    match serde_json::from_str::<ApiResult<T>>(&text) {
+        Ok(ApiResult { ok: true, data: Some(data), .. }) => Ok(data),
+        Ok(result) => Err(ApiErr::Error(result.message.unwrap_or(status.to_string())).into()),
+        Err(_) => Err(anyhow::anyhow!("Request failed: {} - {}", status, text)),
+    }
+}
+
+/// Get file metadata and a signed download path from the blob service
+pub fn get_blob(token: &str, file_id: i64) -> Result<BlobResponse> {
     let client = reqwest::blocking::Client::new();
-    
-    // Send download request
     let response = client
-        .get(&format!("{}/blobs/{}", blob_host(), file_id))
+        .get(format!("{}/blobs/{}", blob_host(), file_id))
         .header("Authorization", format!("Bearer {}", token))
         .send()?;
-    
     if response.status().is_success() {
         let result: ApiResult<BlobResponse> = response.json()?;
         if result.ok {
-            let path = result.data.unwrap().path;
-            log::info!("Downloading file: {}", path);
-            let file_data = client
-                .get(&path)
-                .send()?
-                .bytes()?;
-            std::fs::write(save_path, file_data)?;
-            return Ok(());
-        }else{
-            return Err(ApiErr::Error(result.message.unwrap()).into());
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
         }
     } else {
         let status = response.status();
         let error_text = response.text()?;
-        Err(anyhow::anyhow!("Failed to download file: {} - {}", status, error_text))
+        Err(anyhow::anyhow!("Failed to get file: {} - {}", status, error_text))
     }
-}
\ No newline at end of file
+}
+
+/// Download a file from the blob service, streaming it to `save_path`
+pub fn download_file(token: &str, file_id: i64, save_path: &str) -> Result<()> {
+    // The metadata response carries either a signed content path on the blob service
+    // or a presigned URL on the object store the file lives in
+    let blob = get_blob(token, file_id)?;
+    let url = if blob.path.starts_with("http://") || blob.path.starts_with("https://") {
+        blob.path
+    } else {
+        format!("{}{}", blob_host(), blob.path)
+    };
+    log::info!("Downloading file: {}", url);
+    let client = reqwest::blocking::Client::builder()
+        .timeout(None)
+        .build()?;
+    let mut response = client
+        .get(url)
+        .send()?;
+    if !response.status().is_success() {
+        let status = response.status();
+        let error_text = response.text()?;
+        return Err(anyhow::anyhow!("Failed to download file: {} - {}", status, error_text));
+    }
+    let mut file = File::options().read(true).write(true).create(true).truncate(true).open(save_path)?;
+    response.copy_to(&mut file)?;
+    // Verify the content against the hash recorded at upload time
+    if let Some(expected) = blob.hash {
+        file.seek(SeekFrom::Start(0))?;
+        let actual = sha256(&mut file)?;
+        if actual != expected {
+            return Err(anyhow::anyhow!("Downloaded file is corrupted: sha256 {} != {}", actual, expected));
+        }
+    }
+    Ok(())
+}
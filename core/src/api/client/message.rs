@@ -1,21 +1,86 @@
-use crate::{api::types::message::Message, response::{ApiErr, ApiResult}};
+use crate::{api::types::message::{Message, MessageHit, PinResponse, ScheduleRequest, ScheduledResponse, SearchMessageRequest}, request::Cursor, response::{ApiErr, ApiResult, Cursors}};
 use reqwest;
 use anyhow::Result;
 use crate::api::client::session_host;
 
 
-/// 获取指定会话的消息历史
-pub fn get_session_messages(token: &str, session_id: i64) -> Result<Vec<Message>> {
+/// 按游标获取一页会话消息，返回按时间倒序排列的消息及向前、向后翻页的游标
+pub fn get_session_messages_page(token: &str, session_id: i64, cursor: &Cursor) -> Result<(Vec<Message>, Cursors)> {
     let client = reqwest::blocking::Client::new();
-    let url = format!("{}/session/{}/messages", session_host(), session_id);
+    let url = format!("{}/{}/messages", session_host(), session_id);
     
     let response = client
         .get(&url)
         .header("Authorization", format!("Bearer {}", token))
+        .query(cursor)
         .send()?;
         
     if response.status().is_success() {
         let result: ApiResult<Vec<Message>> = response.json()?;
+        if result.ok {
+            Ok((result.data.unwrap_or_default(), Cursors { before: result.before, after: result.after }))
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to get session messages: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 获取指定会话最新的limit条消息历史，按游标逐页向前拉取
+pub fn get_session_messages(token: &str, session_id: i64, limit: u32) -> Result<Vec<Message>> {
+    let mut messages = vec![];
+    let mut cursor = Cursor { ps: Some(limit.min(Cursor::MAX_SIZE)), before: None, after: None };
+    while (messages.len() as u32) < limit {
+        let (page, cursors) = get_session_messages_page(token, session_id, &cursor)?;
+        messages.extend(page);
+        match cursors.before {
+            Some(before) => cursor.before = Some(before),
+            None => break,
+        }
+        cursor.ps = Some((limit - messages.len() as u32).min(Cursor::MAX_SIZE));
+    }
+    messages.truncate(limit as usize);
+    Ok(messages)
+}
+
+/// 在当前用户所在的会话中全文搜索消息
+pub fn search_messages(token: &str, request: &SearchMessageRequest) -> Result<Vec<MessageHit>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/search/messages", session_host());
+
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .query(request)
+        .send()?;
+
+    if response.status().is_success() {
+        let result: ApiResult<Vec<MessageHit>> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap_or_default())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to search messages: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 获取会话的置顶消息，按置顶时间倒序排列
+pub fn get_pins(token: &str, session_id: i64) -> Result<Vec<PinResponse>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/pins", session_host(), session_id);
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<Vec<PinResponse>> = response.json()?;
         if result.ok {
             Ok(result.data.unwrap())
         } else {
@@ -24,6 +89,68 @@ pub fn get_session_messages(token: &str, session_id: i64) -> Result<Vec<Message>
     } else {
         let status = response.status();
         let error_text = response.text()?;
-        Err(ApiErr::Error(format!("Failed to get session messages: {} - {}", status, error_text).into()).into())
+        Err(ApiErr::Error(format!("Failed to get pins: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 在会话中创建定时消息，到达send_at时由服务端发送
+pub fn schedule_message(token: &str, session_id: i64, request: &ScheduleRequest) -> Result<ScheduledResponse> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/scheduled", session_host(), session_id);
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(request)
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<ScheduledResponse> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to schedule message: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 获取当前用户在会话中待发送的定时消息
+pub fn get_scheduled(token: &str, session_id: i64) -> Result<Vec<ScheduledResponse>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/scheduled", session_host(), session_id);
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<Vec<ScheduledResponse>> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap_or_default())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to get scheduled messages: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 取消待发送的定时消息
+pub fn cancel_scheduled(token: &str, session_id: i64, id: i64) -> Result<()> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/scheduled/{}", session_host(), session_id, id);
+    let response = client
+        .delete(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        Ok(())
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to cancel scheduled message: {} - {}", status, error_text)).into())
     }
-}
\ No newline at end of file
+}
@@ -1,25 +1,24 @@
 use std::sync::OnceLock;
 
+use crate::config::ApiConfig;
+
 pub mod blob;
 pub mod session;
 pub mod login;
 pub mod contact;
 pub mod message;
+pub mod notice;
 
 static CONNECTION_HOST : OnceLock<String> = OnceLock::new();
 static USER_HOST : OnceLock<String> = OnceLock::new();
 static SESSION_HOST: OnceLock<String> = OnceLock::new();
 static BLOB_HOST: OnceLock<String> = OnceLock::new();
 
-pub fn init() {
-    let user_host = dotenv::var("USER_SERVER_URL").expect("请设置USER_SERVER_URL环境变量");
-    let connection_host = dotenv::var("CONNECTION_SERVER_URL").expect("请设置CONNECTION_SERVER_URL环境变j'j");
-    let session_host = dotenv::var("SESSION_SERVER_URL").expect("请设置SESSION_SERVER_URL环境变j'j");
-    let blob_host = dotenv::var("BLOB_SERVER_URL").expect("请设置BLOB_SERVER_URL环境变j'j");
-    USER_HOST.set(user_host).expect("初始化USER_HOST失败");
-    CONNECTION_HOST.set(connection_host).expect("初始化CONNECTION_HOST失败");
-    SESSION_HOST.set(session_host).expect("初始化SESSION_HOST失败");
-    BLOB_HOST.set(blob_host).expect("初始化BLOB_HOST失败");
+pub fn init(config: &ApiConfig) {
+    USER_HOST.set(config.user.clone()).expect("初始化USER_HOST失败");
+    CONNECTION_HOST.set(config.connection.clone()).expect("初始化CONNECTION_HOST失败");
+    SESSION_HOST.set(config.session.clone()).expect("初始化SESSION_HOST失败");
+    BLOB_HOST.set(config.blob.clone()).expect("初始化BLOB_HOST失败");
 }
 
 pub(crate) fn user_host() -> String {
@@ -0,0 +1,27 @@
+use crate::api::types::notice::NoticeResponse;
+use crate::response::{ApiErr, ApiResult};
+use reqwest;
+use anyhow::Result;
+use crate::api::client::session_host;
+
+/// 获取当前用户可见且未过期的系统公告，用于登录后补齐离线期间发布的公告
+pub fn get_notices(token: &str) -> Result<Vec<NoticeResponse>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/notices", session_host());
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<Vec<NoticeResponse>> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to get notices: {} - {}", status, error_text)).into())
+    }
+}
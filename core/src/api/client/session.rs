@@ -1,4 +1,4 @@
-use crate::api::types::session::{CreateSessionRequest, SessionDetailResponse, SessionResponse};
+use crate::api::types::session::{ChannelResponse, CreateSessionRequest, SessionDetailResponse, SessionResponse, SessionSettings};
 use crate::response::{ApiErr, ApiResult};
 
 use reqwest;
@@ -72,6 +72,139 @@ pub fn create_session(token: &str, payload: CreateSessionRequest) -> Result<Sess
     }
 }
 
+/// 将会话标记为已读
+pub fn mark_read(token: &str, id: i64) -> Result<()> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/read", session_host(), id);
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<bool> = response.json()?;
+        if result.ok {
+            Ok(())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to mark session read: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 修改当前用户在会话中的通知设置，返回服务端保存后的设置
+pub fn update_settings(token: &str, id: i64, settings: &SessionSettings) -> Result<SessionSettings> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/settings", session_host(), id);
+    let response = client
+        .put(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .json(settings)
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<SessionSettings> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to update session settings: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 通过邀请令牌加入会话
+pub fn join_invite(token: &str, invite: &str) -> Result<SessionResponse> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/invites/{}/join", session_host(), invite);
+    let response = client
+        .post(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<SessionResponse> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to join session: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 获取所在组织的频道
+pub fn get_channels(token: &str) -> Result<Vec<ChannelResponse>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/channels", session_host());
+    let response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        let result: ApiResult<Vec<ChannelResponse>> = response.json()?;
+        if result.ok {
+            Ok(result.data.unwrap())
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to get channels: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 订阅或退订频道，订阅时返回频道会话
+pub fn subscribe(token: &str, id: i64, subscribed: bool) -> Result<Option<SessionResponse>> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/channels/{}/subscription", session_host(), id);
+    let request = if subscribed { client.put(&url) } else { client.delete(&url) };
+    let response = request
+        .header("Authorization", format!("Bearer {}", token))
+        .send()?;
+    if response.status().is_success() {
+        if !subscribed {
+            return Ok(None);
+        }
+        let result: ApiResult<SessionResponse> = response.json()?;
+        if result.ok {
+            Ok(result.data)
+        } else {
+            Err(ApiErr::Error(result.message.unwrap()).into())
+        }
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to update subscription: {} - {}", status, error_text)).into())
+    }
+}
+
+/// 导出会话的全部聊天记录，以流的方式写入本地文件，返回写入的字节数
+pub fn export_session(token: &str, id: i64, format: &str, save_path: &str) -> Result<u64> {
+    let client = reqwest::blocking::Client::new();
+    let url = format!("{}/sessions/{}/export", session_host(), id);
+    let mut response = client
+        .get(&url)
+        .header("Authorization", format!("Bearer {}", token))
+        .query(&[("format", format)])
+        .send()?;
+    if response.status().is_success() {
+        let mut file = std::fs::File::create(save_path)?;
+        Ok(response.copy_to(&mut file)?)
+    } else {
+        let status = response.status();
+        let error_text = response.text()?;
+        Err(ApiErr::Error(format!("Failed to export session: {} - {}", status, error_text)).into())
+    }
+}
+
 //计算两个用户的唯一会话ID（始终不变，以便更快的找到两人的会话）
 pub fn calc_session_id(uid1: i64, uid2: i64) -> u64{
     let (min, max) = if uid1 < uid2 { (uid1 as u64, uid2 as u64) } else { (uid2 as u64, uid1 as u64) };
@@ -8,6 +8,14 @@ pub struct BlobUploadResponse {
     pub name: String,
     pub size: i64,
     pub exp: Option<String>,
+    //图片的宽高(像素)，非图片为空
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    //缩略图的文件ID，按尺寸从小到大排列
+    #[serde(default)]
+    pub thumbnails: Vec<i64>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,12 +24,84 @@ pub struct BlobResponse {
     pub name: String,
     pub size: i64,
     pub exp: Option<String>,
+    //下载地址：blob服务上带签名的相对路径，或对象存储的预签名完整地址
     pub path: String,
+    //文件内容的SHA-256，用于校验下载结果；去重之前上传的文件为空
+    #[serde(default)]
+    pub hash: Option<String>,
+    #[serde(default)]
+    pub width: Option<u32>,
+    #[serde(default)]
+    pub height: Option<u32>,
+    #[serde(default)]
+    pub thumbnails: Vec<i64>,
+}
+
+//下载链接中的签名参数，由获取文件信息接口返回的path携带
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlobContentRequest {
+    pub token: String,
+    pub expire: i64,
+}
+
+//上传文件的有效期(天)，为空时使用服务端默认值，不能超过服务端允许的最长天数
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BlobUploadRequest {
+    #[serde(default)]
+    pub expire_days: Option<u32>,
+}
+
+//创建分片上传，hash为整个文件的SHA-256(十六进制)，同一用户重复创建同一文件时返回未完成的上传以便续传
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateUploadRequest {
+    pub name: String,
+    pub size: i64,
+    pub btype: Option<String>,
+    pub hash: String,
+    #[serde(default)]
+    pub expire_days: Option<u32>,
+}
+
+//分片在文件中的起始位置，必须等于已接收的字节数
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadChunkRequest {
+    pub offset: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UploadResponse {
+    pub id: i64,
+    pub name: String,
+    pub size: i64,
+    pub received: i64,
+}
+
+//回收过期文件，dry_run为true时只统计不删除
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GcRequest {
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GcResponse {
+    pub dry_run: bool,
+    //删除的过期文件数
+    pub blobs: u64,
+    //释放的存储空间(字节)，内容仍被其他未过期文件引用时不计入
+    pub bytes: u64,
+    //删除的未完成上传数，其临时文件的大小计入bytes
+    #[serde(default)]
+    pub uploads: u64,
 }
 
 impl Display for BlobUploadResponse {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "[File] {} (size: {}, exp: {}, download id: {})", 
-            self.name, self.size, self.exp.as_ref().unwrap_or(&"never".to_string()), base62::encode(self.id as u128))
+            self.name, self.size, self.exp.as_ref().unwrap_or(&"never".to_string()), base62::encode(self.id as u128))?;
+        if let (Some(width), Some(height)) = (self.width, self.height) {
+            write!(f, " [Image {}x{}]", width, height)?;
+        }
+        Ok(())
     }
 }
\ No newline at end of file
@@ -6,6 +6,12 @@ pub struct MessageRequest {
     _limit: Option<u32>,
 }
 
+//消息负载，与下发给客户端的聊天消息内容一致
+pub type Payload = pubchat::core::message::chrs::Message;
+
+//当前的消息负载格式版本，0表示旧数据，只有content文本
+pub const PAYLOAD_VERSION: i32 = 1;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Message {
     pub id: i64,
@@ -13,7 +19,107 @@ pub struct Message {
     pub receiver: i64,
     pub session: i64,
     pub mtype: i32,
+    //可读文本，用于搜索和预览
     pub content: String,
     pub timestamp: i64,
     pub uname: String,
-}
\ No newline at end of file
+    //结构化的消息内容，文件、代码、图片等按类型渲染
+    #[serde(default)]
+    pub payload: Option<Payload>,
+    #[serde(default)]
+    pub version: i32,
+}
+
+impl Message {
+    //展示文本，优先使用结构化负载
+    pub fn text(&self) -> String {
+        match &self.payload {
+            Some(payload) => payload.to_string(),
+            None => self.content.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SearchMessageRequest {
+    //搜索关键词，多个关键词以空格分隔，需同时命中
+    pub q: String,
+    //限定会话
+    pub session: Option<i64>,
+    //限定发送者
+    pub sender: Option<i64>,
+    //起止时间戳(毫秒)
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub ps: Option<u32>,
+    pub pn: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageHit {
+    pub id: i64,
+    pub sender: i64,
+    pub session: i64,
+    pub mtype: i32,
+    pub uname: String,
+    pub timestamp: i64,
+    //命中片段，关键词以**包裹
+    pub snippet: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeadLetterRequest {
+    //死信队列名称，默认为当前服务的死信队列，必须以_dlq结尾
+    pub queue: Option<String>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    //消息id，消息无法解析时为空
+    pub id: Option<u64>,
+    //处理失败的队列，重放时投递回该队列
+    pub source: String,
+    pub exchange: String,
+    pub routing_key: String,
+    pub reason: String,
+    //失败次数
+    pub attempts: u32,
+    //转入死信队列的时间戳(毫秒)
+    pub failed_at: i64,
+    //消息原文
+    pub body: String,
+}
+
+//会话中的置顶消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PinResponse {
+    pub message: Message,
+    //置顶操作者
+    pub pinned_by: i64,
+    pub pinned_name: String,
+    //置顶时间戳(毫秒)
+    pub pinned_at: i64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ScheduleRequest {
+    //消息文本，修改时为空表示不修改
+    pub text: Option<String>,
+    //计划发送时间戳(毫秒)，修改时为空表示不修改
+    pub send_at: Option<i64>,
+}
+
+//定时消息
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledResponse {
+    pub id: i64,
+    pub session: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub content: String,
+    //计划发送时间戳(毫秒)
+    pub send_at: i64,
+    //0-待发送, 1-发送中, 2-已发送, 3-已取消, 4-发送失败
+    pub status: i8,
+}
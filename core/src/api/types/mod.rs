@@ -3,4 +3,6 @@ pub mod user;
 pub mod session;
 pub mod auth;
 pub mod contact;
-pub mod message;
\ No newline at end of file
+pub mod message;
+pub mod notice;
+pub mod moderation;
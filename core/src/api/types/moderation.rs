@@ -0,0 +1,199 @@
+use std::sync::LazyLock;
+
+use anyhow::Result;
+use pubchat::core::message::{Rule, RuleAction, RuleKind};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use crate::response::ApiErr;
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RuleRequest {
+    //规则所属组织
+    pub oid: i64,
+    //规则类型: 0-违禁词, 1-正则表达式, 2-链接, 3-消息长度
+    pub kind: i8,
+    //违禁词、正则表达式或禁止的链接域名(为空表示禁止所有链接)
+    #[serde(default)]
+    pub pattern: String,
+    //kind=3时的最大消息长度(字符数)
+    #[serde(default)]
+    pub max_len: u32,
+    //处理方式: 0-拒绝发送, 1-屏蔽违规内容, 2-提交人工审核
+    pub action: i8,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct RuleQuery {
+    pub oid: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RuleResponse {
+    pub id: i64,
+    pub oid: i64,
+    pub kind: i8,
+    pub pattern: String,
+    pub max_len: u32,
+    pub action: i8,
+    pub creator: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ReviewRequest {
+    pub oid: i64,
+    //审核状态，为空时查询待审核的消息
+    pub status: Option<i8>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct ReviewResponse {
+    //被标记的消息id
+    pub mid: i64,
+    pub session: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub content: String,
+    //命中的规则说明
+    pub reason: String,
+    //0-待审核, 1-已通过, 2-已删除
+    pub status: i8,
+    pub reviewer: Option<i64>,
+    pub timestamp: i64,
+}
+
+//审核结果
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Verdict {
+    //拒绝原因，不为空时消息不发送
+    pub rejected: Option<String>,
+    //屏蔽违规内容后的文本，为空表示未修改
+    pub masked: Option<String>,
+    //需要人工审核的原因
+    pub flagged: Option<String>,
+}
+
+static LINK: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"(?i)\b(?:https?://|www\.)[^\s]+").unwrap());
+//限制用户提交的正则表达式编译后的大小，regex保证线性时间匹配，不存在回溯爆炸
+const REGEX_SIZE_LIMIT: usize = 1 << 16;
+const MAX_PATTERN_LEN: usize = 200;
+
+#[derive(Debug)]
+enum Matcher {
+    Pattern(Regex),
+    //禁止的链接域名，为空表示禁止所有链接
+    Link(Option<String>),
+    Length(usize),
+}
+
+#[derive(Debug)]
+struct Compiled {
+    matcher: Matcher,
+    action: RuleAction,
+    reason: String,
+}
+
+//组织的内容审核规则，连接服务按组织缓存，会话服务用于校验规则及审核定时消息
+#[derive(Debug)]
+pub struct Moderator {
+    rules: Vec<Compiled>,
+}
+
+fn compile(rule: &Rule) -> Result<Compiled> {
+    let kind = RuleKind::try_from(rule.kind).map_err(|_| ApiErr::Bad(400, "审核规则类型不合法".to_string()))?;
+    let action = RuleAction::try_from(rule.action).map_err(|_| ApiErr::Bad(400, "审核规则处理方式不合法".to_string()))?;
+    let pattern = rule.pattern.trim();
+    if pattern.chars().count() > MAX_PATTERN_LEN {
+        return Err(ApiErr::Bad(400, format!("审核规则长度不能超过{}个字符", MAX_PATTERN_LEN)).into());
+    }
+    let regex = |pattern: &str| RegexBuilder::new(pattern)
+        .size_limit(REGEX_SIZE_LIMIT)
+        .build()
+        .map_err(|e| ApiErr::Bad(400, format!("正则表达式不合法: {}", e)));
+    let (matcher, reason) = match kind {
+        RuleKind::Keyword | RuleKind::Regex if pattern.is_empty() => {
+            return Err(ApiErr::Bad(400, "审核规则内容不能为空".to_string()).into());
+        }
+        RuleKind::Keyword => (Matcher::Pattern(regex(&format!("(?i){}", regex::escape(pattern)))?), format!("消息包含违禁词: {}", pattern)),
+        RuleKind::Regex => (Matcher::Pattern(regex(pattern)?), "消息包含违禁内容".to_string()),
+        RuleKind::Link if pattern.is_empty() => (Matcher::Link(None), "消息不允许包含链接".to_string()),
+        RuleKind::Link => (Matcher::Link(Some(pattern.to_lowercase())), format!("消息不允许包含{}的链接", pattern)),
+        RuleKind::Length if rule.max_len == 0 => {
+            return Err(ApiErr::Bad(400, "最大消息长度必须大于0".to_string()).into());
+        }
+        RuleKind::Length => (Matcher::Length(rule.max_len as usize), format!("消息长度不能超过{}个字符", rule.max_len)),
+    };
+    Ok(Compiled { matcher, action, reason })
+}
+
+//链接的域名是否为domain或其子域名
+fn host_matches(link: &str, domain: &str) -> bool {
+    let rest = link.split_once("://").map(|(_, rest)| rest).unwrap_or(link);
+    let host = rest.split(['/', '?', '#', ':']).next().unwrap_or_default().to_lowercase();
+    host == domain || host.ends_with(&format!(".{}", domain))
+}
+
+impl Compiled {
+    //返回命中的文本区间，长度规则返回超出部分
+    fn find(&self, text: &str) -> Vec<(usize, usize)> {
+        match &self.matcher {
+            Matcher::Pattern(regex) => regex.find_iter(text).filter(|m| !m.is_empty()).map(|m| (m.start(), m.end())).collect(),
+            Matcher::Link(domain) => LINK.find_iter(text)
+                .filter(|m| domain.as_ref().is_none_or(|d| host_matches(m.as_str(), d)))
+                .map(|m| (m.start(), m.end()))
+                .collect(),
+            Matcher::Length(max) => text.char_indices().nth(*max).map(|(i, _)| vec![(i, text.len())]).unwrap_or_default(),
+        }
+    }
+}
+
+impl Moderator {
+    //编译规则，存在不合法的规则时返回错误
+    pub fn new(rules: &[Rule]) -> Result<Self> {
+        Ok(Moderator { rules: rules.iter().map(compile).collect::<Result<_>>()? })
+    }
+
+    //编译规则，跳过不合法的规则
+    pub fn lenient(rules: &[Rule]) -> Self {
+        Moderator { rules: rules.iter().filter_map(|r| compile(r).ok()).collect() }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    //按规则审核消息文本，拒绝优先；拒绝和人工审核按原文判断，屏蔽在前一条规则屏蔽后的文本上继续进行
+    pub fn check(&self, text: &str) -> Verdict {
+        if let Some(rule) = self.rules.iter().find(|r| r.action == RuleAction::Reject && !r.find(text).is_empty()) {
+            return Verdict { rejected: Some(rule.reason.clone()), ..Default::default() };
+        }
+        let mut verdict = Verdict {
+            flagged: self.rules.iter()
+                .find(|r| r.action == RuleAction::Flag && !r.find(text).is_empty())
+                .map(|r| r.reason.clone()),
+            ..Default::default()
+        };
+        let mut current = text.to_string();
+        for rule in self.rules.iter().filter(|r| r.action == RuleAction::Mask) {
+            let found = rule.find(&current);
+            if found.is_empty() {
+                continue;
+            }
+            //超长的消息截断，其余违规内容替换为星号
+            let truncate = matches!(rule.matcher, Matcher::Length(_));
+            let mut masked = String::with_capacity(current.len());
+            let mut last = 0;
+            for (start, end) in found {
+                masked.push_str(&current[last..start]);
+                if !truncate {
+                    masked.extend(std::iter::repeat_n('*', current[start..end].chars().count().min(8)));
+                }
+                last = end;
+            }
+            masked.push_str(&current[last..]);
+            current = masked;
+            verdict.masked = Some(current.clone());
+        }
+        verdict
+    }
+}
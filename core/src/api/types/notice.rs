@@ -0,0 +1,28 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct CreateNoticeRequest {
+    //公告级别: 0-INFO, 1-WARN, 2-CRITICAL
+    pub severity: i32,
+    //公告范围: 0-所有用户, 1-组织, 2-会话
+    pub scope: i32,
+    //组织id或会话id，scope=0时忽略
+    #[serde(default)]
+    pub target: i64,
+    pub text: String,
+    //过期时间戳(毫秒)，为空表示不过期
+    pub exp: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct NoticeResponse {
+    pub id: i64,
+    pub severity: i32,
+    pub scope: i32,
+    pub target: i64,
+    pub text: String,
+    pub exp: Option<i64>,
+    pub sender: i64,
+    pub uname: String,
+    pub ts: i64,
+}
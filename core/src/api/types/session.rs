@@ -8,17 +8,183 @@ pub struct CreateSessionRequest {
     pub id: i64,
     pub name: String,
     pub members: Vec<ContactResponse>,
+    //会话类型: 0-单聊, 1-群聊, 2-频道，为空时按会话id和成员推断单聊或群聊
+    #[serde(default)]
+    pub kind: Option<i8>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SessionResponse {
     pub id: i64,
     pub name: String,
+    //会话类型: 0-单聊, 1-群聊, 2-频道
+    #[serde(default)]
+    pub kind: i8,
+    //未读消息数
+    #[serde(default)]
+    pub unread: i64,
+    //最后一条消息预览
+    #[serde(default)]
+    pub last_message: Option<MessagePreview>,
+    //当前用户在该会话中的通知设置
+    #[serde(default)]
+    pub settings: SessionSettings,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MessagePreview {
+    pub id: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub content: String,
+    pub timestamp: i64,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ReadRequest {
+    //已读到的消息id，为空时标记会话全部已读
+    pub message: Option<i64>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct SessionDetailResponse {
     pub id: i64,
     pub name: String,
+    //频道只返回可以发言的管理员，全部订阅者通过成员接口分页查询
     pub members: Vec<ContactResponse>,
+    #[serde(default)]
+    pub kind: i8,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct AddMembersRequest {
+    pub members: Vec<ContactResponse>,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone)]
+pub struct RenameSessionRequest {
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct MemberResponse {
+    pub id: i64,
+    pub name: String,
+    //0-普通成员, 1-管理员, 2-群主
+    pub role: i8,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct RetentionRequest {
+    //会话消息保留天数，0表示永久保留，为空时清除会话设置，沿用组织或全局配置
+    pub days: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RetentionResponse {
+    //会话自身的设置
+    pub days: Option<u32>,
+    //实际生效的保留天数，0表示永久保留
+    pub effective: u32,
+    //生效设置的来源: session、org、default
+    pub source: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct ExportRequest {
+    //导出格式: json、md、html，默认为json
+    pub format: Option<String>,
+}
+
+//用户在会话中的通知设置，既是设置请求也是响应
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SessionSettings {
+    //通知级别: 0-所有消息, 1-仅提及自己的消息
+    #[serde(default)]
+    pub notify: i8,
+    //静音截止时间戳(毫秒)，为空表示未静音
+    #[serde(default)]
+    pub muted_until: Option<i64>,
+}
+
+impl SessionSettings {
+    pub const NOTIFY_ALL: i8 = 0;
+    pub const NOTIFY_MENTIONS: i8 = 1;
+
+    pub fn muted(&self, now: i64) -> bool {
+        self.muted_until.is_some_and(|until| until > now)
+    }
+
+    //收到消息时是否提醒接收者(响铃、未读角标)，uname为接收者昵称
+    pub fn notify(&self, text: &str, uname: &str, now: i64) -> bool {
+        if self.muted(now) {
+            return false;
+        }
+        self.notify != Self::NOTIFY_MENTIONS || mentioned(text, uname)
+    }
+}
+
+impl From<&pubchat::core::message::Setting> for SessionSettings {
+    fn from(setting: &pubchat::core::message::Setting) -> Self {
+        Self {
+            notify: setting.notify as i8,
+            muted_until: setting.muted_until.map(|t| t as i64),
+        }
+    }
+}
+
+//消息是否以@昵称的形式提及了用户，昵称后须为单词边界，@alice不匹配@alicex
+pub fn mentioned(text: &str, uname: &str) -> bool {
+    if uname.is_empty() {
+        return false;
+    }
+    let tag = format!("@{}", uname);
+    text.match_indices(&tag).any(|(i, _)| {
+        text[i + tag.len()..].chars().next().is_none_or(|c| !c.is_alphanumeric() && c != '_')
+    })
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct CreateInviteRequest {
+    //有效期(秒)，为空时使用默认有效期
+    pub ttl_secs: Option<u64>,
+    //最多使用次数，为空表示不限次数
+    pub max_uses: Option<u32>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct InviteResponse {
+    pub id: i64,
+    pub session: i64,
+    //签名的邀请令牌，通过POST /invites/{token}/join加入会话
+    pub token: String,
+    pub creator: i64,
+    pub uname: String,
+    pub max_uses: Option<u32>,
+    pub uses: u32,
+    //过期时间戳(毫秒)
+    pub exp: i64,
+    pub revoked: bool,
+    //创建时间戳(毫秒)
+    pub createtime: i64,
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct ChannelResponse {
+    pub id: i64,
+    pub name: String,
+    //订阅人数
+    pub subscribers: i64,
+    //当前用户是否已订阅
+    pub subscribed: bool,
+}
+
+//会话的成员、成员的通知设置及所属组织的审核规则，连接服务的缓存未命中时从会话服务加载
+#[derive(Debug, Deserialize, Serialize, Clone, Default)]
+pub struct SessionState {
+    pub members: pubchat::core::message::Members,
+    pub moderation: pubchat::core::message::Moderation,
+    //只包含非默认的设置
+    #[serde(default)]
+    pub settings: Vec<pubchat::core::message::Setting>,
 }
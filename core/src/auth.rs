@@ -3,11 +3,12 @@ use axum::{
     http::header::AUTHORIZATION, response::IntoResponse,
 };
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::{sync::OnceLock, time::{SystemTime, UNIX_EPOCH}};
 use tower_http::validate_request::ValidateRequest;
 use anyhow::Result;
 
+use crate::config::AuthConfig;
 use crate::response::ApiErr;
 
 // Quick instructions
@@ -38,26 +39,35 @@ use crate::response::ApiErr;
 
 static KEYS: OnceLock<Keys> = OnceLock::new();
 
-pub fn init() {
-    let secret = std::env::var("JWT_SECRET").expect("未设置JWT_SECRET");
-    let keys = Keys::new(secret.as_bytes());
+//内置超级管理员的用户id，见user服务初始化数据，服务之间的内部调用也以超级管理员身份签发令牌
+pub const SUPER_ADMIN: i64 = 0;
+
+pub fn init(config: &AuthConfig) {
+    let keys = Keys::new(config.jwt_secret.as_bytes(), config.token_ttl_secs as u128 * 1000);
     KEYS.set(keys).or(Err("")).expect("设置jwtsecret异常");
 }
 
-pub fn issue(id: i64, name: String) -> Result<String> {
+//Token有效期(毫秒)
+pub fn ttl() -> u128 {
+    KEYS.get().map(|k| k.ttl).unwrap_or(1800000u128)
+}
+
+//oid为用户所在组织，用于组织公告、频道订阅及内容审核规则，不属于任何组织时为0
+pub fn issue(id: i64, name: String, oid: i64) -> Result<String> {
     let keys = KEYS.get();
     if keys.is_none(){
         return Err(ApiErr::Error("JWT密钥异常".to_string()).into());
     }
+    let keys = keys.unwrap();
     let claims = User {
         id,
         name,
-        oid: 0,
+        oid,
         // Mandatory expiry time as UTC timestamp
-        exp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() + 1800000u128, // May 2033
+        exp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() + keys.ttl,
     };
     // Create the authorization token
-    encode(&Header::default(), &claims, &keys.unwrap().encoding)
+    encode(&Header::default(), &claims, &keys.encoding)
     .map_err(|_| ApiErr::Error("Token颁发失败".to_string()).into())
 }
 
@@ -80,6 +90,27 @@ pub fn verify(token: &str) -> Result<User> {
     Ok(claims)
 }
 
+//使用JWT密钥签发自定义内容的令牌，如会话邀请链接，claims需包含exp(秒)字段及表示令牌用途的aud字段
+pub fn sign<T: Serialize>(claims: &T) -> Result<String> {
+    let keys = KEYS.get().ok_or(ApiErr::Error("JWT密钥异常".to_string()))?;
+    encode(&Header::default(), claims, &keys.encoding)
+        .map_err(|_| ApiErr::Error("令牌签发失败".to_string()).into())
+}
+
+//校验sign签发的令牌并返回其内容，令牌的aud必须为audience，其他业务校验由调用方负责
+pub fn open<T: DeserializeOwned + Clone>(token: &str, audience: &str) -> Result<T> {
+    let keys = KEYS.get().ok_or(ApiErr::Error("JWT密钥异常".to_string()))?;
+    let mut validation = Validation::default();
+    validation.set_audience(&[audience]);
+    validation.set_required_spec_claims(&["exp", "aud"]);
+    decode::<T>(token, &keys.decoding, &validation)
+        .map(|data| data.claims)
+        .map_err(|e| {
+            log::warn!("令牌校验失败>>{}", e);
+            ApiErr::Bad(400, "令牌无效".to_string()).into()
+        })
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct AuthHeader;
 
@@ -116,13 +147,15 @@ impl<B> ValidateRequest<B> for AuthHeader {
 struct Keys {
     encoding: EncodingKey,
     decoding: DecodingKey,
+    ttl: u128,
 }
 
 impl Keys {
-    fn new(secret: &[u8]) -> Self {
+    fn new(secret: &[u8], ttl: u128) -> Self {
         Self {
             encoding: EncodingKey::from_secret(secret),
             decoding: DecodingKey::from_secret(secret),
+            ttl,
         }
     }
 }
@@ -0,0 +1,784 @@
+//! 统一配置
+//!
+//! 每个服务的配置按以下顺序逐层覆盖:
+//! 1. 代码中的默认值
+//! 2. 配置文件(`PUBCHAT_CONFIG`指定，默认当前目录下的`pubchat.toml`，可选)中的公共段，如`[database]`
+//! 3. 配置文件中以服务名命名的段，如`[session.database]`
+//! 4. 环境变量：兼容旧的`.env`变量名(如`DATABASE_URL`)，以及`PUBCHAT__<段>__<键>`(如`PUBCHAT__SERVER__BIND`)
+//!
+//! 加载完成后会对配置进行校验，任何一项不合法都会汇总成可读的错误信息返回。
+
+use std::{collections::BTreeMap, env, net::SocketAddr, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use thiserror::Error;
+use toml::{Table, Value};
+
+//配置文件路径环境变量
+const CONFIG_PATH_ENV: &str = "PUBCHAT_CONFIG";
+//未指定配置文件时尝试读取的文件
+const DEFAULT_CONFIG_FILE: &str = "pubchat.toml";
+//通用环境变量覆盖前缀
+const ENV_PREFIX: &str = "PUBCHAT__";
+//配置文件中按服务划分的段名
+const SERVICES: [&str; 5] = ["user", "session", "blob", "connection", "client"];
+
+//兼容历史.env中的变量名：(环境变量, 段, 键)
+const LEGACY_ENV: [(&str, &str, &str); 11] = [
+    ("DATABASE_URL", "database", "url"),
+    ("REDIS_URL", "redis", "url"),
+    ("JWT_SECRET", "auth", "jwt_secret"),
+    ("RABBITMQ_ADDR", "rabbitmq", "addr"),
+    ("RABBITMQ_MESSAGE_EXCHANGE", "rabbitmq", "exchange"),
+    ("RABBITMQ_SESSION_MESSAGE_QUEUE_NAME", "rabbitmq", "queue"),
+    ("ACCESS_PRIVATE_KEY", "storage", "access_key"),
+    ("USER_SERVER_URL", "api", "user"),
+    ("CONNECTION_SERVER_URL", "api", "connection"),
+    ("SESSION_SERVER_URL", "api", "session"),
+    ("BLOB_SERVER_URL", "api", "blob"),
+];
+
+#[derive(Debug, Error)]
+pub enum ConfigError {
+    #[error("读取配置文件{0}失败: {1}")]
+    Read(PathBuf, std::io::Error),
+    #[error("解析配置文件{0}失败: {1}")]
+    Parse(PathBuf, toml::de::Error),
+    #[error("配置项类型不正确: {0}")]
+    Type(toml::de::Error),
+    #[error("配置校验失败:\n{}", .0.iter().map(|e| format!("  - {}", e)).collect::<Vec<_>>().join("\n"))]
+    Invalid(Vec<String>),
+}
+
+/// 配置校验，发现的问题追加到`errors`中
+pub trait Validate {
+    fn validate(&self, errors: &mut Vec<String>);
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ServerConfig {
+    //监听地址
+    pub bind: String,
+}
+
+impl ServerConfig {
+    fn new(bind: &str) -> Self {
+        Self { bind: bind.to_string() }
+    }
+}
+
+impl Validate for ServerConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.bind.parse::<SocketAddr>().is_err() {
+            errors.push(format!("server.bind: `{}`不是合法的监听地址，格式如0.0.0.0:3001", self.bind));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    pub max_connections: u32,
+    pub acquire_timeout_secs: u64,
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: String::new(), max_connections: 5, acquire_timeout_secs: 5, idle_timeout_secs: 30 }
+    }
+}
+
+impl Validate for DatabaseConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        check_url("database.url", "DATABASE_URL", &self.url, &crate::db::Backend::SCHEMES, errors);
+        if self.max_connections == 0 {
+            errors.push("database.max_connections: 必须大于0".to_string());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RedisConfig {
+    pub url: String,
+}
+
+impl Validate for RedisConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        check_url("redis.url", "REDIS_URL", &self.url, &["redis://", "rediss://"], errors);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RabbitConfig {
+    pub addr: String,
+    //消息交换机
+    pub exchange: String,
+    //当前服务消费的队列
+    pub queue: String,
+    //处理失败的消息重新入队的最大次数，超过后转入死信队列
+    pub max_retries: u32,
+    //死信交换机及当前服务的死信队列
+    pub dead_letter_exchange: String,
+    pub dead_letter_queue: String,
+}
+
+impl RabbitConfig {
+    fn new(queue: &str) -> Self {
+        Self {
+            addr: String::new(),
+            exchange: "pubchat_message_exc".to_string(),
+            queue: queue.to_string(),
+            max_retries: 5,
+            dead_letter_exchange: "pubchat_message_dlx".to_string(),
+            dead_letter_queue: format!("{}{}", queue, crate::queue::DEAD_LETTER_SUFFIX),
+        }
+    }
+}
+
+impl Validate for RabbitConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        check_url("rabbitmq.addr", "RABBITMQ_ADDR", &self.addr, &["amqp://", "amqps://"], errors);
+        if self.exchange.trim().is_empty() {
+            errors.push("rabbitmq.exchange: 不能为空(RABBITMQ_MESSAGE_EXCHANGE)".to_string());
+        }
+        if self.queue.trim().is_empty() {
+            errors.push("rabbitmq.queue: 不能为空(RABBITMQ_SESSION_MESSAGE_QUEUE_NAME)".to_string());
+        }
+        if self.dead_letter_exchange.trim().is_empty() {
+            errors.push("rabbitmq.dead_letter_exchange: 不能为空".to_string());
+        }
+        if self.dead_letter_queue.trim().is_empty() || self.dead_letter_queue == self.queue {
+            errors.push("rabbitmq.dead_letter_queue: 不能为空且不能与rabbitmq.queue相同".to_string());
+        }
+        if !self.dead_letter_queue.ends_with(crate::queue::DEAD_LETTER_SUFFIX) {
+            errors.push(format!("rabbitmq.dead_letter_queue: 应以{}结尾", crate::queue::DEAD_LETTER_SUFFIX));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AuthConfig {
+    pub jwt_secret: String,
+    //Token有效期(秒)
+    pub token_ttl_secs: u64,
+}
+
+impl Default for AuthConfig {
+    fn default() -> Self {
+        Self { jwt_secret: String::new(), token_ttl_secs: 1800 }
+    }
+}
+
+impl Validate for AuthConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.jwt_secret.is_empty() {
+            errors.push("auth.jwt_secret: 未设置(JWT_SECRET)".to_string());
+        }
+        if self.token_ttl_secs == 0 {
+            errors.push("auth.token_ttl_secs: 必须大于0".to_string());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StorageConfig {
+    //文件存储根目录
+    pub path: String,
+    //对外访问路径前缀，对应nginx中的location
+    pub access_path: String,
+    //访问链接签名密钥
+    pub access_key: String,
+    //访问链接有效期(秒)
+    pub link_ttl_secs: u64,
+    //新上传文件的存储位置：local为本地目录，s3为[s3]段配置的对象存储
+    pub provider: String,
+}
+
+impl StorageConfig {
+    fn new(access_path: &str) -> Self {
+        #[cfg(target_os = "windows")]
+        let path = "D:/tmp/";
+        #[cfg(not(target_os = "windows"))]
+        let path = "/home/data/images/";
+        Self {
+            path: path.to_string(),
+            access_path: access_path.to_string(),
+            access_key: String::new(),
+            link_ttl_secs: 1800,
+            provider: StorageConfig::LOCAL.to_string(),
+        }
+    }
+}
+
+impl StorageConfig {
+    pub const LOCAL: &str = "local";
+    pub const S3: &str = "s3";
+}
+
+impl Validate for StorageConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.path.trim().is_empty() {
+            errors.push("storage.path: 不能为空".to_string());
+        }
+        if !self.access_path.starts_with('/') || !self.access_path.ends_with('/') {
+            errors.push(format!("storage.access_path: `{}`必须以/开头并以/结尾", self.access_path));
+        }
+        if self.provider != Self::LOCAL && self.provider != Self::S3 {
+            errors.push(format!("storage.provider: `{}`应为{}或{}", self.provider, Self::LOCAL, Self::S3));
+        }
+    }
+}
+
+//S3兼容的对象存储，如AWS S3、MinIO
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct S3Config {
+    //服务地址，如https://s3.us-east-1.amazonaws.com、http://127.0.0.1:9000
+    pub endpoint: String,
+    pub region: String,
+    pub bucket: String,
+    pub access_key: String,
+    pub secret_key: String,
+    //true时使用endpoint/bucket/key形式的地址，MinIO等自建服务通常需要；false时使用bucket.endpoint/key
+    pub path_style: bool,
+}
+
+impl Default for S3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            region: "us-east-1".to_string(),
+            bucket: String::new(),
+            access_key: String::new(),
+            secret_key: String::new(),
+            path_style: true,
+        }
+    }
+}
+
+impl S3Config {
+    pub fn is_configured(&self) -> bool {
+        !self.endpoint.is_empty()
+    }
+}
+
+impl Validate for S3Config {
+    fn validate(&self, errors: &mut Vec<String>) {
+        check_url("s3.endpoint", "PUBCHAT__S3__ENDPOINT", &self.endpoint, &["http://", "https://"], errors);
+        for (key, value) in [("region", &self.region), ("bucket", &self.bucket),
+            ("access_key", &self.access_key), ("secret_key", &self.secret_key)] {
+            if value.trim().is_empty() {
+                errors.push(format!("s3.{}: 未设置(PUBCHAT__S3__{})", key, key.to_uppercase()));
+            }
+        }
+    }
+}
+
+//客户端访问的各服务地址
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ApiConfig {
+    pub user: String,
+    //连接服务为TCP长连接，格式为host:port
+    pub connection: String,
+    pub session: String,
+    pub blob: String,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self {
+            user: "http://127.0.0.1:3000".to_string(),
+            connection: "127.0.0.1:8080".to_string(),
+            session: "http://127.0.0.1:3001".to_string(),
+            blob: "http://127.0.0.1:3002".to_string(),
+        }
+    }
+}
+
+impl Validate for ApiConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        let http = ["http://", "https://"];
+        check_url("api.user", "USER_SERVER_URL", &self.user, &http, errors);
+        check_url("api.session", "SESSION_SERVER_URL", &self.session, &http, errors);
+        check_url("api.blob", "BLOB_SERVER_URL", &self.blob, &http, errors);
+        if self.connection.trim().is_empty() || self.connection.contains("://") {
+            errors.push(format!("api.connection: `{}`格式应为host:port(CONNECTION_SERVER_URL)", self.connection));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UserConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub redis: RedisConfig,
+    pub auth: AuthConfig,
+    //头像存储
+    pub storage: StorageConfig,
+}
+
+impl Default for UserConfig {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::new("0.0.0.0:3000"),
+            database: DatabaseConfig::default(),
+            redis: RedisConfig::default(),
+            auth: AuthConfig::default(),
+            storage: StorageConfig::new("/images/"),
+        }
+    }
+}
+
+impl Validate for UserConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.server.validate(errors);
+        self.database.validate(errors);
+        self.redis.validate(errors);
+        self.auth.validate(errors);
+        self.storage.validate(errors);
+        if self.storage.provider != StorageConfig::LOCAL {
+            errors.push(format!("storage.provider: 头像只支持{}存储", StorageConfig::LOCAL));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SessionConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub redis: RedisConfig,
+    pub rabbitmq: RabbitConfig,
+    pub auth: AuthConfig,
+    pub retention: RetentionConfig,
+    //导出会话时查询文件信息
+    pub api: ApiConfig,
+}
+
+impl Default for SessionConfig {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::new("0.0.0.0:3001"),
+            database: DatabaseConfig::default(),
+            redis: RedisConfig::default(),
+            rabbitmq: RabbitConfig::new("pubchat_session_message_queue_session"),
+            auth: AuthConfig::default(),
+            retention: RetentionConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
+}
+
+impl Validate for SessionConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.server.validate(errors);
+        self.database.validate(errors);
+        self.redis.validate(errors);
+        self.rabbitmq.validate(errors);
+        self.auth.validate(errors);
+        self.retention.validate(errors);
+        self.api.validate(errors);
+    }
+}
+
+//消息保留策略，天数为0表示永久保留
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RetentionConfig {
+    //全局默认保留天数
+    pub days: u32,
+    //按组织覆盖的保留天数，key为组织ID
+    pub orgs: BTreeMap<String, u32>,
+    //清理任务执行间隔(秒)
+    pub interval_secs: u64,
+    //每批删除的消息数
+    pub batch_size: u32,
+    //过期消息是否先归档到messages_archive表
+    pub archive: bool,
+    //清理指标(GET /metrics)的监听地址，与业务接口分开，默认只允许本机访问
+    pub metrics_bind: String,
+}
+
+impl Default for RetentionConfig {
+    fn default() -> Self {
+        Self {
+            days: 0,
+            orgs: BTreeMap::new(),
+            interval_secs: 3600,
+            batch_size: 1000,
+            archive: false,
+            metrics_bind: "127.0.0.1:9101".to_string(),
+        }
+    }
+}
+
+impl RetentionConfig {
+    //组织的保留天数，未配置时返回None
+    pub fn org(&self, oid: i64) -> Option<u32> {
+        self.orgs.get(&oid.to_string()).copied()
+    }
+}
+
+impl Validate for RetentionConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        for key in self.orgs.keys() {
+            if key.parse::<i64>().is_err() {
+                errors.push(format!("retention.orgs: `{}`不是合法的组织ID", key));
+            }
+        }
+        if self.interval_secs == 0 {
+            errors.push("retention.interval_secs: 必须大于0".to_string());
+        }
+        if self.batch_size == 0 {
+            errors.push("retention.batch_size: 必须大于0".to_string());
+        }
+        if self.metrics_bind.parse::<SocketAddr>().is_err() {
+            errors.push(format!("retention.metrics_bind: `{}`不是合法的监听地址，格式如127.0.0.1:9101", self.metrics_bind));
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BlobConfig {
+    pub server: ServerConfig,
+    pub database: DatabaseConfig,
+    pub redis: RedisConfig,
+    pub auth: AuthConfig,
+    pub storage: StorageConfig,
+    pub s3: S3Config,
+    pub expiry: ExpiryConfig,
+    pub thumbnail: ThumbnailConfig,
+    pub upload: UploadConfig,
+}
+
+impl Default for BlobConfig {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::new("0.0.0.0:3002"),
+            database: DatabaseConfig::default(),
+            redis: RedisConfig::default(),
+            auth: AuthConfig::default(),
+            storage: StorageConfig::new("/blobs/"),
+            s3: S3Config::default(),
+            expiry: ExpiryConfig::default(),
+            thumbnail: ThumbnailConfig::default(),
+            upload: UploadConfig::default(),
+        }
+    }
+}
+
+impl Validate for BlobConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.server.validate(errors);
+        self.database.validate(errors);
+        self.redis.validate(errors);
+        self.auth.validate(errors);
+        self.storage.validate(errors);
+        if self.storage.access_key.is_empty() {
+            errors.push("storage.access_key: 未设置(ACCESS_PRIVATE_KEY)".to_string());
+        }
+        //已有文件可能存放在S3，切回本地存储时仍需读取，配置了就校验
+        if self.storage.provider == StorageConfig::S3 || self.s3.is_configured() {
+            self.s3.validate(errors);
+        }
+        self.expiry.validate(errors);
+        self.thumbnail.validate(errors);
+        self.upload.validate(errors);
+    }
+}
+
+//分片上传的文件大小上限及未完成上传的清理
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UploadConfig {
+    //单个文件的最大字节数
+    pub max_size: u64,
+    //超过该小时数没有写入分片的上传视为放弃，由回收任务删除
+    pub stale_hours: u32,
+}
+
+impl Default for UploadConfig {
+    fn default() -> Self {
+        Self { max_size: 4 * 1024 * 1024 * 1024, stale_hours: 24 }
+    }
+}
+
+impl Validate for UploadConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.max_size == 0 || self.max_size > i64::MAX as u64 {
+            errors.push("upload.max_size: 必须大于0".to_string());
+        }
+        if self.stale_hours == 0 {
+            errors.push("upload.stale_hours: 必须大于0".to_string());
+        }
+    }
+}
+
+//上传图片时生成的缩略图
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThumbnailConfig {
+    //缩略图长边的像素数，原图不超过该尺寸时不生成；为空时不生成缩略图
+    pub sizes: Vec<u32>,
+    //超过该像素数的图片只记录宽高，不解码生成缩略图
+    pub max_pixels: u64,
+}
+
+impl Default for ThumbnailConfig {
+    fn default() -> Self {
+        Self { sizes: vec![128, 512], max_pixels: 40_000_000 }
+    }
+}
+
+impl Validate for ThumbnailConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.sizes.iter().any(|&s| s == 0 || s > 4096) {
+            errors.push(format!("thumbnail.sizes: {:?}中的尺寸应为1到4096", self.sizes));
+        }
+        if self.max_pixels == 0 {
+            errors.push("thumbnail.max_pixels: 必须大于0".to_string());
+        }
+    }
+}
+
+//上传文件的有效期及过期文件回收
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExpiryConfig {
+    //未指定有效期时的默认天数
+    pub days: u32,
+    //上传者可以设置的最长天数
+    pub max_days: u32,
+    //回收任务执行间隔(秒)
+    pub interval_secs: u64,
+    //每批处理的过期文件数
+    pub batch_size: u32,
+}
+
+impl Default for ExpiryConfig {
+    fn default() -> Self {
+        Self { days: 7, max_days: 30, interval_secs: 3600, batch_size: 100 }
+    }
+}
+
+impl Validate for ExpiryConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        if self.days == 0 {
+            errors.push("expiry.days: 必须大于0".to_string());
+        }
+        if self.max_days < self.days {
+            errors.push(format!("expiry.max_days: 不能小于expiry.days({})", self.days));
+        }
+        if self.interval_secs == 0 {
+            errors.push("expiry.interval_secs: 必须大于0".to_string());
+        }
+        if self.batch_size == 0 {
+            errors.push("expiry.batch_size: 必须大于0".to_string());
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ConnectionConfig {
+    pub server: ServerConfig,
+    pub rabbitmq: RabbitConfig,
+    pub auth: AuthConfig,
+    //会话成员缓存未命中时从api.session加载
+    pub api: ApiConfig,
+}
+
+impl Default for ConnectionConfig {
+    fn default() -> Self {
+        Self {
+            server: ServerConfig::new("127.0.0.1:8080"),
+            rabbitmq: RabbitConfig::new("pubchat_session_message_queue_connection"),
+            auth: AuthConfig::default(),
+            api: ApiConfig::default(),
+        }
+    }
+}
+
+impl Validate for ConnectionConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.server.validate(errors);
+        self.rabbitmq.validate(errors);
+        self.auth.validate(errors);
+        self.api.validate(errors);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ClientConfig {
+    pub api: ApiConfig,
+}
+
+impl Validate for ClientConfig {
+    fn validate(&self, errors: &mut Vec<String>) {
+        self.api.validate(errors);
+    }
+}
+
+/// 加载指定服务的配置
+pub fn load<T>(service: &str) -> Result<T, ConfigError>
+where
+    T: Default + Serialize + DeserializeOwned + Validate,
+{
+    let file = read_file()?;
+    let env: Vec<(String, String)> = env::vars().collect();
+    resolve(service, file, &env)
+}
+
+/// 加载配置，失败时输出可读的错误信息并退出进程
+pub fn load_or_exit<T>(service: &str) -> T
+where
+    T: Default + Serialize + DeserializeOwned + Validate,
+{
+    match load(service) {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("[{}] {}", service, e);
+            eprintln!("[{}] {}", service, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// 输出当前生效的配置，密钥等敏感信息会被隐藏
+pub fn print<T: Serialize>(service: &str, config: &T) {
+    let mut value = Value::try_from(config).expect("序列化配置失败");
+    mask(&mut value);
+    let text = toml::to_string_pretty(&value).unwrap_or_default();
+    log::info!("[{}] effective config:\n{}", service, text);
+    println!("[{}] effective config:\n{}", service, text);
+}
+
+fn read_file() -> Result<Option<Table>, ConfigError> {
+    let path = match env::var(CONFIG_PATH_ENV) {
+        Ok(path) => PathBuf::from(path),
+        Err(_) => {
+            let path = PathBuf::from(DEFAULT_CONFIG_FILE);
+            if !path.exists() {
+                return Ok(None);
+            }
+            path
+        }
+    };
+    let content = std::fs::read_to_string(&path).map_err(|e| ConfigError::Read(path.clone(), e))?;
+    let table = toml::from_str::<Table>(&content).map_err(|e| ConfigError::Parse(path, e))?;
+    Ok(Some(table))
+}
+
+//按默认值、配置文件公共段、配置文件服务段、环境变量的顺序合并并校验
+pub(crate) fn resolve<T>(service: &str, file: Option<Table>, env: &[(String, String)]) -> Result<T, ConfigError>
+where
+    T: Default + Serialize + DeserializeOwned + Validate,
+{
+    let mut table = match Value::try_from(T::default()).expect("序列化默认配置失败") {
+        Value::Table(table) => table,
+        _ => Table::new(),
+    };
+    if let Some(mut file) = file {
+        let own = file.remove(service);
+        //公共段只合并当前服务用到的部分
+        let shared: Table = file
+            .into_iter()
+            .filter(|(k, _)| table.contains_key(k) && !SERVICES.contains(&k.as_str()))
+            .collect();
+        merge(&mut table, shared);
+        if let Some(Value::Table(own)) = own {
+            merge(&mut table, own);
+        }
+    }
+    for (name, section, key) in LEGACY_ENV {
+        if let Some((_, value)) = env.iter().find(|(n, _)| n == name) {
+            set(&mut table, section, key, value);
+        }
+    }
+    for (name, value) in env {
+        if let Some(path) = name.strip_prefix(ENV_PREFIX) {
+            let parts: Vec<String> = path.split("__").map(|p| p.to_lowercase()).collect();
+            if let [section, key] = parts.as_slice() {
+                set(&mut table, section, key, value);
+            }
+        }
+    }
+    let config: T = Value::Table(table).try_into().map_err(ConfigError::Type)?;
+    let mut errors = vec![];
+    config.validate(&mut errors);
+    if !errors.is_empty() {
+        return Err(ConfigError::Invalid(errors));
+    }
+    Ok(config)
+}
+
+fn merge(base: &mut Table, overlay: Table) {
+    for (key, value) in overlay {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base)), Value::Table(overlay)) => merge(base, overlay),
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+//环境变量均为字符串，按默认值的类型进行转换；转换失败时保留字符串，由反序列化报告类型错误
+fn set(table: &mut Table, section: &str, key: &str, raw: &str) {
+    let Some(Value::Table(section)) = table.get_mut(section) else {
+        return;
+    };
+    let value = match section.get(key) {
+        Some(Value::Integer(_)) => raw.parse().map(Value::Integer).unwrap_or(Value::String(raw.to_string())),
+        Some(Value::Boolean(_)) => raw.parse().map(Value::Boolean).unwrap_or(Value::String(raw.to_string())),
+        _ => Value::String(raw.to_string()),
+    };
+    section.insert(key.to_string(), value);
+}
+
+fn check_url(field: &str, env: &str, url: &str, schemes: &[&str], errors: &mut Vec<String>) {
+    if url.trim().is_empty() {
+        errors.push(format!("{}: 未设置({})", field, env));
+    } else if !schemes.iter().any(|s| url.starts_with(s)) {
+        errors.push(format!("{}: `{}`应以{}开头", field, mask_url(url), schemes.join("或")));
+    }
+}
+
+fn mask(value: &mut Value) {
+    if let Value::Table(table) = value {
+        for (key, value) in table.iter_mut() {
+            match value {
+                Value::String(s) if (key.contains("secret") || key.ends_with("key")) && !s.is_empty() => {
+                    *s = "******".to_string();
+                }
+                Value::String(s) => *s = mask_url(s),
+                _ => mask(value),
+            }
+        }
+    }
+}
+
+//隐藏连接串中的密码，如redis://:pwd@host -> redis://:******@host
+pub(crate) fn mask_url(url: &str) -> String {
+    let Some(start) = url.find("://").map(|i| i + 3) else {
+        return url.to_string();
+    };
+    let Some(at) = url[start..].rfind('@').map(|i| start + i) else {
+        return url.to_string();
+    };
+    match url[start..at].find(':') {
+        Some(colon) => format!("{}******{}", &url[..start + colon + 1], &url[at..]),
+        None => url.to_string(),
+    }
+}
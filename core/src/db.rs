@@ -0,0 +1,235 @@
+//! 数据库后端
+//!
+//! 服务端的仓储层按`database.url`的协议选择后端：`mysql://`、`sqlite:`或`postgres://`。
+//! 各后端的连接池统一封装为[`Pool`]，仓储函数通过[`dispatch!`](crate::dispatch)宏对三种连接执行同一段查询代码，
+//! SQL使用`?`占位符书写，Postgres连接上会自动改写为`$1, $2...`。
+//! 方言不同的语句(如`INSERT IGNORE`、`ON DUPLICATE KEY UPDATE`)按[`Backend`]分别书写，迁移脚本按后端放在`migrations/<backend>`目录。
+
+use std::{borrow::Cow, str::FromStr, time::Duration};
+
+use anyhow::Result;
+use sqlx::{
+    migrate::Migrator,
+    mysql::MySqlPoolOptions,
+    pool::PoolConnection,
+    postgres::PgPoolOptions,
+    sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions},
+    MySql, MySqlPool, PgPool, Postgres, Sqlite, SqlitePool,
+};
+
+use crate::config::DatabaseConfig;
+use crate::response::ApiErr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    MySql,
+    Sqlite,
+    Postgres,
+}
+
+impl Backend {
+    //支持的连接地址协议
+    pub const SCHEMES: [&str; 4] = ["mysql://", "sqlite:", "postgres://", "postgresql://"];
+
+    pub fn from_url(url: &str) -> Result<Self> {
+        if url.starts_with("mysql://") {
+            Ok(Backend::MySql)
+        } else if url.starts_with("sqlite:") {
+            Ok(Backend::Sqlite)
+        } else if url.starts_with("postgres://") || url.starts_with("postgresql://") {
+            Ok(Backend::Postgres)
+        } else {
+            Err(ApiErr::Error(format!("不支持的数据库地址，应以{}开头", Self::SCHEMES.join("或"))).into())
+        }
+    }
+
+    //MySQL与其他后端语法不同的语句，SQLite和Postgres的语法一致
+    pub fn pick<'a>(&self, mysql: &'a str, standard: &'a str) -> &'a str {
+        match self {
+            Backend::MySql => mysql,
+            _ => standard,
+        }
+    }
+
+    //主键或唯一键冲突时忽略的插入语句，sql为`INTO table (...) VALUES (...)`
+    pub fn insert_ignore(&self, sql: &str) -> String {
+        match self {
+            Backend::MySql => format!("INSERT IGNORE {}", sql),
+            _ => format!("INSERT {} ON CONFLICT DO NOTHING", sql),
+        }
+    }
+
+    //主键或唯一键冲突时更新columns的插入语句，keys为冲突的键
+    pub fn upsert(&self, sql: &str, keys: &[&str], columns: &[&str]) -> String {
+        match self {
+            Backend::MySql => format!("INSERT {} ON DUPLICATE KEY UPDATE {}", sql,
+                columns.iter().map(|c| format!("{0} = VALUES({0})", c)).collect::<Vec<_>>().join(", ")),
+            _ => format!("INSERT {} ON CONFLICT ({}) DO UPDATE SET {}", sql, keys.join(", "),
+                columns.iter().map(|c| format!("{0} = excluded.{0}", c)).collect::<Vec<_>>().join(", ")),
+        }
+    }
+
+    //将DATETIME列转换为毫秒时间戳的表达式
+    pub fn epoch_millis(&self, column: &str) -> String {
+        match self {
+            Backend::MySql => format!("UNIX_TIMESTAMP({}) * 1000", column),
+            Backend::Sqlite => format!("CAST(strftime('%s', {}) AS INTEGER) * 1000", column),
+            Backend::Postgres => format!("CAST(EXTRACT(EPOCH FROM {}) AS BIGINT) * 1000", column),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum Pool {
+    MySql(MySqlPool),
+    Sqlite(SqlitePool),
+    Postgres(PgPool),
+}
+
+pub enum Connection {
+    MySql(PoolConnection<MySql>),
+    Sqlite(PoolConnection<Sqlite>),
+    Postgres(PoolConnection<Postgres>),
+}
+
+//内存数据库随最后一个连接关闭而销毁，只能使用一个常驻连接
+fn in_memory(url: &str) -> bool {
+    url.contains(":memory:") || url.contains("mode=memory")
+}
+
+impl Pool {
+    //连接数据库并执行对应后端的迁移脚本，migrator按后端返回`sqlx::migrate!`生成的迁移
+    pub async fn connect(config: &DatabaseConfig, migrator: fn(Backend) -> Migrator) -> Result<Pool> {
+        let backend = Backend::from_url(&config.url)?;
+        let acquire_timeout = Duration::from_secs(config.acquire_timeout_secs);
+        let idle_timeout = Duration::from_secs(config.idle_timeout_secs);
+        let pool = match backend {
+            Backend::MySql => Pool::MySql(MySqlPoolOptions::new()
+                .max_connections(config.max_connections)
+                .acquire_timeout(acquire_timeout)
+                .idle_timeout(idle_timeout)
+                .acquire_slow_threshold(Duration::from_secs(3))
+                .connect(&config.url)
+                .await?),
+            Backend::Sqlite => {
+                let options = SqliteConnectOptions::from_str(&config.url)?
+                    .create_if_missing(true)
+                    .foreign_keys(true);
+                let pool = if in_memory(&config.url) {
+                    SqlitePoolOptions::new()
+                        .max_connections(1)
+                        .min_connections(1)
+                        .idle_timeout(None)
+                        .max_lifetime(None)
+                } else {
+                    SqlitePoolOptions::new()
+                        .max_connections(config.max_connections)
+                        .idle_timeout(idle_timeout)
+                };
+                let options = if in_memory(&config.url) { options } else { options.journal_mode(SqliteJournalMode::Wal) };
+                Pool::Sqlite(pool.acquire_timeout(acquire_timeout).connect_with(options).await?)
+            }
+            Backend::Postgres => Pool::Postgres(PgPoolOptions::new()
+                .max_connections(config.max_connections)
+                .acquire_timeout(acquire_timeout)
+                .idle_timeout(idle_timeout)
+                .acquire_slow_threshold(Duration::from_secs(3))
+                .connect(&config.url)
+                .await?),
+        };
+        let migrator = migrator(backend);
+        match &pool {
+            Pool::MySql(pool) => migrator.run(pool).await?,
+            Pool::Sqlite(pool) => migrator.run(pool).await?,
+            Pool::Postgres(pool) => migrator.run(pool).await?,
+        }
+        Ok(pool)
+    }
+
+    pub fn backend(&self) -> Backend {
+        match self {
+            Pool::MySql(_) => Backend::MySql,
+            Pool::Sqlite(_) => Backend::Sqlite,
+            Pool::Postgres(_) => Backend::Postgres,
+        }
+    }
+
+    pub async fn acquire(&self) -> Result<Connection> {
+        Ok(match self {
+            Pool::MySql(pool) => Connection::MySql(pool.acquire().await?),
+            Pool::Sqlite(pool) => Connection::Sqlite(pool.acquire().await?),
+            Pool::Postgres(pool) => Connection::Postgres(pool.acquire().await?),
+        })
+    }
+}
+
+//将`?`占位符改写为Postgres的`$1, $2...`，忽略字符串字面量中的问号
+pub fn numbered(sql: &str) -> Cow<'_, str> {
+    if !sql.contains('?') {
+        return Cow::Borrowed(sql);
+    }
+    let mut result = String::with_capacity(sql.len() + 16);
+    let mut quoted = false;
+    let mut index = 0;
+    for c in sql.chars() {
+        match c {
+            '\'' => {
+                quoted = !quoted;
+                result.push(c);
+            }
+            '?' if !quoted => {
+                index += 1;
+                result.push('$');
+                result.push_str(&index.to_string());
+            }
+            _ => result.push(c),
+        }
+    }
+    Cow::Owned(result)
+}
+
+/// 在三种后端的连接上执行同一段查询代码
+///
+/// `dispatch!(connection, sql, |sql, conn| body)`：`sql`为使用`?`占位符的语句，
+/// 在Postgres连接上会改写为编号占位符；`conn`为对应后端的`&mut Connection`，可作为`Executor`使用。
+/// 不需要语句时(如使用`QueryBuilder`)可省略`sql`：`dispatch!(connection, |conn| body)`。
+#[macro_export]
+macro_rules! dispatch {
+    ($connection:expr, |$c:ident| $body:expr) => {
+        match $connection {
+            $crate::db::Connection::MySql(mut connection) => {
+                let $c = connection.as_mut();
+                $body
+            }
+            $crate::db::Connection::Sqlite(mut connection) => {
+                let $c = connection.as_mut();
+                $body
+            }
+            $crate::db::Connection::Postgres(mut connection) => {
+                let $c = connection.as_mut();
+                $body
+            }
+        }
+    };
+    ($connection:expr, $sql:expr, |$q:ident, $c:ident| $body:expr) => {
+        match $connection {
+            $crate::db::Connection::MySql(mut connection) => {
+                let $c = connection.as_mut();
+                let $q: &str = $sql;
+                $body
+            }
+            $crate::db::Connection::Sqlite(mut connection) => {
+                let $c = connection.as_mut();
+                let $q: &str = $sql;
+                $body
+            }
+            $crate::db::Connection::Postgres(mut connection) => {
+                let $c = connection.as_mut();
+                let sql: &str = $sql;
+                let sql = $crate::db::numbered(sql);
+                let $q: &str = &sql;
+                $body
+            }
+        }
+    };
+}
@@ -0,0 +1,61 @@
+//! 多实例部署时后台任务的租约
+//!
+//! 后台任务在每个实例上都会启动，每次执行前先获取租约，同一时间只有持有租约的实例执行。
+//! 租约记录在服务自己数据库的`leases`表中，持有者为进程启动后生成的id；持有者在租约到期前再次获取即为续期，
+//! 实例异常退出后租约到期，其他实例可以获取。
+
+use std::{sync::OnceLock, time::Duration};
+
+use anyhow::Result;
+
+use crate::db::Pool;
+use crate::dispatch;
+
+static HOLDER: OnceLock<i64> = OnceLock::new();
+
+//当前进程的持有者id
+pub fn holder() -> i64 {
+    *HOLDER.get_or_init(|| snowflaker::next_id().map(|id| id as i64).unwrap_or_else(|_| std::process::id() as i64))
+}
+
+fn now() -> i64 {
+    chrono::Utc::now().timestamp_millis()
+}
+
+//获取或续期租约，租约由其他实例持有且未到期时返回false
+pub async fn acquire(pool: &Pool, name: &str, ttl: Duration) -> Result<bool> {
+    let now = now();
+    let sql = pool.backend().insert_ignore("INTO leases (name, holder, expires_at) VALUES (?, ?, ?)");
+    dispatch!(pool.acquire().await?, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(name)
+            .bind(0i64)
+            .bind(0i64)
+            .execute(conn)
+            .await?;
+    });
+    let rows = dispatch!(pool.acquire().await?, "UPDATE leases SET holder = ?, expires_at = ? WHERE name = ? AND (holder = ? OR expires_at < ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(holder())
+            .bind(now + ttl.as_millis() as i64)
+            .bind(name)
+            .bind(holder())
+            .bind(now)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(rows > 0)
+}
+
+//提前释放当前进程持有的租约，其他实例无需等待到期
+pub async fn release(pool: &Pool, name: &str) -> Result<()> {
+    dispatch!(pool.acquire().await?, "UPDATE leases SET expires_at = 0 WHERE name = ? AND holder = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(name)
+            .bind(holder())
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
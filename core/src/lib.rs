@@ -4,4 +4,9 @@ pub mod extract;
 pub mod auth;
 pub mod log;
 pub mod api;
-pub mod extension;
\ No newline at end of file
+pub mod extension;
+pub mod config;
+pub mod db;
+pub mod queue;
+pub mod lease;
+pub mod test;
\ No newline at end of file
@@ -0,0 +1,26 @@
+//! 消息队列的重试及死信约定，会话服务与连接服务共用
+//!
+//! 处理失败的消息带上已失败次数重新发布到原队列，超过`rabbitmq.max_retries`后转入死信队列，
+//! 死信附带来源队列、失败原因等消息头，由会话服务的管理接口查看和重放。
+
+//死信消息的附加头
+pub const SOURCE: &str = "x-pubchat-source";
+pub const EXCHANGE: &str = "x-pubchat-exchange";
+pub const ROUTING_KEY: &str = "x-pubchat-routing-key";
+pub const REASON: &str = "x-pubchat-reason";
+//已失败的次数，重试及转入死信时都会写入
+pub const ATTEMPTS: &str = "x-pubchat-attempts";
+pub const FAILED_AT: &str = "x-pubchat-failed-at";
+//重放死信时去掉的消息头
+pub const HEADERS: [&str; 6] = [SOURCE, EXCHANGE, ROUTING_KEY, REASON, ATTEMPTS, FAILED_AT];
+
+//死信队列名称的后缀，管理接口只允许操作以此结尾的队列
+pub const DEAD_LETTER_SUFFIX: &str = "_dlq";
+
+//重新发布前的等待时间，按失败次数递增
+pub const RETRY_BACKOFF: std::time::Duration = std::time::Duration::from_millis(200);
+
+//处理失败后是否还可以重试，attempts为包括本次在内的失败次数
+pub fn can_retry(attempts: u32, max_retries: u32) -> bool {
+    attempts <= max_retries
+}
@@ -29,4 +29,50 @@ impl Default for Page {
     fn default() -> Self {
         Page { ps: 10u32, pn: 1u32, fd: None, dir: None }
     }
-}
\ No newline at end of file
+}
+
+//游标分页参数，before向更早的消息翻页，after向更新的消息翻页，都为空时返回最新的一页
+//游标取自上一页响应中的cursor，也可直接传入毫秒时间戳
+#[derive(Debug, Deserialize, Serialize, Default)]
+pub struct Cursor{
+    pub ps: Option<u32>,//页大小
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl Cursor{
+    pub const DEFAULT_SIZE: u32 = 20;
+    pub const MAX_SIZE: u32 = 100;
+
+    pub fn ps(&self) -> u32 {
+        self.ps.unwrap_or(Self::DEFAULT_SIZE).clamp(1, Self::MAX_SIZE)
+    }
+}
+
+//游标位置，按(timestamp, id)排序保证相同时间戳的消息顺序稳定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Position{
+    pub timestamp: i64,
+    pub id: i64,
+}
+
+impl Position{
+    pub fn new(timestamp: i64, id: i64) -> Self {
+        Self { timestamp, id }
+    }
+
+    //解析游标，格式为`{timestamp}_{id}`；只有时间戳时，before取该时间之前、after取该时间之后的消息
+    pub fn parse(cursor: &str, before: bool) -> Option<Self> {
+        match cursor.trim().split_once('_') {
+            Some((timestamp, id)) => Some(Self::new(timestamp.parse().ok()?, id.parse().ok()?)),
+            None => {
+                let timestamp = cursor.trim().parse().ok()?;
+                Some(Self::new(timestamp, if before { i64::MIN } else { i64::MAX }))
+            }
+        }
+    }
+
+    pub fn encode(&self) -> String {
+        format!("{}_{}", self.timestamp, self.id)
+    }
+}
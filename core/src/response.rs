@@ -7,6 +7,15 @@ pub enum ApiResponse<T: Serialize>{
     One(T),
     //分页数据
     List(Vec<T>, i64),
+    //游标分页数据，携带向前、向后翻页的游标
+    Cursor(Vec<T>, Cursors),
+}
+
+//游标分页的翻页游标，before用于获取更早的一页，after用于获取更新的一页，为空表示该方向没有更多数据
+#[derive(Debug, Default, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Cursors{
+    pub before: Option<String>,
+    pub after: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Error)]
@@ -52,7 +61,9 @@ impl<T: Serialize> IntoResponse for ApiResponse<T>{
             Self::One(data) => 
                 (axum::http::StatusCode::OK, Json(ApiResult::ok(data))).into_response(),
             Self::List(data, count) =>
-                (axum::http::StatusCode::OK, Json(ApiResult::<Vec<T>>::page(data, count))).into_response()
+                (axum::http::StatusCode::OK, Json(ApiResult::<Vec<T>>::page(data, count))).into_response(),
+            Self::Cursor(data, cursors) =>
+                (axum::http::StatusCode::OK, Json(ApiResult::<Vec<T>>::cursor(data, cursors))).into_response(),
         }
     }
 }
@@ -66,6 +77,12 @@ pub struct ApiResult<T: Serialize>{
     pub ok: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub count: Option<i64>,
+    //更早一页的游标
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<String>,
+    //更新一页的游标
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<String>,
 }
 
 impl <T: Serialize> ApiResult<T>{
@@ -75,6 +92,8 @@ impl <T: Serialize> ApiResult<T>{
             message: None,
             ok: true,
             count: None,
+            before: None,
+            after: None,
         }
     }
 
@@ -84,6 +103,8 @@ impl <T: Serialize> ApiResult<T>{
             message: Some(message),
             ok: false,
             count: None,
+            before: None,
+            after: None,
         }
     }
 
@@ -93,6 +114,19 @@ impl <T: Serialize> ApiResult<T>{
             message: None,
             ok: true,
             count: Some(count),
+            before: None,
+            after: None,
+        }
+    }
+
+    pub fn cursor(data: T, cursors: Cursors) -> Self{
+        Self{
+            data: Some(data),
+            message: None,
+            ok: true,
+            count: None,
+            before: cursors.before,
+            after: cursors.after,
         }
     }
 }
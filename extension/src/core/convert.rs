@@ -36,4 +36,16 @@ impl Display for crate::core::message::chrs::Message {
                 file.name, file.size, file.exp.as_ref().unwrap_or(&"never".to_string()), base62::encode(file.id as u64)),
         }
     }
-}
\ No newline at end of file
+}
+impl crate::core::message::Notice {
+    //公告是否已过期，now为毫秒时间戳
+    pub fn expired(&self, now: u64) -> bool {
+        self.exp.is_some_and(|exp| exp <= now)
+    }
+}
+
+impl Display for crate::core::message::Notice {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::result::Result<(), std::fmt::Error> {
+        write!(f, "[{}] {}", self.severity().as_str_name(), self.text)
+    }
+}
@@ -1,7 +1,7 @@
 // This file is @generated by prost-build.
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
-#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+#[derive(Clone, PartialEq, ::prost::Message)]
 pub struct Message {
     /// 消息id
     #[prost(uint64, tag = "1")]
@@ -12,14 +12,14 @@ pub struct Message {
     /// 消息类型
     #[prost(enumeration = "Type", tag = "3")]
     pub mtype: i32,
-    #[prost(oneof = "message::Content", tags = "4, 5, 6, 7, 8, 9")]
+    #[prost(oneof = "message::Content", tags = "4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14")]
     pub content: ::core::option::Option<message::Content>,
 }
 /// Nested message and enum types in `Message`.
 pub mod message {
     #[derive(serde::Serialize, serde::Deserialize)]
     #[serde(rename_all = "snake_case")]
-    #[derive(Clone, PartialEq, Eq, Hash, ::prost::Oneof)]
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
     pub enum Content {
         #[prost(message, tag = "4")]
         Cort(super::Cort),
@@ -33,6 +33,16 @@ pub mod message {
         Ping(super::Ping),
         #[prost(message, tag = "9")]
         Pong(super::Pong),
+        #[prost(message, tag = "10")]
+        Notice(super::Notice),
+        #[prost(message, tag = "11")]
+        Members(super::Members),
+        #[prost(message, tag = "12")]
+        Setting(super::Setting),
+        #[prost(message, tag = "13")]
+        Moderation(super::Moderation),
+        #[prost(message, tag = "14")]
+        Reject(super::Reject),
     }
 }
 /// ConnectRequest
@@ -76,6 +86,9 @@ pub struct Chrt {
     pub ctype: i32,
     #[prost(uint64, tag = "6")]
     pub ts: u64,
+    /// 命中需要人工审核的规则时由连接服务填写原因，会话服务据此记录待审核消息
+    #[prost(string, optional, tag = "9")]
+    pub flag: ::core::option::Option<::prost::alloc::string::String>,
     #[prost(oneof = "chrt::Message", tags = "7, 8")]
     pub message: ::core::option::Option<chrt::Message>,
 }
@@ -110,6 +123,9 @@ pub struct Chrs {
     pub ctype: i32,
     #[prost(uint64, tag = "6")]
     pub ts: u64,
+    /// 接收者已静音该会话或消息未提及接收者，客户端不提醒
+    #[prost(bool, tag = "9")]
+    pub silent: bool,
     #[prost(oneof = "chrs::Message", tags = "7, 8")]
     pub message: ::core::option::Option<chrs::Message>,
 }
@@ -147,6 +163,110 @@ pub struct Blob {
     #[prost(string, optional, tag = "4")]
     pub exp: ::core::option::Option<::prost::alloc::string::String>,
 }
+/// 系统公告，如维护通知、组织公告
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Notice {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(enumeration = "Severity", tag = "2")]
+    pub severity: i32,
+    #[prost(enumeration = "Scope", tag = "3")]
+    pub scope: i32,
+    /// 公告范围对应的组织id或会话id，scope=ALL时为0
+    #[prost(uint64, tag = "4")]
+    pub target: u64,
+    #[prost(string, tag = "5")]
+    pub text: ::prost::alloc::string::String,
+    /// 过期时间戳(单位:毫秒)，为空表示不过期
+    #[prost(uint64, optional, tag = "6")]
+    pub exp: ::core::option::Option<u64>,
+    #[prost(uint64, tag = "7")]
+    pub sender: u64,
+    /// 发布者昵称
+    #[prost(string, tag = "8")]
+    pub uname: ::prost::alloc::string::String,
+    #[prost(uint64, tag = "9")]
+    pub ts: u64,
+    /// scope=SESSION时由会话服务填充会话成员
+    #[prost(uint64, repeated, tag = "10")]
+    pub receivers: ::prost::alloc::vec::Vec<u64>,
+}
+/// 会话成员列表，会话服务在成员变更时发布，连接服务据此确定聊天消息的接收者
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Members {
+    #[prost(uint64, tag = "1")]
+    pub session: u64,
+    #[prost(uint64, repeated, tag = "2")]
+    pub members: ::prost::alloc::vec::Vec<u64>,
+    #[prost(enumeration = "SessionKind", tag = "3")]
+    pub kind: i32,
+    /// 群主及管理员，频道中只有他们可以发言
+    #[prost(uint64, repeated, tag = "4")]
+    pub admins: ::prost::alloc::vec::Vec<u64>,
+    /// 会话所属组织，连接服务据此选择内容审核规则
+    #[prost(uint64, tag = "5")]
+    pub oid: u64,
+}
+/// 用户在会话中的通知设置，会话服务在设置变更时发布，连接服务据此标记静默消息
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Setting {
+    #[prost(uint64, tag = "1")]
+    pub session: u64,
+    #[prost(uint64, tag = "2")]
+    pub uid: u64,
+    #[prost(enumeration = "Notify", tag = "3")]
+    pub notify: i32,
+    /// 静音截止时间戳(单位:毫秒)，为空表示未静音
+    #[prost(uint64, optional, tag = "4")]
+    pub muted_until: ::core::option::Option<u64>,
+}
+/// 组织的内容审核规则，会话服务在规则变更时发布组织的全部规则，连接服务据此审核聊天消息
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Moderation {
+    #[prost(uint64, tag = "1")]
+    pub oid: u64,
+    #[prost(message, repeated, tag = "2")]
+    pub rules: ::prost::alloc::vec::Vec<Rule>,
+}
+/// 审核规则
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Rule {
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(enumeration = "RuleKind", tag = "2")]
+    pub kind: i32,
+    /// 违禁词、正则表达式或禁止的链接域名(为空表示禁止所有链接)
+    #[prost(string, tag = "3")]
+    pub pattern: ::prost::alloc::string::String,
+    /// kind=LENGTH时的最大消息长度(字符数)
+    #[prost(uint32, tag = "4")]
+    pub max_len: u32,
+    #[prost(enumeration = "RuleAction", tag = "5")]
+    pub action: i32,
+}
+/// 聊天消息被审核拒绝，连接服务发送给发送者
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, PartialEq, Eq, Hash, ::prost::Message)]
+pub struct Reject {
+    /// 被拒绝的消息id
+    #[prost(uint64, tag = "1")]
+    pub id: u64,
+    #[prost(uint64, tag = "2")]
+    pub session: u64,
+    #[prost(string, tag = "3")]
+    pub reason: ::prost::alloc::string::String,
+}
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "snake_case")]
 #[derive(Clone, Copy, PartialEq, Eq, Hash, ::prost::Message)]
@@ -237,6 +357,16 @@ pub enum Type {
     /// 心跳消息
     Ping = 6,
     Pong = 7,
+    /// 系统公告
+    Noti = 8,
+    /// 会话成员变更
+    Memb = 9,
+    /// 会话通知设置变更
+    Sett = 10,
+    /// 组织内容审核规则变更
+    Modr = 11,
+    /// 聊天消息被审核拒绝，发送给发送者
+    Rjct = 12,
 }
 impl Type {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -251,6 +381,11 @@ impl Type {
             Self::Chrs => "CHRS",
             Self::Ping => "PING",
             Self::Pong => "PONG",
+            Self::Noti => "NOTI",
+            Self::Memb => "MEMB",
+            Self::Sett => "SETT",
+            Self::Modr => "MODR",
+            Self::Rjct => "RJCT",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -262,6 +397,218 @@ impl Type {
             "CHRS" => Some(Self::Chrs),
             "PING" => Some(Self::Ping),
             "PONG" => Some(Self::Pong),
+            "NOTI" => Some(Self::Noti),
+            "MEMB" => Some(Self::Memb),
+            "SETT" => Some(Self::Sett),
+            "MODR" => Some(Self::Modr),
+            "RJCT" => Some(Self::Rjct),
+            _ => None,
+        }
+    }
+}
+/// 会话类型
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum SessionKind {
+    /// 单聊
+    Direct = 0,
+    /// 群聊
+    Group = 1,
+    /// 广播频道，组织内成员均可订阅，只有管理员可以发言
+    Channel = 2,
+}
+impl SessionKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Direct => "DIRECT",
+            Self::Group => "GROUP",
+            Self::Channel => "CHANNEL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "DIRECT" => Some(Self::Direct),
+            "GROUP" => Some(Self::Group),
+            "CHANNEL" => Some(Self::Channel),
+            _ => None,
+        }
+    }
+}
+/// 通知级别
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Notify {
+    /// 所有消息
+    All = 0,
+    /// 仅提及自己的消息
+    Mentions = 1,
+}
+impl Notify {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::All => "NOTIFY_ALL",
+            Self::Mentions => "NOTIFY_MENTIONS",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "NOTIFY_ALL" => Some(Self::All),
+            "NOTIFY_MENTIONS" => Some(Self::Mentions),
+            _ => None,
+        }
+    }
+}
+/// 审核规则类型
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RuleKind {
+    /// 违禁词，不区分大小写
+    Keyword = 0,
+    /// 正则表达式
+    Regex = 1,
+    /// 链接
+    Link = 2,
+    /// 消息长度
+    Length = 3,
+}
+impl RuleKind {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Keyword => "KEYWORD",
+            Self::Regex => "REGEX",
+            Self::Link => "LINK",
+            Self::Length => "LENGTH",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "KEYWORD" => Some(Self::Keyword),
+            "REGEX" => Some(Self::Regex),
+            "LINK" => Some(Self::Link),
+            "LENGTH" => Some(Self::Length),
+            _ => None,
+        }
+    }
+}
+/// 命中审核规则后的处理方式
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum RuleAction {
+    /// 拒绝发送
+    Reject = 0,
+    /// 屏蔽违规内容后发送
+    Mask = 1,
+    /// 正常发送并提交人工审核
+    Flag = 2,
+}
+impl RuleAction {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Reject => "REJECT",
+            Self::Mask => "MASK",
+            Self::Flag => "FLAG",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "REJECT" => Some(Self::Reject),
+            "MASK" => Some(Self::Mask),
+            "FLAG" => Some(Self::Flag),
+            _ => None,
+        }
+    }
+}
+/// 公告级别
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Severity {
+    Info = 0,
+    Warn = 1,
+    Critical = 2,
+}
+impl Severity {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::Info => "INFO",
+            Self::Warn => "WARN",
+            Self::Critical => "CRITICAL",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "INFO" => Some(Self::Info),
+            "WARN" => Some(Self::Warn),
+            "CRITICAL" => Some(Self::Critical),
+            _ => None,
+        }
+    }
+}
+/// 公告范围
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord, ::prost::Enumeration)]
+#[repr(i32)]
+pub enum Scope {
+    /// 所有用户
+    All = 0,
+    /// 指定组织
+    Org = 1,
+    /// 指定会话
+    Session = 2,
+}
+impl Scope {
+    /// String value of the enum field names used in the ProtoBuf definition.
+    ///
+    /// The values are not transformed in any way and thus are considered stable
+    /// (if the ProtoBuf definition does not change) and safe for programmatic use.
+    pub fn as_str_name(&self) -> &'static str {
+        match self {
+            Self::All => "ALL",
+            Self::Org => "ORG",
+            Self::Session => "SESSION",
+        }
+    }
+    /// Creates an enum from field names used in the ProtoBuf definition.
+    pub fn from_str_name(value: &str) -> ::core::option::Option<Self> {
+        match value {
+            "ALL" => Some(Self::All),
+            "ORG" => Some(Self::Org),
+            "SESSION" => Some(Self::Session),
             _ => None,
         }
     }
@@ -283,6 +630,8 @@ pub enum ChatType {
     Rtf = 4,
     /// * 文件
     File = 5,
+    /// * 系统消息，如成员变更
+    System = 6,
 }
 impl ChatType {
     /// String value of the enum field names used in the ProtoBuf definition.
@@ -297,6 +646,7 @@ impl ChatType {
             Self::Code => "CODE",
             Self::Rtf => "RTF",
             Self::File => "FILE",
+            Self::System => "SYSTEM",
         }
     }
     /// Creates an enum from field names used in the ProtoBuf definition.
@@ -308,6 +658,7 @@ impl ChatType {
             "CODE" => Some(Self::Code),
             "RTF" => Some(Self::Rtf),
             "FILE" => Some(Self::File),
+            "SYSTEM" => Some(Self::System),
             _ => None,
         }
     }
@@ -0,0 +1,10 @@
+-- 会话置顶消息表
+CREATE TABLE `pinned_messages` (
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `mid` BIGINT NOT NULL COMMENT '消息ID',
+  `uid` BIGINT NOT NULL COMMENT '置顶操作者ID',
+  `uname` VARCHAR(255) NOT NULL COMMENT '置顶操作者名称',
+  `pintime` BIGINT NOT NULL COMMENT '置顶时间戳(毫秒)',
+  PRIMARY KEY (`sid`, `mid`),
+  KEY `idx_sid_pintime` (`sid`, `pintime`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='会话置顶消息表';
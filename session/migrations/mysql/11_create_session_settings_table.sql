@@ -0,0 +1,9 @@
+-- 用户会话通知设置表，没有记录时使用默认设置(通知所有消息、未静音)
+CREATE TABLE `session_settings` (
+  `uid` BIGINT NOT NULL COMMENT '用户ID',
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `notify` TINYINT NOT NULL DEFAULT '0' COMMENT '通知级别: 0-所有消息, 1-仅提及',
+  `muted_until` BIGINT NULL DEFAULT NULL COMMENT '静音截止时间戳(毫秒)',
+  `updatetime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`uid`, `sid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='用户会话通知设置表';
@@ -0,0 +1,15 @@
+-- 会话邀请链接表
+CREATE TABLE `invites` (
+  `id` BIGINT NOT NULL COMMENT '邀请ID',
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `token` VARCHAR(512) NOT NULL COMMENT '签名的邀请令牌',
+  `creator` BIGINT NOT NULL COMMENT '创建者ID',
+  `uname` VARCHAR(255) NOT NULL COMMENT '创建者名称',
+  `max_uses` INT NULL DEFAULT NULL COMMENT '最多使用次数，为空表示不限',
+  `uses` INT NOT NULL DEFAULT '0' COMMENT '已使用次数',
+  `exp` BIGINT NOT NULL COMMENT '过期时间戳(毫秒)',
+  `revoked` TINYINT(1) NOT NULL DEFAULT '0' COMMENT '是否已撤销',
+  `createtime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  KEY `idx_sid` (`sid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='会话邀请链接表';
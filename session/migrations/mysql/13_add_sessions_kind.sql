@@ -0,0 +1,11 @@
+-- 会话类型，已有会话中由两个成员id计算出会话id的为单聊，其余为群聊
+ALTER TABLE `sessions`
+  ADD COLUMN `kind` TINYINT NOT NULL DEFAULT '1' COMMENT '会话类型: 0-单聊, 1-群聊, 2-频道',
+  ADD KEY `idx_oid_kind` (`oid`, `kind`);
+
+UPDATE `sessions` s
+JOIN (
+  SELECT `sid`, MIN(`uid`) AS `a`, MAX(`uid`) AS `b`, COUNT(1) AS `c` FROM `user_sessions` GROUP BY `sid`
+) m ON m.`sid` = s.`id`
+SET s.`kind` = 0
+WHERE m.`c` = 2 AND s.`id` = ((m.`a` << 32) | (m.`b` & 0xFFFFFFFF));
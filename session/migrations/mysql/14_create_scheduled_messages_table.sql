@@ -0,0 +1,17 @@
+-- 定时消息表，调度任务认领到期的消息后通过消息交换机发布
+CREATE TABLE `scheduled_messages` (
+  `id` BIGINT NOT NULL COMMENT '定时消息ID，发布时作为消息ID，重复发布时消息只会保存一次',
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `sender` BIGINT NOT NULL COMMENT '发送者ID',
+  `uname` VARCHAR(255) NOT NULL COMMENT '发送者名称',
+  `content` TEXT NOT NULL COMMENT '消息内容',
+  `send_at` BIGINT NOT NULL COMMENT '计划发送时间戳(毫秒)',
+  `status` TINYINT NOT NULL DEFAULT '0' COMMENT '状态: 0-待发送, 1-发送中, 2-已发送, 3-已取消, 4-发送失败',
+  `claimed_by` BIGINT NULL DEFAULT NULL COMMENT '认领该消息的调度批次',
+  `claimed_at` BIGINT NULL DEFAULT NULL COMMENT '认领时间戳(毫秒)，超时未完成的认领会被释放',
+  `createtime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updatetime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  KEY `idx_status_send_at` (`status`, `send_at`),
+  KEY `idx_sid_sender` (`sid`, `sender`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='定时消息表';
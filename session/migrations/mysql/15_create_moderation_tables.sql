@@ -0,0 +1,31 @@
+-- 组织内容审核规则表，规则变更后同步给连接服务
+CREATE TABLE `moderation_rules` (
+  `id` BIGINT NOT NULL COMMENT '规则ID',
+  `oid` BIGINT NOT NULL COMMENT '组织ID',
+  `kind` TINYINT NOT NULL COMMENT '规则类型: 0-违禁词, 1-正则表达式, 2-链接, 3-消息长度',
+  `pattern` VARCHAR(255) NOT NULL DEFAULT '' COMMENT '违禁词、正则表达式或禁止的链接域名',
+  `max_len` INT UNSIGNED NOT NULL DEFAULT '0' COMMENT '最大消息长度(字符数)',
+  `action` TINYINT NOT NULL COMMENT '处理方式: 0-拒绝发送, 1-屏蔽违规内容, 2-提交人工审核',
+  `creator` BIGINT NOT NULL COMMENT '创建者ID',
+  `createtime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  KEY `idx_oid` (`oid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='内容审核规则表';
+
+-- 待人工审核的消息
+CREATE TABLE `moderation_reviews` (
+  `mid` BIGINT NOT NULL COMMENT '消息ID',
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `oid` BIGINT NOT NULL COMMENT '会话所属组织ID',
+  `sender` BIGINT NOT NULL COMMENT '发送者ID',
+  `uname` VARCHAR(255) NOT NULL COMMENT '发送者名称',
+  `content` TEXT NOT NULL COMMENT '消息内容',
+  `reason` VARCHAR(255) NOT NULL COMMENT '命中的规则说明',
+  `status` TINYINT NOT NULL DEFAULT '0' COMMENT '状态: 0-待审核, 1-已通过, 2-已删除',
+  `reviewer` BIGINT NULL DEFAULT NULL COMMENT '审核人ID',
+  `timestamp` BIGINT NOT NULL COMMENT '消息发送时间戳(毫秒)',
+  `createtime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  `updatetime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`mid`),
+  KEY `idx_oid_status` (`oid`, `status`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='待审核消息表';
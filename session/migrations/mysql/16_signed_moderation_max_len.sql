@@ -0,0 +1,3 @@
+-- 最大消息长度改为有符号整数，与SQLite、Postgres的表结构保持一致
+ALTER TABLE `moderation_rules`
+  MODIFY COLUMN `max_len` BIGINT NOT NULL DEFAULT '0' COMMENT '最大消息长度(字符数)';
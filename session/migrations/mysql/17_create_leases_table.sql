@@ -0,0 +1,7 @@
+-- 后台任务的租约表，多实例部署时同一时间只有持有租约的实例执行任务
+CREATE TABLE `leases` (
+  `name` VARCHAR(64) NOT NULL COMMENT '任务名称',
+  `holder` BIGINT NOT NULL DEFAULT '0' COMMENT '持有租约的实例',
+  `expires_at` BIGINT NOT NULL DEFAULT '0' COMMENT '租约到期时间戳(毫秒)，到期后其他实例可以获取',
+  PRIMARY KEY (`name`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='后台任务租约表';
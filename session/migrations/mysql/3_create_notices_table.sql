@@ -0,0 +1,16 @@
+-- 系统公告表
+CREATE TABLE `notices` (
+  `id` BIGINT NOT NULL,
+  `severity` TINYINT NOT NULL DEFAULT '0' COMMENT '公告级别: 0-INFO, 1-WARN, 2-CRITICAL',
+  `scope` TINYINT NOT NULL DEFAULT '0' COMMENT '公告范围: 0-所有用户, 1-组织, 2-会话',
+  `target` BIGINT NOT NULL DEFAULT '0' COMMENT '组织ID或会话ID',
+  `text` TEXT NOT NULL COMMENT '公告内容',
+  `exp` BIGINT DEFAULT NULL COMMENT '过期时间戳(毫秒)，为空表示不过期',
+  `sender` BIGINT NOT NULL COMMENT '发布者ID',
+  `uname` VARCHAR(100) NOT NULL DEFAULT '' COMMENT '发布者昵称',
+  `timestamp` BIGINT NOT NULL COMMENT '发布时间戳',
+  `createtime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  KEY `idx_scope_target` (`scope`, `target`),
+  KEY `idx_timestamp` (`timestamp`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='系统公告表';
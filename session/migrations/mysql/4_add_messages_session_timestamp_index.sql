@@ -0,0 +1,4 @@
+-- 会话消息按(session, timestamp, id)游标分页
+ALTER TABLE `messages`
+  ADD KEY `idx_session_timestamp` (`session`, `timestamp`, `id`),
+  DROP KEY `idx_session`;
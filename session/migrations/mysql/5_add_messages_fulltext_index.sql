@@ -0,0 +1,3 @@
+-- 消息内容全文索引，使用ngram解析器支持中日韩文本
+ALTER TABLE `messages`
+  ADD FULLTEXT KEY `ft_content` (`content`) WITH PARSER ngram;
@@ -0,0 +1,9 @@
+-- 用户会话已读位置表
+CREATE TABLE `read_cursors` (
+  `uid` BIGINT NOT NULL COMMENT '用户ID',
+  `sid` BIGINT NOT NULL COMMENT '会话ID',
+  `last_read_message_id` BIGINT NOT NULL DEFAULT '0' COMMENT '已读到的消息ID',
+  `last_read_timestamp` BIGINT NOT NULL DEFAULT '0' COMMENT '已读到的消息时间戳',
+  `updatetime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP ON UPDATE CURRENT_TIMESTAMP,
+  PRIMARY KEY (`uid`, `sid`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='用户会话已读位置表';
@@ -0,0 +1,15 @@
+-- 会话最后一条消息，用于会话列表排序及预览
+ALTER TABLE `sessions`
+  ADD COLUMN `last_message_id` BIGINT NULL COMMENT '最后一条消息ID',
+  ADD COLUMN `last_message_at` BIGINT NULL COMMENT '最后一条消息时间戳',
+  ADD KEY `idx_last_message_at` (`last_message_at`);
+
+-- 回填已有会话的最后一条消息
+UPDATE `sessions` s
+JOIN (
+  SELECT m.session, MAX(m.timestamp) AS ts FROM `messages` m GROUP BY m.session
+) t ON t.session = s.id
+SET s.last_message_at = t.ts,
+    s.last_message_id = (
+      SELECT MAX(m.id) FROM `messages` m WHERE m.session = s.id AND m.timestamp = t.ts
+    );
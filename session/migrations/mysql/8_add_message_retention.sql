@@ -0,0 +1,19 @@
+-- 会话所属组织及消息保留天数，NULL表示沿用组织或全局配置，0表示永久保留
+ALTER TABLE `sessions`
+  ADD COLUMN `oid` BIGINT NOT NULL DEFAULT '0' COMMENT '所属组织ID',
+  ADD COLUMN `retention_days` INT NULL COMMENT '消息保留天数';
+
+-- 过期消息归档表
+CREATE TABLE `messages_archive` (
+  `id` BIGINT NOT NULL,
+  `sender` BIGINT NOT NULL COMMENT '发送者ID',
+  `session` BIGINT NOT NULL COMMENT '聊天室ID',
+  `mtype` TINYINT NOT NULL DEFAULT '0' COMMENT '消息类型',
+  `content` TEXT NOT NULL COMMENT '消息内容',
+  `timestamp` BIGINT NOT NULL COMMENT '消息时间戳',
+  `uname` VARCHAR(45) NOT NULL DEFAULT '' COMMENT '发送者昵称',
+  `createtime` DATETIME NOT NULL,
+  `archivetime` DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (`id`),
+  KEY `idx_session_timestamp` (`session`, `timestamp`)
+) ENGINE=InnoDB DEFAULT CHARSET=utf8mb4 COLLATE=utf8mb4_unicode_ci COMMENT='聊天消息归档表';
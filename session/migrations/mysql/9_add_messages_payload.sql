@@ -0,0 +1,13 @@
+-- 结构化消息负载，JSON格式，version为负载格式版本，0表示只有content文本的旧数据
+ALTER TABLE `messages`
+  ADD COLUMN `payload` TEXT NULL COMMENT '结构化消息内容',
+  ADD COLUMN `version` TINYINT NOT NULL DEFAULT '0' COMMENT '消息内容格式版本';
+
+-- 旧的文本消息直接转换，文件消息的ID为base62编码，由会话服务启动时转换
+UPDATE `messages`
+SET `payload` = JSON_OBJECT('text', JSON_OBJECT('text', `content`)), `version` = 1
+WHERE `content` NOT LIKE '[File] %';
+
+ALTER TABLE `messages_archive`
+  ADD COLUMN `payload` TEXT NULL COMMENT '结构化消息内容',
+  ADD COLUMN `version` TINYINT NOT NULL DEFAULT '0' COMMENT '消息内容格式版本';
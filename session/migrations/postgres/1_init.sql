@@ -0,0 +1,159 @@
+-- 会话服务Postgres表结构，对应MySQL迁移1-15
+CREATE TABLE messages (
+  id BIGINT NOT NULL PRIMARY KEY,
+  sender BIGINT NOT NULL,
+  session BIGINT NOT NULL,
+  mtype INTEGER NOT NULL DEFAULT 0,
+  content TEXT NOT NULL,
+  timestamp BIGINT NOT NULL,
+  uname VARCHAR(45) NOT NULL DEFAULT '',
+  payload TEXT NULL,
+  version INTEGER NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_messages_sender ON messages (sender);
+CREATE INDEX idx_messages_timestamp ON messages (timestamp);
+CREATE INDEX idx_messages_session_timestamp ON messages (session, timestamp, id);
+
+CREATE TABLE messages_archive (
+  id BIGINT NOT NULL PRIMARY KEY,
+  sender BIGINT NOT NULL,
+  session BIGINT NOT NULL,
+  mtype INTEGER NOT NULL DEFAULT 0,
+  content TEXT NOT NULL,
+  timestamp BIGINT NOT NULL,
+  uname VARCHAR(45) NOT NULL DEFAULT '',
+  payload TEXT NULL,
+  version INTEGER NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL,
+  archivetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_messages_archive_session_timestamp ON messages_archive (session, timestamp);
+
+CREATE TABLE sessions (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(100) NOT NULL,
+  creator BIGINT NOT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_message_id BIGINT NULL,
+  last_message_at BIGINT NULL,
+  oid BIGINT NOT NULL DEFAULT 0,
+  retention_days INTEGER NULL,
+  kind INTEGER NOT NULL DEFAULT 1
+);
+CREATE INDEX idx_sessions_creator ON sessions (creator);
+CREATE INDEX idx_sessions_last_message_at ON sessions (last_message_at);
+CREATE INDEX idx_sessions_oid_kind ON sessions (oid, kind);
+
+CREATE TABLE user_sessions (
+  id BIGINT NOT NULL PRIMARY KEY,
+  uid BIGINT NOT NULL,
+  uname VARCHAR(100) NOT NULL,
+  sid BIGINT NOT NULL,
+  role INTEGER NOT NULL DEFAULT 0,
+  jointime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (uid, sid)
+);
+CREATE INDEX idx_user_sessions_sid ON user_sessions (sid);
+
+CREATE TABLE notices (
+  id BIGINT NOT NULL PRIMARY KEY,
+  severity INTEGER NOT NULL DEFAULT 0,
+  scope INTEGER NOT NULL DEFAULT 0,
+  target BIGINT NOT NULL DEFAULT 0,
+  text TEXT NOT NULL,
+  exp BIGINT DEFAULT NULL,
+  sender BIGINT NOT NULL,
+  uname VARCHAR(100) NOT NULL DEFAULT '',
+  timestamp BIGINT NOT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_notices_scope_target ON notices (scope, target);
+CREATE INDEX idx_notices_timestamp ON notices (timestamp);
+
+CREATE TABLE read_cursors (
+  uid BIGINT NOT NULL,
+  sid BIGINT NOT NULL,
+  last_read_message_id BIGINT NOT NULL DEFAULT 0,
+  last_read_timestamp BIGINT NOT NULL DEFAULT 0,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, sid)
+);
+
+CREATE TABLE pinned_messages (
+  sid BIGINT NOT NULL,
+  mid BIGINT NOT NULL,
+  uid BIGINT NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  pintime BIGINT NOT NULL,
+  PRIMARY KEY (sid, mid)
+);
+CREATE INDEX idx_pinned_messages_sid_pintime ON pinned_messages (sid, pintime);
+
+CREATE TABLE session_settings (
+  uid BIGINT NOT NULL,
+  sid BIGINT NOT NULL,
+  notify INTEGER NOT NULL DEFAULT 0,
+  muted_until BIGINT NULL DEFAULT NULL,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, sid)
+);
+
+CREATE TABLE invites (
+  id BIGINT NOT NULL PRIMARY KEY,
+  sid BIGINT NOT NULL,
+  token VARCHAR(512) NOT NULL,
+  creator BIGINT NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  max_uses INTEGER NULL DEFAULT NULL,
+  uses INTEGER NOT NULL DEFAULT 0,
+  exp BIGINT NOT NULL,
+  revoked BOOLEAN NOT NULL DEFAULT FALSE,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_invites_sid ON invites (sid);
+
+CREATE TABLE scheduled_messages (
+  id BIGINT NOT NULL PRIMARY KEY,
+  sid BIGINT NOT NULL,
+  sender BIGINT NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  content TEXT NOT NULL,
+  send_at BIGINT NOT NULL,
+  status INTEGER NOT NULL DEFAULT 0,
+  claimed_by BIGINT NULL DEFAULT NULL,
+  claimed_at BIGINT NULL DEFAULT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_scheduled_messages_status_send_at ON scheduled_messages (status, send_at);
+CREATE INDEX idx_scheduled_messages_sid_sender ON scheduled_messages (sid, sender);
+
+CREATE TABLE moderation_rules (
+  id BIGINT NOT NULL PRIMARY KEY,
+  oid BIGINT NOT NULL,
+  kind INTEGER NOT NULL,
+  pattern VARCHAR(255) NOT NULL DEFAULT '',
+  max_len BIGINT NOT NULL DEFAULT 0,
+  action INTEGER NOT NULL,
+  creator BIGINT NOT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_moderation_rules_oid ON moderation_rules (oid);
+
+CREATE TABLE moderation_reviews (
+  mid BIGINT NOT NULL PRIMARY KEY,
+  sid BIGINT NOT NULL,
+  oid BIGINT NOT NULL,
+  sender BIGINT NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  content TEXT NOT NULL,
+  reason VARCHAR(255) NOT NULL,
+  status INTEGER NOT NULL DEFAULT 0,
+  reviewer BIGINT NULL DEFAULT NULL,
+  timestamp BIGINT NOT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_moderation_reviews_oid_status ON moderation_reviews (oid, status);
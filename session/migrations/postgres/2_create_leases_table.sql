@@ -0,0 +1,5 @@
+CREATE TABLE leases (
+  name VARCHAR(64) NOT NULL PRIMARY KEY,
+  holder BIGINT NOT NULL DEFAULT 0,
+  expires_at BIGINT NOT NULL DEFAULT 0
+);
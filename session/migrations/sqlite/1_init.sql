@@ -0,0 +1,159 @@
+-- 会话服务SQLite表结构，对应MySQL迁移1-15
+CREATE TABLE messages (
+  id INTEGER NOT NULL PRIMARY KEY,
+  sender INTEGER NOT NULL,
+  session INTEGER NOT NULL,
+  mtype INTEGER NOT NULL DEFAULT 0,
+  content TEXT NOT NULL,
+  timestamp INTEGER NOT NULL,
+  uname VARCHAR(45) NOT NULL DEFAULT '',
+  payload TEXT NULL,
+  version INTEGER NOT NULL DEFAULT 0,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_messages_sender ON messages (sender);
+CREATE INDEX idx_messages_timestamp ON messages (timestamp);
+CREATE INDEX idx_messages_session_timestamp ON messages (session, timestamp, id);
+
+CREATE TABLE messages_archive (
+  id INTEGER NOT NULL PRIMARY KEY,
+  sender INTEGER NOT NULL,
+  session INTEGER NOT NULL,
+  mtype INTEGER NOT NULL DEFAULT 0,
+  content TEXT NOT NULL,
+  timestamp INTEGER NOT NULL,
+  uname VARCHAR(45) NOT NULL DEFAULT '',
+  payload TEXT NULL,
+  version INTEGER NOT NULL DEFAULT 0,
+  createtime DATETIME NOT NULL,
+  archivetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_messages_archive_session_timestamp ON messages_archive (session, timestamp);
+
+CREATE TABLE sessions (
+  id INTEGER NOT NULL PRIMARY KEY,
+  name VARCHAR(100) NOT NULL,
+  creator INTEGER NOT NULL,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  last_message_id INTEGER NULL,
+  last_message_at INTEGER NULL,
+  oid INTEGER NOT NULL DEFAULT 0,
+  retention_days INTEGER NULL,
+  kind INTEGER NOT NULL DEFAULT 1
+);
+CREATE INDEX idx_sessions_creator ON sessions (creator);
+CREATE INDEX idx_sessions_last_message_at ON sessions (last_message_at);
+CREATE INDEX idx_sessions_oid_kind ON sessions (oid, kind);
+
+CREATE TABLE user_sessions (
+  id INTEGER NOT NULL PRIMARY KEY,
+  uid INTEGER NOT NULL,
+  uname VARCHAR(100) NOT NULL,
+  sid INTEGER NOT NULL,
+  role INTEGER NOT NULL DEFAULT 0,
+  jointime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  UNIQUE (uid, sid)
+);
+CREATE INDEX idx_user_sessions_sid ON user_sessions (sid);
+
+CREATE TABLE notices (
+  id INTEGER NOT NULL PRIMARY KEY,
+  severity INTEGER NOT NULL DEFAULT 0,
+  scope INTEGER NOT NULL DEFAULT 0,
+  target INTEGER NOT NULL DEFAULT 0,
+  text TEXT NOT NULL,
+  exp INTEGER DEFAULT NULL,
+  sender INTEGER NOT NULL,
+  uname VARCHAR(100) NOT NULL DEFAULT '',
+  timestamp INTEGER NOT NULL,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_notices_scope_target ON notices (scope, target);
+CREATE INDEX idx_notices_timestamp ON notices (timestamp);
+
+CREATE TABLE read_cursors (
+  uid INTEGER NOT NULL,
+  sid INTEGER NOT NULL,
+  last_read_message_id INTEGER NOT NULL DEFAULT 0,
+  last_read_timestamp INTEGER NOT NULL DEFAULT 0,
+  updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, sid)
+);
+
+CREATE TABLE pinned_messages (
+  sid INTEGER NOT NULL,
+  mid INTEGER NOT NULL,
+  uid INTEGER NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  pintime INTEGER NOT NULL,
+  PRIMARY KEY (sid, mid)
+);
+CREATE INDEX idx_pinned_messages_sid_pintime ON pinned_messages (sid, pintime);
+
+CREATE TABLE session_settings (
+  uid INTEGER NOT NULL,
+  sid INTEGER NOT NULL,
+  notify INTEGER NOT NULL DEFAULT 0,
+  muted_until INTEGER NULL DEFAULT NULL,
+  updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  PRIMARY KEY (uid, sid)
+);
+
+CREATE TABLE invites (
+  id INTEGER NOT NULL PRIMARY KEY,
+  sid INTEGER NOT NULL,
+  token VARCHAR(512) NOT NULL,
+  creator INTEGER NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  max_uses INTEGER NULL DEFAULT NULL,
+  uses INTEGER NOT NULL DEFAULT 0,
+  exp INTEGER NOT NULL,
+  revoked BOOLEAN NOT NULL DEFAULT FALSE,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_invites_sid ON invites (sid);
+
+CREATE TABLE scheduled_messages (
+  id INTEGER NOT NULL PRIMARY KEY,
+  sid INTEGER NOT NULL,
+  sender INTEGER NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  content TEXT NOT NULL,
+  send_at INTEGER NOT NULL,
+  status INTEGER NOT NULL DEFAULT 0,
+  claimed_by INTEGER NULL DEFAULT NULL,
+  claimed_at INTEGER NULL DEFAULT NULL,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_scheduled_messages_status_send_at ON scheduled_messages (status, send_at);
+CREATE INDEX idx_scheduled_messages_sid_sender ON scheduled_messages (sid, sender);
+
+CREATE TABLE moderation_rules (
+  id INTEGER NOT NULL PRIMARY KEY,
+  oid INTEGER NOT NULL,
+  kind INTEGER NOT NULL,
+  pattern VARCHAR(255) NOT NULL DEFAULT '',
+  max_len INTEGER NOT NULL DEFAULT 0,
+  action INTEGER NOT NULL,
+  creator INTEGER NOT NULL,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_moderation_rules_oid ON moderation_rules (oid);
+
+CREATE TABLE moderation_reviews (
+  mid INTEGER NOT NULL PRIMARY KEY,
+  sid INTEGER NOT NULL,
+  oid INTEGER NOT NULL,
+  sender INTEGER NOT NULL,
+  uname VARCHAR(255) NOT NULL,
+  content TEXT NOT NULL,
+  reason VARCHAR(255) NOT NULL,
+  status INTEGER NOT NULL DEFAULT 0,
+  reviewer INTEGER NULL DEFAULT NULL,
+  timestamp INTEGER NOT NULL,
+  createtime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime DATETIME NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+CREATE INDEX idx_moderation_reviews_oid_status ON moderation_reviews (oid, status);
@@ -0,0 +1,5 @@
+CREATE TABLE leases (
+  name VARCHAR(64) NOT NULL PRIMARY KEY,
+  holder INTEGER NOT NULL DEFAULT 0,
+  expires_at INTEGER NOT NULL DEFAULT 0
+);
@@ -0,0 +1,16 @@
+use std::sync::OnceLock;
+
+use core::config::SessionConfig;
+
+pub static CONFIG: OnceLock<SessionConfig> = OnceLock::new();
+
+//加载并校验配置，配置不合法时直接退出
+pub fn init() -> &'static SessionConfig {
+    let config = core::config::load_or_exit::<SessionConfig>("session");
+    core::config::print("session", &config);
+    CONFIG.get_or_init(|| config)
+}
+
+pub fn get() -> &'static SessionConfig {
+    CONFIG.get().expect("配置未初始化")
+}
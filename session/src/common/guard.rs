@@ -0,0 +1,54 @@
+use std::{future::Future, pin::Pin, sync::OnceLock};
+
+use anyhow::Result;
+use axum::extract::{FromRequestParts, RawPathParams};
+use axum::http::request::Parts;
+use core::auth::User;
+use core::response::ApiErr;
+use crate::repository::membership;
+
+//成员关系查询函数，参数为(会话id, 用户id)
+pub type Checker = fn(i64, i64) -> Pin<Box<dyn Future<Output = Result<bool>> + Send>>;
+
+static CHECKER: OnceLock<Checker> = OnceLock::new();
+
+//测试中替换成员关系查询函数，须在第一次请求前设置
+#[cfg(test)]
+pub(crate) fn init(checker: Checker) -> Checker {
+    *CHECKER.get_or_init(|| checker)
+}
+
+//成员关系查询函数，默认使用Redis缓存+数据库查询
+fn checker() -> Checker {
+    *CHECKER.get_or_init(|| |sid, uid| Box::pin(membership::is_member(sid, uid)))
+}
+
+//会话成员守卫，从路径参数`id`或`session`中取会话id，当前用户不是该会话成员时返回403
+#[derive(Debug, Clone)]
+pub struct Member {
+    pub sid: i64,
+    pub claims: User,
+}
+
+impl<S> FromRequestParts<S> for Member
+where
+    S: Send + Sync,
+{
+    type Rejection = ApiErr;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let claims = parts.extensions.get::<User>()
+            .cloned()
+            .ok_or(ApiErr::Bad(401, "未登录".to_string()))?;
+        let params = RawPathParams::from_request_parts(parts, state).await
+            .map_err(|_| ApiErr::Bad(400, "缺少会话ID".to_string()))?;
+        let sid = params.iter()
+            .find(|(k, _)| *k == "id" || *k == "session")
+            .and_then(|(_, v)| v.parse::<i64>().ok())
+            .ok_or(ApiErr::Bad(400, "会话ID不合法".to_string()))?;
+        if !checker()(sid, claims.id).await? {
+            return Err(ApiErr::Bad(403, "不是该会话的成员".to_string()));
+        }
+        Ok(Self { sid, claims })
+    }
+}
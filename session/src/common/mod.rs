@@ -1 +1,3 @@
-pub mod router;
\ No newline at end of file
+pub mod router;
+pub mod config;
+pub mod guard;
\ No newline at end of file
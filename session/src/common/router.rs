@@ -5,12 +5,32 @@ use axum::{error_handling::HandleErrorLayer, routing::any, Router};
 use core::response::{e404, e500};
 use crate::controller::message;
 use crate::controller::session;
+use crate::controller::notice;
+use crate::controller::member;
+use crate::controller::retention;
+use crate::controller::admin;
+use crate::controller::pin;
+use crate::controller::setting;
+use crate::controller::invite;
+use crate::controller::channel;
+use crate::controller::scheduled;
+use crate::controller::moderation;
 
 pub fn init() -> Result<Router> {
     let app = Router::new();
     Ok(app.route("/index", axum::routing::get(crate::controller::message::index))
         .merge(message::router())
         .merge(session::router())
+        .merge(notice::router())
+        .merge(member::router())
+        .merge(retention::router())
+        .merge(admin::router())
+        .merge(pin::router())
+        .merge(setting::router())
+        .merge(invite::router())
+        .merge(channel::router())
+        .merge(scheduled::router())
+        .merge(moderation::router())
         .layer(ValidateRequestHeaderLayer::custom(core::auth::AuthHeader{}))
         .fallback(any(e404))
         .layer(CorsLayer::new().allow_methods(Any).allow_origin(Any))
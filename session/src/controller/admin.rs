@@ -0,0 +1,44 @@
+use core::api::types::message::{DeadLetter, DeadLetterRequest};
+use core::api::types::session::SessionState;
+use core::auth::User;
+use core::extract::{Path, Query};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{get, post};
+use axum::Router;
+use crate::service::{admin, member};
+
+// 查看死信队列中的消息
+pub async fn get_dead_letters(
+    Extension(claims): Extension<User>,
+    Query(payload): Query<DeadLetterRequest>,
+) -> Result<ApiResponse<DeadLetter>, ApiErr> {
+    let letters = admin::get_dead_letters(&claims, payload).await?;
+    let count = letters.len() as i64;
+    Ok(ApiResponse::List(letters, count))
+}
+
+// 将死信重新投递到原队列，返回重放的消息数
+pub async fn replay_dead_letters(
+    Extension(claims): Extension<User>,
+    Query(payload): Query<DeadLetterRequest>,
+) -> Result<ApiResponse<u32>, ApiErr> {
+    Ok(ApiResponse::One(admin::replay_dead_letters(&claims, payload).await?))
+}
+
+// 连接服务缓存未命中时加载会话的成员及审核规则
+pub async fn get_session_state(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<SessionState>, ApiErr> {
+    Ok(ApiResponse::One(member::state(&claims, id).await?))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/admin/dead-letters", get(get_dead_letters))
+        .route("/admin/dead-letters/replay", post(replay_dead_letters))
+        .route("/admin/sessions/{id}/state", get(get_session_state))
+}
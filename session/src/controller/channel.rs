@@ -0,0 +1,42 @@
+use core::api::types::session::{ChannelResponse, SessionResponse};
+use core::auth::User;
+use core::extract::Path;
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{get, put};
+use axum::Router;
+use crate::service::channel;
+
+// 获取所在组织的频道
+pub async fn get_channels(
+    Extension(claims): Extension<User>,
+) -> Result<ApiResponse<ChannelResponse>, ApiErr> {
+    let channels = channel::get_channels(&claims).await?;
+    let count = channels.len() as i64;
+    Ok(ApiResponse::List(channels, count))
+}
+
+// 订阅频道
+pub async fn subscribe(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<SessionResponse>, ApiErr> {
+    Ok(ApiResponse::One(channel::subscribe(&claims, id).await?))
+}
+
+// 退订频道
+pub async fn unsubscribe(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    channel::unsubscribe(&claims, id).await?;
+    Ok(ApiResponse::One(true))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/channels", get(get_channels))
+        .route("/channels/{id}/subscription", put(subscribe).delete(unsubscribe))
+}
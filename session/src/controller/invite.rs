@@ -0,0 +1,53 @@
+use core::api::types::session::{CreateInviteRequest, InviteResponse, SessionResponse};
+use core::auth::User;
+use core::extract::{Json, Path};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{delete, get, post};
+use axum::Router;
+use crate::service::invite;
+
+// 创建会话邀请链接，仅管理员可操作
+pub async fn create_invite(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+    Json(payload): Json<CreateInviteRequest>,
+) -> Result<ApiResponse<InviteResponse>, ApiErr> {
+    Ok(ApiResponse::One(invite::create_invite(&claims, id, payload).await?))
+}
+
+// 获取会话的全部邀请链接，包括已过期和已撤销的
+pub async fn get_invites(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<InviteResponse>, ApiErr> {
+    let invites = invite::get_invites(&claims, id).await?;
+    let count = invites.len() as i64;
+    Ok(ApiResponse::List(invites, count))
+}
+
+// 撤销邀请链接
+pub async fn revoke(
+    Extension(claims): Extension<User>,
+    Path((id, iid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    invite::revoke(&claims, id, iid).await?;
+    Ok(ApiResponse::One(true))
+}
+
+// 通过邀请令牌加入会话
+pub async fn join(
+    Extension(claims): Extension<User>,
+    Path(token): Path<String>,
+) -> Result<ApiResponse<SessionResponse>, ApiErr> {
+    Ok(ApiResponse::One(invite::join(&claims, &token).await?))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/invites", get(get_invites).post(create_invite))
+        .route("/sessions/{id}/invites/{iid}", delete(revoke))
+        .route("/invites/{token}/join", post(join))
+}
@@ -0,0 +1,92 @@
+use core::api::types::session::{AddMembersRequest, MemberResponse, RenameSessionRequest, SessionResponse};
+use core::auth::User;
+use core::extract::{Json, Path};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{delete, get, post, put};
+use axum::Router;
+use crate::service::member;
+
+pub async fn get_members(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::get_members(&claims, id).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub async fn add_members(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+    Json(payload): Json<AddMembersRequest>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::add_members(&claims, id, payload.members).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub async fn remove_member(
+    Extension(claims): Extension<User>,
+    Path((id, uid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::remove_member(&claims, id, uid).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub async fn leave(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    member::leave(&claims, id).await?;
+    Ok(ApiResponse::One(true))
+}
+
+pub async fn rename(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+    Json(payload): Json<RenameSessionRequest>,
+) -> Result<ApiResponse<SessionResponse>, ApiErr> {
+    let session = member::rename(&claims, id, payload.name).await?;
+    Ok(ApiResponse::One(session))
+}
+
+pub async fn promote(
+    Extension(claims): Extension<User>,
+    Path((id, uid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::set_admin(&claims, id, uid, true).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub async fn demote(
+    Extension(claims): Extension<User>,
+    Path((id, uid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::set_admin(&claims, id, uid, false).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub async fn transfer(
+    Extension(claims): Extension<User>,
+    Path((id, uid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<MemberResponse>, ApiErr> {
+    let members = member::transfer(&claims, id, uid).await?;
+    let count = members.len() as i64;
+    Ok(ApiResponse::List(members, count))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/members", get(get_members).post(add_members))
+        .route("/sessions/{id}/members/{uid}", delete(remove_member))
+        .route("/sessions/{id}/leave", post(leave))
+        .route("/sessions/{id}/name", put(rename))
+        .route("/sessions/{id}/admins/{uid}", put(promote).delete(demote))
+        .route("/sessions/{id}/owner/{uid}", put(transfer))
+}
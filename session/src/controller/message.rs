@@ -1,21 +1,32 @@
-use core::request::Page;
+use core::request::Cursor;
 use core::response::ApiResponse;
 use core::response::ApiErr;
 
 use anyhow::Result;
 use axum::routing::get;
 use axum::Router;
-use core::extract::Path;
 use core::extract::Query;
+use crate::common::guard::Member;
 use crate::service::message;
-use core::api::types::message::Message;
+use core::api::types::message::{Message, MessageHit, SearchMessageRequest};
+use core::auth::User;
+use axum::Extension;
 
 pub async fn get_messages_by_session(
-    Path(session): Path<i64>,
-    Query(params): Query<Page>,
+    member: Member,
+    Query(cursor): Query<Cursor>,
 ) -> Result<ApiResponse<Message>, ApiErr>{
-    let limit = params.ps;
-    Ok(ApiResponse::List(message::get_messages_by_session(session, limit).await?, 0))
+    let (messages, cursors) = message::get_messages_by_session(member.sid, cursor).await?;
+    Ok(ApiResponse::Cursor(messages, cursors))
+}
+
+//在当前用户所在的会话中全文搜索消息
+pub async fn search_messages(
+    Extension(claims): Extension<User>,
+    Query(request): Query<SearchMessageRequest>,
+) -> Result<ApiResponse<MessageHit>, ApiErr>{
+    let (hits, count) = message::search_messages(claims.id, request).await?;
+    Ok(ApiResponse::List(hits, count))
 }
 
 pub async fn index() -> Result<ApiResponse<Message>, ApiErr> {
@@ -25,4 +36,5 @@ pub async fn index() -> Result<ApiResponse<Message>, ApiErr> {
 pub fn router() -> Router {
     Router::new()
         .route("/{session}/messages", get(get_messages_by_session))
+        .route("/search/messages", get(search_messages))
 }
@@ -1,2 +1,13 @@
 pub mod message;
-pub mod session;
\ No newline at end of file
+pub mod session;
+pub mod notice;
+pub mod member;
+pub mod retention;
+pub mod admin;
+
+pub mod pin;
+pub mod setting;
+pub mod invite;
+pub mod channel;
+pub mod scheduled;
+pub mod moderation;
\ No newline at end of file
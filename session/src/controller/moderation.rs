@@ -0,0 +1,74 @@
+use core::api::types::moderation::{ReviewRequest, ReviewResponse, RuleQuery, RuleRequest, RuleResponse};
+use core::auth::User;
+use core::extract::{Json, Path, Query};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{delete, get, post};
+use axum::Router;
+use crate::service::moderation;
+
+// 查看组织的审核规则
+pub async fn get_rules(
+    Extension(claims): Extension<User>,
+    Query(query): Query<RuleQuery>,
+) -> Result<ApiResponse<RuleResponse>, ApiErr> {
+    let rules = moderation::get_rules(&claims, query.oid).await?;
+    let count = rules.len() as i64;
+    Ok(ApiResponse::List(rules, count))
+}
+
+// 添加审核规则
+pub async fn add_rule(
+    Extension(claims): Extension<User>,
+    Json(payload): Json<RuleRequest>,
+) -> Result<ApiResponse<RuleResponse>, ApiErr> {
+    Ok(ApiResponse::One(moderation::add_rule(&claims, payload).await?))
+}
+
+// 删除审核规则
+pub async fn delete_rule(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    moderation::delete_rule(&claims, id).await?;
+    Ok(ApiResponse::One(true))
+}
+
+// 查看组织中待审核或已审核的消息
+pub async fn get_reviews(
+    Extension(claims): Extension<User>,
+    Query(payload): Query<ReviewRequest>,
+) -> Result<ApiResponse<ReviewResponse>, ApiErr> {
+    let reviews = moderation::get_reviews(&claims, payload).await?;
+    let count = reviews.len() as i64;
+    Ok(ApiResponse::List(reviews, count))
+}
+
+// 审核通过
+pub async fn approve(
+    Extension(claims): Extension<User>,
+    Path(mid): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    moderation::approve(&claims, mid).await?;
+    Ok(ApiResponse::One(true))
+}
+
+// 删除违规消息
+pub async fn remove(
+    Extension(claims): Extension<User>,
+    Path(mid): Path<i64>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    moderation::remove(&claims, mid).await?;
+    Ok(ApiResponse::One(true))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/admin/moderation/rules", get(get_rules).post(add_rule))
+        .route("/admin/moderation/rules/{id}", delete(delete_rule))
+        .route("/admin/moderation/reviews", get(get_reviews))
+        .route("/admin/moderation/reviews/{mid}/approve", post(approve))
+        .route("/admin/moderation/reviews/{mid}/remove", post(remove))
+}
@@ -0,0 +1,33 @@
+use core::api::types::notice::{CreateNoticeRequest, NoticeResponse};
+use core::auth::User;
+use core::extract::Json;
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::get;
+use axum::Router;
+use crate::service::notice;
+
+//发布系统公告
+pub async fn publish_notice(
+    Extension(claims): Extension<User>,
+    Json(payload): Json<CreateNoticeRequest>,
+) -> Result<ApiResponse<NoticeResponse>, ApiErr> {
+    let notice = notice::publish(&claims, payload).await?;
+    Ok(ApiResponse::One(notice))
+}
+
+//获取当前用户可见的有效公告
+pub async fn get_active_notices(
+    Extension(claims): Extension<User>,
+) -> Result<ApiResponse<NoticeResponse>, ApiErr> {
+    let notices = notice::get_active_notices(&claims).await?;
+    let count = notices.len() as i64;
+    Ok(ApiResponse::List(notices, count))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/notices", get(get_active_notices).post(publish_notice))
+}
@@ -0,0 +1,46 @@
+use core::api::types::message::PinResponse;
+use core::auth::User;
+use core::extract::Path;
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{get, put};
+use axum::Router;
+use crate::common::guard::Member;
+use crate::service::pin;
+
+// 获取会话的置顶消息
+pub async fn get_pins(
+    member: Member,
+) -> Result<ApiResponse<PinResponse>, ApiErr> {
+    let pins = pin::get_pins(member.sid).await?;
+    let count = pins.len() as i64;
+    Ok(ApiResponse::List(pins, count))
+}
+
+// 置顶消息，仅管理员可操作
+pub async fn pin(
+    Extension(claims): Extension<User>,
+    Path((id, mid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<PinResponse>, ApiErr> {
+    let pins = pin::pin(&claims, id, mid).await?;
+    let count = pins.len() as i64;
+    Ok(ApiResponse::List(pins, count))
+}
+
+// 取消置顶，仅管理员可操作
+pub async fn unpin(
+    Extension(claims): Extension<User>,
+    Path((id, mid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<PinResponse>, ApiErr> {
+    let pins = pin::unpin(&claims, id, mid).await?;
+    let count = pins.len() as i64;
+    Ok(ApiResponse::List(pins, count))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/pins", get(get_pins))
+        .route("/sessions/{id}/pins/{mid}", put(pin).delete(unpin))
+}
@@ -0,0 +1,42 @@
+use core::api::types::session::{RetentionRequest, RetentionResponse};
+use core::auth::User;
+use core::extract::{Json, Path};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::get;
+use axum::Router;
+use crate::service::retention;
+
+// 获取会话的消息保留设置
+pub async fn get_retention(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<RetentionResponse>, ApiErr> {
+    Ok(ApiResponse::One(retention::get_retention(&claims, id).await?))
+}
+
+// 设置会话的消息保留天数，仅管理员可操作
+pub async fn set_retention(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+    Json(payload): Json<RetentionRequest>,
+) -> Result<ApiResponse<RetentionResponse>, ApiErr> {
+    Ok(ApiResponse::One(retention::set_retention(&claims, id, payload.days).await?))
+}
+
+// 服务指标，只在retention.metrics_bind上提供，无需认证
+pub async fn metrics() -> String {
+    retention::metrics()
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/retention", get(get_retention).put(set_retention))
+}
+
+pub fn metrics_router() -> Router {
+    Router::new()
+        .route("/metrics", get(metrics))
+}
@@ -0,0 +1,53 @@
+use core::api::types::message::{ScheduleRequest, ScheduledResponse};
+use core::auth::User;
+use core::extract::{Json, Path};
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::Extension;
+use axum::routing::{get, put};
+use axum::Router;
+use crate::service::scheduled;
+
+// 创建定时消息
+pub async fn create(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+    Json(payload): Json<ScheduleRequest>,
+) -> Result<ApiResponse<ScheduledResponse>, ApiErr> {
+    Ok(ApiResponse::One(scheduled::create(&claims, id, payload).await?))
+}
+
+// 获取当前用户在会话中待发送的定时消息
+pub async fn get_scheduled(
+    Extension(claims): Extension<User>,
+    Path(id): Path<i64>,
+) -> Result<ApiResponse<ScheduledResponse>, ApiErr> {
+    let messages = scheduled::get_scheduled(&claims, id).await?;
+    let count = messages.len() as i64;
+    Ok(ApiResponse::List(messages, count))
+}
+
+// 修改待发送的定时消息
+pub async fn update(
+    Extension(claims): Extension<User>,
+    Path((id, mid)): Path<(i64, i64)>,
+    Json(payload): Json<ScheduleRequest>,
+) -> Result<ApiResponse<ScheduledResponse>, ApiErr> {
+    Ok(ApiResponse::One(scheduled::update(&claims, id, mid, payload).await?))
+}
+
+// 取消待发送的定时消息
+pub async fn cancel(
+    Extension(claims): Extension<User>,
+    Path((id, mid)): Path<(i64, i64)>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    scheduled::cancel(&claims, id, mid).await?;
+    Ok(ApiResponse::One(true))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/scheduled", get(get_scheduled).post(create))
+        .route("/sessions/{id}/scheduled/{mid}", put(update).delete(cancel))
+}
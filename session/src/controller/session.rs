@@ -1,22 +1,30 @@
 use core::api::types::session::SessionResponse;
 use core::auth::User;
 use core::extract::Json;
-use core::extract::Path;
 use core::response::ApiResponse;
 use core::response::ApiErr;
 use anyhow::Result;
 use axum::Extension;
 use axum::routing::{get, post};
 use axum::Router;
+use crate::common::guard::Member;
 use crate::service::session;
 use core::api::types::session::SessionDetailResponse;
-use core::api::types::session::{CreateSessionRequest};
+use core::api::types::session::{CreateSessionRequest, ReadRequest};
+use core::api::types::session::ExportRequest;
+use core::extract::Query;
+use axum::body::Body;
+use axum::http::HeaderMap;
+use axum::http::header::{AUTHORIZATION, CONTENT_DISPOSITION, CONTENT_TYPE};
+use axum::response::{IntoResponse, Response};
+use crate::repository::session as session_repo;
+use crate::service::export::{self, Format};
 
 pub async fn create_session(
     Extension(claims): Extension<User>,
     Json(payload): Json<CreateSessionRequest>,
 ) -> Result<ApiResponse<SessionResponse>, ApiErr> {
-    let session = session::create_session(claims.id, payload).await?;
+    let session = session::create_session(&claims, payload).await?;
     Ok(ApiResponse::One(session.into()))
 }
 
@@ -25,20 +33,56 @@ pub async fn get_current_user_sessions(
     Extension(claims): Extension<User>,
 ) -> Result<ApiResponse<SessionResponse>, ApiErr> {
     let sessions = session::get_sessions_by_user(claims.id).await?;
-    Ok(ApiResponse::List(sessions.into_iter().map(|s|s.into()).collect(), 0))
+    Ok(ApiResponse::List(sessions, 0))
 }
 
 pub async fn get_session_details(
-    Path(id): Path<i64>,
+    member: Member,
 ) -> Result<ApiResponse<SessionDetailResponse>, ApiErr> {
     // 获取会话基本信息
-    let detail = session::get_session_by_id(id).await?;
+    let detail = session::get_session_by_id(member.sid).await?;
     Ok(ApiResponse::One(detail))
 }
 
+// 标记会话已读
+pub async fn mark_read(
+    member: Member,
+    Query(payload): Query<ReadRequest>,
+) -> Result<ApiResponse<bool>, ApiErr> {
+    session::mark_read(member.claims.id, member.sid, payload.message).await?;
+    Ok(ApiResponse::One(true))
+}
+
+// 导出会话的全部聊天记录
+pub async fn export(
+    member: Member,
+    headers: HeaderMap,
+    Query(payload): Query<ExportRequest>,
+) -> Result<Response, ApiErr> {
+    let format = Format::parse(payload.format.as_deref())?;
+    let session = session_repo::find_session_by_id(member.sid).await?
+        .ok_or(ApiErr::Bad(404, "会话不存在".to_string()))?;
+    // 使用调用者的Token查询文件信息
+    let token = headers.get(AUTHORIZATION)
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.strip_prefix("Bearer "))
+        .unwrap_or_default()
+        .to_string();
+    let filename = format!("session-{}.{}", session.id, format.extension());
+    Ok((
+        [
+            (CONTENT_TYPE, format.content_type().to_string()),
+            (CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename)),
+        ],
+        Body::from_stream(export::export(session, format, token)),
+    ).into_response())
+}
+
 pub fn router() -> Router {
     Router::new()
         .route("/sessions", post(create_session))
         .route("/user/sessions", get(get_current_user_sessions))
         .route("/sessions/{id}", get(get_session_details))
+        .route("/sessions/{id}/read", post(mark_read))
+        .route("/sessions/{id}/export", get(export))
 }
\ No newline at end of file
@@ -0,0 +1,29 @@
+use core::api::types::session::SessionSettings;
+use core::extract::Json;
+use core::response::ApiResponse;
+use core::response::ApiErr;
+use anyhow::Result;
+use axum::routing::get;
+use axum::Router;
+use crate::common::guard::Member;
+use crate::service::setting;
+
+// 获取当前用户在会话中的通知设置
+pub async fn get_settings(
+    member: Member,
+) -> Result<ApiResponse<SessionSettings>, ApiErr> {
+    Ok(ApiResponse::One(setting::get_settings(member.claims.id, member.sid).await?))
+}
+
+// 修改当前用户在会话中的通知设置：静音截止时间、仅提及时通知
+pub async fn set_settings(
+    member: Member,
+    Json(payload): Json<SessionSettings>,
+) -> Result<ApiResponse<SessionSettings>, ApiErr> {
+    Ok(ApiResponse::One(setting::set_settings(member.claims.id, member.sid, payload).await?))
+}
+
+pub fn router() -> Router {
+    Router::new()
+        .route("/sessions/{id}/settings", get(get_settings).put(set_settings))
+}
@@ -13,19 +13,37 @@ pub mod queue;
 pub mod test;
 
 use crate::repository::{db, rdb};
-use crate::common::router;
+use crate::common::{config, router};
 
 
 #[tokio::main]
 async fn main() -> Result<()> {
     dotenv().ok();
     core::log::init(Some(".pubchat_session.log"));
-    auth::init();
-    rdb::init().await;
-    db::init().await;
-    queue::init().await?;
+    let config = config::init();
+    auth::init(&config.auth);
+    core::api::client::init(&config.api);
+    rdb::init(&config.redis).await;
+    db::init(&config.database).await;
+    tokio::spawn(async {
+        if let Err(e) = service::message::upgrade().await {
+            log::error!("Failed to upgrade legacy messages: {}", e);
+        }
+    });
+    service::activity::init();
+    service::retention::init(&config.retention);
+    service::scheduled::init();
+    queue::init(&config.rabbitmq).await?;
+    //清理指标使用单独的内部监听地址，不经过业务接口的认证
+    let metrics = TcpListener::bind(&config.retention.metrics_bind).await?;
+    log::info!("metrics listening on {}", metrics.local_addr()?);
+    tokio::spawn(async move {
+        if let Err(e) = axum::serve(metrics, controller::retention::metrics_router()).await {
+            log::error!("Metrics listener stopped: {}", e);
+        }
+    });
     let app = router::init().expect("路由模块初始化失败");
-    let listener = TcpListener::bind("0.0.0.0:3001").await.unwrap();
+    let listener = TcpListener::bind(&config.server.bind).await.unwrap();
     log::info!("listening on {}", listener.local_addr().unwrap());
     axum::serve(listener, app).await.unwrap();
     Ok(())
@@ -0,0 +1,49 @@
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Invite {
+    pub id: i64,
+    pub sid: i64,
+    pub token: String,
+    pub creator: i64,
+    pub uname: String,
+    pub max_uses: Option<i32>,
+    pub uses: i32,
+    pub exp: i64,
+    pub revoked: bool,
+    pub createtime: NaiveDateTime,
+}
+
+//邀请令牌中签名的内容，令牌只用于定位邀请记录，有效性以数据库记录为准
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InviteClaims {
+    pub iid: i64,
+    pub sid: i64,
+    //过期时间戳(秒)
+    pub exp: i64,
+    pub aud: String,
+}
+
+impl InviteClaims {
+    //邀请令牌与登录令牌使用同一密钥，以aud区分，登录令牌不能当作邀请令牌使用，反之亦然
+    pub const AUDIENCE: &'static str = "pubchat:invite";
+}
+
+impl From<Invite> for core::api::types::session::InviteResponse {
+    fn from(invite: Invite) -> Self {
+        Self {
+            id: invite.id,
+            session: invite.sid,
+            token: invite.token,
+            creator: invite.creator,
+            uname: invite.uname,
+            max_uses: invite.max_uses.map(|m| m as u32),
+            uses: invite.uses as u32,
+            exp: invite.exp,
+            revoked: invite.revoked,
+            createtime: invite.createtime.and_utc().timestamp_millis(),
+        }
+    }
+}
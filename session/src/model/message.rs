@@ -10,4 +10,10 @@ pub struct Message {
     pub content: String,
     pub timestamp: i64,
     pub uname: String,
+    //结构化消息内容(JSON)，见core::api::types::message::Payload
+    #[sqlx(default)]
+    pub payload: Option<String>,
+    #[sqlx(default)]
+    #[sqlx(try_from = "i32")]
+    pub version: i8,
 }
\ No newline at end of file
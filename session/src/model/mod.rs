@@ -1,2 +1,7 @@
 pub mod message;
-pub mod session;
\ No newline at end of file
+pub mod session;
+pub mod notice;
+pub mod pin;
+pub mod invite;
+pub mod scheduled;
+pub mod moderation;
\ No newline at end of file
@@ -0,0 +1,81 @@
+use core::api::types::moderation::{ReviewResponse, RuleResponse};
+use pubchat::core::message::Rule;
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ModerationRule {
+    pub id: i64,
+    pub oid: i64,
+    #[sqlx(try_from = "i32")]
+    pub kind: i8,
+    pub pattern: String,
+    #[sqlx(try_from = "i64")]
+    pub max_len: u32,
+    #[sqlx(try_from = "i32")]
+    pub action: i8,
+    pub creator: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Review {
+    pub mid: i64,
+    pub sid: i64,
+    pub oid: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub content: String,
+    pub reason: String,
+    #[sqlx(try_from = "i32")]
+    pub status: i8,
+    pub reviewer: Option<i64>,
+    pub timestamp: i64,
+}
+
+impl Review {
+    pub const PENDING: i8 = 0;
+    pub const APPROVED: i8 = 1;
+    pub const REMOVED: i8 = 2;
+}
+
+impl From<&ModerationRule> for Rule {
+    fn from(rule: &ModerationRule) -> Self {
+        Self {
+            id: rule.id as u64,
+            kind: rule.kind as i32,
+            pattern: rule.pattern.clone(),
+            max_len: rule.max_len,
+            action: rule.action as i32,
+        }
+    }
+}
+
+impl From<ModerationRule> for RuleResponse {
+    fn from(rule: ModerationRule) -> Self {
+        Self {
+            id: rule.id,
+            oid: rule.oid,
+            kind: rule.kind,
+            pattern: rule.pattern,
+            max_len: rule.max_len,
+            action: rule.action,
+            creator: rule.creator,
+        }
+    }
+}
+
+impl From<Review> for ReviewResponse {
+    fn from(review: Review) -> Self {
+        Self {
+            mid: review.mid,
+            session: review.sid,
+            sender: review.sender,
+            uname: review.uname,
+            content: review.content,
+            reason: review.reason,
+            status: review.status,
+            reviewer: review.reviewer,
+            timestamp: review.timestamp,
+        }
+    }
+}
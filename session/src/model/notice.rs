@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Notice {
+    pub id: i64,
+    #[sqlx(try_from = "i32")]
+    pub severity: i8,
+    #[sqlx(try_from = "i32")]
+    pub scope: i8,
+    pub target: i64,
+    pub text: String,
+    pub exp: Option<i64>,
+    pub sender: i64,
+    pub uname: String,
+    pub timestamp: i64,
+}
+
+impl From<Notice> for core::api::types::notice::NoticeResponse {
+    fn from(notice: Notice) -> Self {
+        Self {
+            id: notice.id,
+            severity: notice.severity as i32,
+            scope: notice.scope as i32,
+            target: notice.target,
+            text: notice.text,
+            exp: notice.exp,
+            sender: notice.sender,
+            uname: notice.uname,
+            ts: notice.timestamp,
+        }
+    }
+}
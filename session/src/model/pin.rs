@@ -0,0 +1,12 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+//会话中的置顶消息，记录置顶操作者及时间
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Pin {
+    pub sid: i64,
+    pub mid: i64,
+    pub uid: i64,
+    pub uname: String,
+    pub pintime: i64,
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Scheduled {
+    pub id: i64,
+    pub sid: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub content: String,
+    pub send_at: i64,
+    #[sqlx(try_from = "i32")]
+    pub status: i8,
+}
+
+impl Scheduled {
+    pub const PENDING: i8 = 0;
+    pub const SENDING: i8 = 1;
+    pub const SENT: i8 = 2;
+    pub const CANCELLED: i8 = 3;
+    pub const FAILED: i8 = 4;
+}
+
+impl From<Scheduled> for core::api::types::message::ScheduledResponse {
+    fn from(scheduled: Scheduled) -> Self {
+        Self {
+            id: scheduled.id,
+            session: scheduled.sid,
+            sender: scheduled.sender,
+            uname: scheduled.uname,
+            content: scheduled.content,
+            send_at: scheduled.send_at,
+            status: scheduled.status,
+        }
+    }
+}
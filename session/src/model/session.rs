@@ -9,6 +9,27 @@ pub struct Session {
     pub creator: i64,
     pub createtime: NaiveDateTime,
     pub updatetime: NaiveDateTime,
+    //最后一条消息，由消息消费者批量更新
+    #[sqlx(default)]
+    pub last_message_id: Option<i64>,
+    #[sqlx(default)]
+    pub last_message_at: Option<i64>,
+    //所属组织，取创建者的组织
+    #[sqlx(default)]
+    pub oid: i64,
+    //消息保留天数，为空时沿用组织或全局配置
+    #[sqlx(default)]
+    pub retention_days: Option<i32>,
+    //会话类型，见pubchat::core::message::SessionKind
+    #[sqlx(default)]
+    #[sqlx(try_from = "i32")]
+    pub kind: i8,
+}
+
+impl Session {
+    pub const DIRECT: i8 = 0;
+    pub const GROUP: i8 = 1;
+    pub const CHANNEL: i8 = 2;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -17,6 +38,7 @@ pub struct UserSession {
     pub uid: i64,
     pub uname: String,
     pub sid: i64,
+    #[sqlx(try_from = "i32")]
     pub role: i8, // 0-普通成员, 1-管理员
     pub jointime: NaiveDateTime,
 }
@@ -26,6 +48,71 @@ impl Into<core::api::types::session::SessionResponse> for Session {
         core::api::types::session::SessionResponse {
             id: self.id,
             name: self.name,
+            kind: self.kind,
+            unread: 0,
+            last_message: None,
+            settings: Default::default(),
         }
     }
-}
\ No newline at end of file
+}
+//用户在会话中的已读位置
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ReadCursor {
+    pub uid: i64,
+    pub sid: i64,
+    pub last_read_message_id: i64,
+    pub last_read_timestamp: i64,
+}
+
+//会话及当前用户的未读数
+#[derive(Debug, Clone, FromRow)]
+pub struct SessionSummary {
+    #[sqlx(flatten)]
+    pub session: Session,
+    pub unread: i64,
+    #[sqlx(try_from = "i32")]
+    pub notify: i8,
+    pub muted_until: Option<i64>,
+}
+
+//组织内的频道及订阅情况
+#[derive(Debug, Clone, FromRow)]
+pub struct ChannelSummary {
+    #[sqlx(flatten)]
+    pub session: Session,
+    pub subscribers: i64,
+    pub subscribed: bool,
+}
+
+//用户在会话中的通知设置
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SessionSetting {
+    pub uid: i64,
+    pub sid: i64,
+    #[sqlx(try_from = "i32")]
+    pub notify: i8,
+    pub muted_until: Option<i64>,
+}
+
+//会话内的身份，群主即会话的creator，可以转让
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Role {
+    Member = 0,
+    Admin = 1,
+    Owner = 2,
+}
+
+impl UserSession {
+    pub const MEMBER: i8 = 0;
+    pub const ADMIN: i8 = 1;
+
+    pub fn role_in(&self, session: &Session) -> Role {
+        if session.creator == self.uid {
+            Role::Owner
+        } else if self.role == Self::ADMIN {
+            Role::Admin
+        } else {
+            Role::Member
+        }
+    }
+}
@@ -4,10 +4,15 @@ use lapin::{
     Channel, options::{BasicAckOptions, BasicConsumeOptions}, types::FieldTable
 };
 use serde_json;
+use core::api::types::message::{Payload, PAYLOAD_VERSION};
+use core::config::RabbitConfig;
+use core::queue::can_retry;
 use crate::model::message::Message;
-use crate::service::message;
+use crate::queue::dead_letter;
+use crate::service::{activity, message, moderation};
 
-pub async fn consume_messages(channel: Channel, queue_name: &str) -> Result<()> {
+pub async fn consume_messages(channel: Channel, config: &RabbitConfig) -> Result<()> {
+        let queue_name = &config.queue;
         let mut consumer = channel
             .basic_consume(
                 queue_name,
@@ -21,37 +26,29 @@ pub async fn consume_messages(channel: Channel, queue_name: &str) -> Result<()>
             if let Ok(delivery) = delivery {
                 match serde_json::from_slice::<pubchat::core::message::Message>(&delivery.data) {
                     Ok(proto_message) => {
-                        // Convert the proto message to our database message model
-                        if let Some(pubchat::core::message::message::Content::Chrt(chat)) = proto_message.content {
-                            if let Some(m) = chat.message { 
-                                let message = Message {
-                                    id: proto_message.id as i64,
-                                    sender: chat.sender as i64,
-                                    session: chat.session as i64,
-                                    mtype: chat.ctype,
-                                    content: format!("{}", m),
-                                    timestamp: chat.ts as i64,
-                                    uname: chat.uname.clone(),
-                                };
-                                // Save the message to the database
-                                if let Err(e) = message::save_message(message).await {
-                                    log::error!("Failed to save message to database: {}", e);
-                                    continue;
+                        let id = proto_message.id;
+                        match handle(proto_message).await {
+                            Ok(()) => {
+                                if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+                                    log::error!("Failed to acknowledge message: {}", e);
+                                }
+                            }
+                            Err(e) => {
+                                let attempts = dead_letter::attempts(&delivery) + 1;
+                                if can_retry(attempts, config.max_retries) {
+                                    log::warn!("Failed to handle message {} (attempt {}), retrying: {}", id, attempts, e);
+                                    dead_letter::retry(channel.clone(), delivery, queue_name.clone(), attempts);
+                                } else {
+                                    log::error!("Failed to handle message {} after {} attempts: {}", id, attempts, e);
+                                    dead_letter::reject(&channel, &delivery, config, attempts, &e.to_string()).await;
                                 }
                             }
-                        }
-                        
-                        // Acknowledge the message
-                        if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
-                            log::error!("Failed to acknowledge message: {}", e);
                         }
                     }
                     Err(e) => {
+                        //无法解析的消息重试也不会成功，直接转入死信队列
                         log::error!("Failed to parse message from RabbitMQ: {}", e);
-                        // Even if parsing fails, acknowledge the message to avoid message buildup
-                        if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
-                            log::error!("Failed to acknowledge message: {}", e);
-                        }
+                        dead_letter::reject(&channel, &delivery, config, 1, &format!("消息解析失败: {}", e)).await;
                     }
                 }
             }
@@ -59,3 +56,32 @@ pub async fn consume_messages(channel: Channel, queue_name: &str) -> Result<()>
 
         Ok(())
     }
+
+//持久化聊天消息，重复投递的消息不会重复写入
+async fn handle(proto_message: pubchat::core::message::Message) -> Result<()> {
+    if let Some(pubchat::core::message::message::Content::Chrt(chat)) = proto_message.content
+        && let Some(m) = &chat.message {
+        //审核记录先于消息写入且幂等，写入失败重试时不会遗漏
+        if let Some(reason) = &chat.flag {
+            moderation::flag(proto_message.id as i64, &chat, reason).await?;
+        }
+        let payload = Payload::from(m);
+        let message = Message {
+            id: proto_message.id as i64,
+            sender: chat.sender as i64,
+            session: chat.session as i64,
+            mtype: chat.ctype,
+            content: payload.to_string(),
+            timestamp: chat.ts as i64,
+            uname: chat.uname.clone(),
+            payload: message::encode(&payload).ok(),
+            version: PAYLOAD_VERSION as i8,
+        };
+        if message::save_message(&message).await? {
+            activity::record(&message);
+        } else {
+            log::info!("Duplicate message {} ignored", message.id);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,195 @@
+use anyhow::Result;
+use core::api::types::message::DeadLetter;
+use core::config::RabbitConfig;
+use core::queue::{ATTEMPTS, EXCHANGE, FAILED_AT, HEADERS, REASON, RETRY_BACKOFF, ROUTING_KEY, SOURCE};
+use lapin::message::Delivery;
+use lapin::options::{BasicAckOptions, BasicGetOptions, BasicNackOptions, BasicPublishOptions, ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions};
+use lapin::types::{AMQPValue, FieldTable, LongString, ShortString};
+use lapin::Channel;
+use crate::queue::POOL;
+
+//声明死信交换机及当前服务的死信队列
+pub async fn declare(channel: &Channel, config: &RabbitConfig) -> Result<()> {
+    channel.exchange_declare(
+            &config.dead_letter_exchange,
+            lapin::ExchangeKind::Direct,
+            ExchangeDeclareOptions::default(),
+            Default::default(),
+        )
+        .await?;
+    channel.queue_declare(&config.dead_letter_queue, QueueDeclareOptions::default(), Default::default()).await?;
+    channel.queue_bind(
+            &config.dead_letter_queue,
+            &config.dead_letter_exchange,
+            &config.dead_letter_queue,
+            QueueBindOptions::default(),
+            Default::default(),
+        )
+        .await?;
+    Ok(())
+}
+
+//消息此前已失败的次数，记录在消息头中，由任一实例重试时累加
+pub fn attempts(delivery: &Delivery) -> u32 {
+    match delivery.properties.headers().as_ref().and_then(|h| h.inner().get(ATTEMPTS)) {
+        Some(AMQPValue::LongUInt(n)) => *n,
+        _ => 0,
+    }
+}
+
+//等待后带上失败次数重新发布到当前服务的队列并确认原消息，发布失败时原消息重新入队
+//在单独的任务中等待，不阻塞其他消息的处理
+pub fn retry(channel: Channel, delivery: Delivery, queue: String, attempts: u32) {
+    tokio::spawn(async move {
+        tokio::time::sleep(RETRY_BACKOFF * attempts).await;
+        let mut headers = delivery.properties.headers().clone().unwrap_or_default();
+        headers.insert(ATTEMPTS.into(), AMQPValue::LongUInt(attempts));
+        let properties = delivery.properties.clone().with_headers(headers);
+        //通过默认交换机直接投递到当前服务的队列，其他服务不会重复消费
+        let published = async {
+            channel.basic_publish("", &queue, BasicPublishOptions::default(), &delivery.data, properties)
+                .await?
+                .await?;
+            anyhow::Ok(())
+        }.await;
+        let result = match published {
+            Ok(()) => delivery.ack(BasicAckOptions::default()).await,
+            Err(e) => {
+                log::error!("Failed to republish message for retry: {}", e);
+                delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await
+            }
+        };
+        if let Err(e) = result {
+            log::error!("Failed to settle retried message: {}", e);
+        }
+    });
+}
+
+//将消息转入死信队列并确认原消息，转入失败时重新入队，避免丢失
+pub async fn reject(channel: &Channel, delivery: &Delivery, config: &RabbitConfig, attempts: u32, reason: &str) {
+    let mut headers = strip(delivery.properties.headers().as_ref());
+    headers.insert(SOURCE.into(), long(&config.queue));
+    headers.insert(EXCHANGE.into(), long(delivery.exchange.as_str()));
+    headers.insert(ROUTING_KEY.into(), long(delivery.routing_key.as_str()));
+    headers.insert(REASON.into(), long(reason));
+    headers.insert(ATTEMPTS.into(), AMQPValue::LongUInt(attempts));
+    headers.insert(FAILED_AT.into(), AMQPValue::LongLongInt(chrono::Utc::now().timestamp_millis()));
+    let properties = delivery.properties.clone().with_headers(headers);
+    let published = async {
+        channel.basic_publish(
+                &config.dead_letter_exchange,
+                &config.dead_letter_queue,
+                BasicPublishOptions::default(),
+                &delivery.data,
+                properties,
+            )
+            .await?
+            .await?;
+        anyhow::Ok(())
+    }.await;
+    match published {
+        Ok(()) => {
+            log::warn!("Message dead-lettered to {}: {}", config.dead_letter_queue, reason);
+            if let Err(e) = delivery.ack(BasicAckOptions::default()).await {
+                log::error!("Failed to acknowledge message: {}", e);
+            }
+        }
+        Err(e) => {
+            log::error!("Failed to dead-letter message: {}", e);
+            if let Err(e) = delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await {
+                log::error!("Failed to requeue message: {}", e);
+            }
+        }
+    }
+}
+
+//查看死信队列中的消息，查看后消息重新放回队列
+pub async fn list(queue: &str, limit: u32) -> Result<Vec<DeadLetter>> {
+    let channel = POOL.get().expect("RabbitMQ连接池未初始化").get().await?.create_channel().await?;
+    let mut letters = vec![];
+    let mut deliveries = vec![];
+    while letters.len() < limit as usize {
+        let Some(message) = channel.basic_get(queue, BasicGetOptions::default()).await? else {
+            break;
+        };
+        letters.push(letter(&message.delivery));
+        deliveries.push(message.delivery);
+    }
+    for delivery in deliveries {
+        delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await?;
+    }
+    Ok(letters)
+}
+
+//将死信队列中的消息投递回原队列重新处理，返回重放的消息数
+pub async fn replay(queue: &str, limit: u32) -> Result<u32> {
+    let channel = POOL.get().expect("RabbitMQ连接池未初始化").get().await?.create_channel().await?;
+    let mut replayed = 0;
+    while replayed < limit {
+        let Some(message) = channel.basic_get(queue, BasicGetOptions::default()).await? else {
+            break;
+        };
+        let delivery = message.delivery;
+        let headers = delivery.properties.headers().as_ref();
+        let Some(source) = header(headers, SOURCE) else {
+            log::warn!("Dead letter without source queue in {}, skipped", queue);
+            delivery.nack(BasicNackOptions { requeue: true, ..Default::default() }).await?;
+            break;
+        };
+        let properties = delivery.properties.clone().with_headers(strip(headers));
+        //通过默认交换机直接投递到原队列，避免其他服务重复消费
+        channel.basic_publish("", &source, BasicPublishOptions::default(), &delivery.data, properties)
+            .await?
+            .await?;
+        delivery.ack(BasicAckOptions::default()).await?;
+        replayed += 1;
+    }
+    log::info!("Replayed {} dead letters from {}", replayed, queue);
+    Ok(replayed)
+}
+
+fn letter(delivery: &Delivery) -> DeadLetter {
+    let headers = delivery.properties.headers().as_ref();
+    let body = String::from_utf8_lossy(&delivery.data).to_string();
+    DeadLetter {
+        id: serde_json::from_slice::<pubchat::core::message::Message>(&delivery.data).ok().map(|m| m.id),
+        source: header(headers, SOURCE).unwrap_or_default(),
+        exchange: header(headers, EXCHANGE).unwrap_or_default(),
+        routing_key: header(headers, ROUTING_KEY).unwrap_or_default(),
+        reason: header(headers, REASON).unwrap_or_default(),
+        attempts: match headers.and_then(|h| h.inner().get(ATTEMPTS)) {
+            Some(AMQPValue::LongUInt(n)) => *n,
+            _ => 0,
+        },
+        failed_at: match headers.and_then(|h| h.inner().get(FAILED_AT)) {
+            Some(AMQPValue::LongLongInt(n)) => *n,
+            _ => 0,
+        },
+        body,
+    }
+}
+
+fn long(value: &str) -> AMQPValue {
+    AMQPValue::LongString(LongString::from(value))
+}
+
+pub fn header(headers: Option<&FieldTable>, key: &str) -> Option<String> {
+    match headers?.inner().get(key)? {
+        AMQPValue::LongString(s) => Some(s.to_string()),
+        AMQPValue::ShortString(s) => Some(s.to_string()),
+        _ => None,
+    }
+}
+
+//去掉死信相关的消息头
+pub fn strip(headers: Option<&FieldTable>) -> FieldTable {
+    let mut table = FieldTable::default();
+    if let Some(headers) = headers {
+        for (k, v) in headers.inner() {
+            if !HEADERS.contains(&k.as_str()) {
+                table.insert(ShortString::from(k.as_str()), v.clone());
+            }
+        }
+    }
+    table
+}
@@ -1,19 +1,26 @@
-use deadpool::Runtime;
-use deadpool_lapin::Config;
+use std::sync::OnceLock;
+
+use deadpool::{managed::Pool, Runtime};
+use deadpool_lapin::{Config, Manager};
 use anyhow::Result;
-use lapin::options::{ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions};
+use core::config::RabbitConfig;
+use lapin::{BasicProperties, options::{BasicPublishOptions, ExchangeDeclareOptions, QueueBindOptions, QueueDeclareOptions}};
 
 mod consumer;
+pub mod dead_letter;
+
+pub static POOL: OnceLock<Pool<Manager>> = OnceLock::new();
+static EXCHANGE: OnceLock<String> = OnceLock::new();
 
-pub async fn init()  -> Result<()>{
-    let connstring = dotenv::var("RABBITMQ_ADDR").expect("请设置RabbitMQ连接地址RABBITMQ_ADDR");
-    let session_message_queue_name = dotenv::var("RABBITMQ_SESSION_MESSAGE_QUEUE_NAME").expect("请设置RabbitMQ队列名称RABBITMQ_SESSION_MESSAGE_QUEUE_NAME");
-    let exc_name = dotenv::var("RABBITMQ_MESSAGE_EXCHANGE").expect("请设置RabbitMQ交换机名称RABBITMQ_MESSAGE_EXCHANGE");
-    print!("RabbitMQ连接地址: {}", connstring);
+pub async fn init(config: &RabbitConfig)  -> Result<()>{
+    let session_message_queue_name = config.queue.clone();
+    let exc_name = &config.exchange;
     let mut cfg = Config::default();
-    cfg.url = Some(connstring.into());
+    cfg.url = Some(config.addr.clone());
     let pool = cfg.create_pool(Some(Runtime::Tokio1))?;
     let channel = pool.get().await?.create_channel().await?;
+    POOL.set(pool).or(Err(anyhow::anyhow!("RabbitMQ连接池重复初始化")))?;
+    EXCHANGE.set(exc_name.clone()).or(Err(anyhow::anyhow!("RabbitMQ交换机重复初始化")))?;
     let _queue = channel
             .queue_declare(
                 &session_message_queue_name,
@@ -23,7 +30,7 @@ pub async fn init()  -> Result<()>{
             .await?; 
     let _exchange = channel
             .exchange_declare(
-                &exc_name,
+                exc_name,
                 lapin::ExchangeKind::Topic,
                 ExchangeDeclareOptions::default(),
                 Default::default(),
@@ -32,18 +39,38 @@ pub async fn init()  -> Result<()>{
     channel
             .queue_bind(
                 &session_message_queue_name, 
-                &exc_name, 
+                exc_name, 
                 "#", 
                 QueueBindOptions::default(), 
                 Default::default()).await?;
-    print!("RabbitMQ队列名称: {}", session_message_queue_name);
+    dead_letter::declare(&channel, config).await?;
+    let config = config.clone();
     // Start RabbitMQ consumer in a separate task
-    // let message_service_clone = message_service.clone();
     tokio::spawn(async move {
-        if let Err(e) = consumer::consume_messages(channel, &session_message_queue_name).await {
+        if let Err(e) = consumer::consume_messages(channel, &config).await {
             log::error!("Error consuming messages: {}", e);
         }
     });
     Ok(())
 }
 
+
+
+//向消息交换机发布消息，由连接服务投递给在线用户
+pub async fn publish(message: &pubchat::core::message::Message, routing_key: &str) -> Result<()> {
+    let payload = serde_json::to_vec(message)?;
+    let pool = POOL.get().expect("RabbitMQ连接池未初始化");
+    let exc_name = EXCHANGE.get().expect("RabbitMQ交换机未初始化");
+    let channel = pool.get().await?.create_channel().await?;
+    let _confirm = channel.basic_publish(
+            exc_name,
+            routing_key,
+            BasicPublishOptions::default(),
+            &payload,
+            BasicProperties::default(),
+        )
+        .await?
+        .await?;
+    log::info!("Message published to RabbitMQ, routing key: {}", routing_key);
+    Ok(())
+}
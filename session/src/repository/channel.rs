@@ -0,0 +1,25 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::session::{ChannelSummary, Session};
+use crate::repository::db;
+
+//查询组织内的频道，附带订阅人数及用户是否已订阅
+pub async fn find_by_org(oid: i64, uid: i64) -> Result<Vec<ChannelSummary>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, r#"
+        SELECT s.*,
+            (SELECT COUNT(1) FROM user_sessions us WHERE us.sid = s.id) AS subscribers,
+            EXISTS(SELECT 1 FROM user_sessions us WHERE us.sid = s.id AND us.uid = ?) AS subscribed
+        FROM sessions s
+        WHERE s.kind = ? AND s.oid = ?
+        ORDER BY s.createtime DESC, s.id DESC
+        "#, |sql, conn| {
+        sqlx::query_as::<_, ChannelSummary>(sql)
+            .bind(uid)
+            .bind(Session::CHANNEL as i32)
+            .bind(oid)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
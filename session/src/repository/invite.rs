@@ -0,0 +1,74 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::invite::Invite;
+use crate::repository::db;
+
+pub async fn save(invite: &Invite) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "INSERT INTO invites (id, sid, token, creator, uname, max_uses, uses, exp, revoked, createtime) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(invite.id)
+            .bind(invite.sid)
+            .bind(&invite.token)
+            .bind(invite.creator)
+            .bind(&invite.uname)
+            .bind(invite.max_uses)
+            .bind(invite.uses)
+            .bind(invite.exp)
+            .bind(invite.revoked)
+            .bind(invite.createtime)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn find_by_id(id: i64) -> Result<Option<Invite>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM invites WHERE id = ?", |sql, conn| {
+        sqlx::query_as::<_, Invite>(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+pub async fn find_by_session(sid: i64) -> Result<Vec<Invite>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM invites WHERE sid = ? ORDER BY createtime DESC, id DESC", |sql, conn| {
+        sqlx::query_as::<_, Invite>(sql)
+            .bind(sid)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//撤销邀请，邀请不存在或已撤销时返回false
+pub async fn revoke(sid: i64, id: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE invites SET revoked = TRUE WHERE sid = ? AND id = ? AND revoked = FALSE", |sql, conn| {
+        sqlx::query(sql)
+            .bind(sid)
+            .bind(id)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//占用一次邀请的使用次数，邀请已撤销、过期或次数用尽时返回false
+pub async fn consume(id: i64, now: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE invites SET uses = uses + 1 WHERE id = ? AND revoked = FALSE AND exp > ? AND (max_uses IS NULL OR uses < max_uses)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .bind(now)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
@@ -0,0 +1,59 @@
+use anyhow::Result;
+use redis::Commands;
+use crate::repository::{rdb, session};
+
+//成员集合缓存有效期(秒)，成员变更时主动失效
+const TTL_SECS: i64 = 600;
+
+fn key(sid: i64) -> String {
+    format!("session:members:{}", sid)
+}
+
+//查询用户是否为会话成员，优先读取Redis中的成员集合，未命中时从数据库加载并回填
+pub async fn is_member(sid: i64, uid: i64) -> Result<bool> {
+    match cached(sid, uid).await {
+        Ok(Some(member)) => return Ok(member),
+        Ok(None) => {}
+        Err(e) => log::error!("读取会话{}成员缓存失败: {}", sid, e),
+    }
+    let members: Vec<i64> = session::find_user_sessions_by_session(sid).await?
+        .into_iter()
+        .map(|m| m.uid)
+        .collect();
+    if !members.is_empty() && let Err(e) = fill(sid, &members).await {
+        log::error!("写入会话{}成员缓存失败: {}", sid, e);
+    }
+    Ok(members.contains(&uid))
+}
+
+//缓存不存在时返回None
+async fn cached(sid: i64, uid: i64) -> Result<Option<bool>> {
+    let mut connection = rdb::connection().await?;
+    let (exists, member): (bool, bool) = redis::pipe()
+        .exists(key(sid))
+        .sismember(key(sid), uid)
+        .query(&mut connection)?;
+    Ok(if exists { Some(member) } else { None })
+}
+
+async fn fill(sid: i64, members: &[i64]) -> Result<()> {
+    let mut connection = rdb::connection().await?;
+    redis::pipe()
+        .atomic()
+        .sadd(key(sid), members)
+        .expire(key(sid), TTL_SECS)
+        .query::<()>(&mut connection)?;
+    Ok(())
+}
+
+//成员变更后使缓存失效
+pub async fn evict(sid: i64) {
+    let result: Result<()> = async {
+        let mut connection = rdb::connection().await?;
+        connection.del::<_, ()>(key(sid))?;
+        Ok(())
+    }.await;
+    if let Err(e) = result {
+        log::error!("清除会话{}成员缓存失败: {}", sid, e);
+    }
+}
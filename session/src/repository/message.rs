@@ -1,34 +1,189 @@
 use anyhow::Result;
+use core::api::types::message::SearchMessageRequest;
+use core::request::Position;
+use core::db::Backend;
+use core::dispatch;
+use sqlx::{Database, Encode, QueryBuilder, Type};
 use crate::model::message::Message;
 use crate::repository::db;
 
+//按消息id幂等写入，消息已存在时返回false
+pub async fn save(message: &Message) -> Result<bool> {
+    let sql = db::backend().insert_ignore("INTO messages (id, sender, session, mtype, content, timestamp, uname, payload, version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)");
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(message.id)
+            .bind(message.sender)
+            .bind(message.session)
+            .bind(message.mtype)
+            .bind(&message.content)
+            .bind(message.timestamp)
+            .bind(&message.uname)
+            .bind(&message.payload)
+            .bind(message.version as i32)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//查询旧格式(version为0)的消息
+//消息表及归档表，两张表的结构相同
+pub const TABLES: [&str; 2] = ["messages", "messages_archive"];
+
+//查询table中只有content文本的旧格式消息，table取自TABLES
+pub async fn find_legacy(table: &str, limit: u32) -> Result<Vec<Message>> {
+    let connection = db::connection().await?;
+    let sql = format!("SELECT * FROM {} WHERE version = 0 LIMIT ?", table);
+    let result = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as::<_, Message>(sql)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
 
-pub async fn save(message: &Message) -> Result<()> {
-    let mut connection = db::connection().await?;
-    sqlx::query("INSERT INTO messages (id, sender, session, mtype, content, timestamp, uname) VALUES (?, ?, ?, ?, ?, ?, ?)")
-        .bind(&message.id)
-        .bind(&message.sender)
-        .bind(&message.session)
-        .bind(&message.mtype)
-        .bind(&message.content)
-        .bind(&message.timestamp)
-        .bind(&message.uname)
-        .execute(connection.as_mut())
-        .await?;
+pub async fn update_payload(table: &str, id: i64, payload: &str, version: i8) -> Result<()> {
+    let connection = db::connection().await?;
+    let sql = format!("UPDATE {} SET payload = ?, version = ? WHERE id = ?", table);
+    dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(payload)
+            .bind(version as i32)
+            .bind(id)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
 
-pub async fn find_by_session(session: i64, limit: u32) -> Result<Vec<Message>> {
-    let mut connection = db::connection().await?;
-    let result : Vec<Message> = sqlx::query_as(r#"
-        SELECT * FROM messages 
-        WHERE session = ? 
-        ORDER BY timestamp DESC
-        LIMIT ?
-        "#)
-    .bind(session)
-    .bind(limit)
-    .fetch_all(connection.as_mut())
-    .await?;
+pub async fn find_by_id(session: i64, id: i64) -> Result<Option<Message>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM messages WHERE session = ? AND id = ?", |sql, conn| {
+        sqlx::query_as::<_, Message>(sql)
+            .bind(session)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//删除会话中的消息，消息不存在时返回false
+pub async fn delete(session: i64, id: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "DELETE FROM messages WHERE session = ? AND id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(session)
+            .bind(id)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+pub async fn find_by_ids(ids: &[i64]) -> Result<Vec<Message>> {
+    if ids.is_empty() {
+        return Ok(vec![]);
+    }
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, |conn| {
+        let mut builder = QueryBuilder::new("SELECT * FROM messages WHERE id IN (");
+        let mut separated = builder.separated(", ");
+        for id in ids {
+            separated.push_bind(*id);
+        }
+        separated.push_unseparated(")");
+        builder.build_query_as::<Message>()
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//按游标查询会话消息，after单独使用时按时间正序返回，其余情况按时间倒序返回
+pub async fn find_by_session(session: i64, before: Option<Position>, after: Option<Position>, limit: u32) -> Result<Vec<Message>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, |conn| {
+        let mut builder = QueryBuilder::new("SELECT * FROM messages WHERE session = ");
+        builder.push_bind(session);
+        if let Some(before) = before {
+            builder.push(" AND (timestamp < ").push_bind(before.timestamp)
+                .push(" OR (timestamp = ").push_bind(before.timestamp)
+                .push(" AND id < ").push_bind(before.id).push("))");
+        }
+        if let Some(after) = after {
+            builder.push(" AND (timestamp > ").push_bind(after.timestamp)
+                .push(" OR (timestamp = ").push_bind(after.timestamp)
+                .push(" AND id > ").push_bind(after.id).push("))");
+        }
+        if after.is_some() && before.is_none() {
+            builder.push(" ORDER BY timestamp ASC, id ASC");
+        } else {
+            builder.push(" ORDER BY timestamp DESC, id DESC");
+        }
+        builder.push(" LIMIT ").push_bind(limit as i64);
+        builder.build_query_as::<Message>()
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//拼接搜索条件，只在uid所在的会话中搜索
+//MySQL使用全文索引按query(布尔模式)搜索，其他后端没有ngram全文索引，按terms逐个进行不区分大小写的LIKE匹配
+fn push_search_filters<'a, DB: Database>(builder: &mut QueryBuilder<'a, DB>, backend: Backend, uid: i64, query: &str, terms: &[String], request: &SearchMessageRequest)
+where
+    i64: Encode<'a, DB> + Type<DB>,
+    String: Encode<'a, DB> + Type<DB>,
+{
+    if backend == Backend::MySql {
+        builder.push(" WHERE MATCH(content) AGAINST(").push_bind(query.to_string()).push(" IN BOOLEAN MODE)");
+    } else {
+        builder.push(" WHERE 1 = 1");
+        for term in terms {
+            let pattern = term.to_lowercase().replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            builder.push(" AND LOWER(content) LIKE ").push_bind(format!("%{}%", pattern)).push(" ESCAPE '\\'");
+        }
+    }
+    builder.push(" AND session IN (SELECT sid FROM user_sessions WHERE uid = ").push_bind(uid).push(")");
+    if let Some(session) = request.session {
+        builder.push(" AND session = ").push_bind(session);
+    }
+    if let Some(sender) = request.sender {
+        builder.push(" AND sender = ").push_bind(sender);
+    }
+    if let Some(from) = request.from {
+        builder.push(" AND timestamp >= ").push_bind(from);
+    }
+    if let Some(to) = request.to {
+        builder.push(" AND timestamp <= ").push_bind(to);
+    }
+}
+
+pub async fn search(uid: i64, query: &str, terms: &[String], request: &SearchMessageRequest, limit: u32, offset: u32) -> Result<(Vec<Message>, i64)> {
+    let backend = db::backend();
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, |conn| {
+        let mut builder = QueryBuilder::new("SELECT * FROM messages");
+        push_search_filters(&mut builder, backend, uid, query, terms, request);
+        builder.push(" ORDER BY timestamp DESC, id DESC LIMIT ").push_bind(limit as i64)
+            .push(" OFFSET ").push_bind(offset as i64);
+        let result: Vec<Message> = builder
+            .build_query_as()
+            .fetch_all(&mut *conn)
+            .await?;
+        let mut builder = QueryBuilder::new("SELECT COUNT(1) FROM messages");
+        push_search_filters(&mut builder, backend, uid, query, terms, request);
+        let count: (i64,) = builder
+            .build_query_as()
+            .fetch_one(&mut *conn)
+            .await?;
+        (result, count.0)
+    });
     Ok(result)
 }
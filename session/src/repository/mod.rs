@@ -1,4 +1,14 @@
 pub mod db;
+pub mod membership;
 pub mod message;
+pub mod notice;
 pub mod rdb;
-pub mod session;
\ No newline at end of file
+pub mod read;
+pub mod retention;
+pub mod session;
+pub mod setting;
+pub mod channel;
+pub mod pin;
+pub mod invite;
+pub mod scheduled;
+pub mod moderation;
\ No newline at end of file
@@ -0,0 +1,128 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::moderation::{ModerationRule, Review};
+use crate::repository::db;
+
+pub async fn save_rule(rule: &ModerationRule) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "INSERT INTO moderation_rules (id, oid, kind, pattern, max_len, action, creator) VALUES (?, ?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(rule.id)
+            .bind(rule.oid)
+            .bind(rule.kind as i32)
+            .bind(&rule.pattern)
+            .bind(rule.max_len as i64)
+            .bind(rule.action as i32)
+            .bind(rule.creator)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn find_rule(id: i64) -> Result<Option<ModerationRule>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT id, oid, kind, pattern, max_len, action, creator FROM moderation_rules WHERE id = ?", |sql, conn| {
+        sqlx::query_as::<_, ModerationRule>(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//按创建顺序查询组织的审核规则
+pub async fn find_rules(oid: i64) -> Result<Vec<ModerationRule>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT id, oid, kind, pattern, max_len, action, creator FROM moderation_rules WHERE oid = ? ORDER BY id", |sql, conn| {
+        sqlx::query_as::<_, ModerationRule>(sql)
+            .bind(oid)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+pub async fn count_rules(oid: i64) -> Result<i64> {
+    let connection = db::connection().await?;
+    let result: (i64,) = dispatch!(connection, "SELECT COUNT(*) FROM moderation_rules WHERE oid = ?", |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(oid)
+            .fetch_one(conn)
+            .await?
+    });
+    Ok(result.0)
+}
+
+pub async fn delete_rule(id: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "DELETE FROM moderation_rules WHERE id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//记录待审核消息，重复投递的消息只记录一次
+pub async fn save_review(review: &Review) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &db::backend().insert_ignore("INTO moderation_reviews (mid, sid, oid, sender, uname, content, reason, status, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"), |sql, conn| {
+        sqlx::query(sql)
+            .bind(review.mid)
+            .bind(review.sid)
+            .bind(review.oid)
+            .bind(review.sender)
+            .bind(&review.uname)
+            .bind(&review.content)
+            .bind(&review.reason)
+            .bind(review.status as i32)
+            .bind(review.timestamp)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+pub async fn find_review(mid: i64) -> Result<Option<Review>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT mid, sid, oid, sender, uname, content, reason, status, reviewer, timestamp FROM moderation_reviews WHERE mid = ?", |sql, conn| {
+        sqlx::query_as::<_, Review>(sql)
+            .bind(mid)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//按消息时间倒序查询组织中指定状态的审核记录
+pub async fn find_reviews(oid: i64, status: i8, limit: u32) -> Result<Vec<Review>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT mid, sid, oid, sender, uname, content, reason, status, reviewer, timestamp FROM moderation_reviews WHERE oid = ? AND status = ? ORDER BY timestamp DESC LIMIT ?", |sql, conn| {
+        sqlx::query_as::<_, Review>(sql)
+            .bind(oid)
+            .bind(status as i32)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//完成审核，只有待审核的记录可以变更，并发审核时只有一个成功
+pub async fn resolve(mid: i64, status: i8, reviewer: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE moderation_reviews SET status = ?, reviewer = ? WHERE mid = ? AND status = 0", |sql, conn| {
+        sqlx::query(sql)
+            .bind(status as i32)
+            .bind(reviewer)
+            .bind(mid)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
@@ -0,0 +1,46 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::notice::Notice;
+use crate::repository::db;
+
+pub async fn save(notice: &Notice) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "INSERT INTO notices (id, severity, scope, target, text, exp, sender, uname, timestamp) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(notice.id)
+            .bind(notice.severity as i32)
+            .bind(notice.scope as i32)
+            .bind(notice.target)
+            .bind(&notice.text)
+            .bind(notice.exp)
+            .bind(notice.sender)
+            .bind(&notice.uname)
+            .bind(notice.timestamp)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+//查询用户可见且在now时仍有效的公告：全员公告、所属组织公告、所在会话公告
+pub async fn find_active(uid: i64, oid: i64, now: i64, limit: u32) -> Result<Vec<Notice>> {
+    let connection = db::connection().await?;
+    let result: Vec<Notice> = dispatch!(connection, r#"
+        SELECT * FROM notices
+        WHERE (exp IS NULL OR exp > ?)
+        AND (scope = 0
+            OR (scope = 1 AND target = ?)
+            OR (scope = 2 AND target IN (SELECT sid FROM user_sessions WHERE uid = ?)))
+        ORDER BY timestamp DESC
+        LIMIT ?
+        "#, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(now)
+            .bind(oid)
+            .bind(uid)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
@@ -0,0 +1,58 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::pin::Pin;
+use crate::repository::db;
+
+//置顶消息，已置顶时返回false
+pub async fn save(pin: &Pin) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &db::backend().insert_ignore("INTO pinned_messages (sid, mid, uid, uname, pintime) VALUES (?, ?, ?, ?, ?)"), |sql, conn| {
+        sqlx::query(sql)
+            .bind(pin.sid)
+            .bind(pin.mid)
+            .bind(pin.uid)
+            .bind(&pin.uname)
+            .bind(pin.pintime)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//取消置顶，消息未置顶时返回false
+pub async fn delete(sid: i64, mid: i64) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "DELETE FROM pinned_messages WHERE sid = ? AND mid = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(sid)
+            .bind(mid)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//按置顶时间倒序查询会话的置顶消息
+pub async fn find_by_session(sid: i64) -> Result<Vec<Pin>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT sid, mid, uid, uname, pintime FROM pinned_messages WHERE sid = ? ORDER BY pintime DESC, mid DESC", |sql, conn| {
+        sqlx::query_as::<_, Pin>(sql)
+            .bind(sid)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+pub async fn count(sid: i64) -> Result<i64> {
+    let connection = db::connection().await?;
+    let result: (i64,) = dispatch!(connection, "SELECT COUNT(*) FROM pinned_messages WHERE sid = ?", |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(sid)
+            .fetch_one(conn)
+            .await?
+    });
+    Ok(result.0)
+}
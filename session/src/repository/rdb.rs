@@ -1,15 +1,14 @@
 use std::sync::OnceLock;
 
 use anyhow::Result;
-use dotenv;
+use core::config::RedisConfig;
 
 use core::response::ApiErr;
 
 pub static POOL: OnceLock<redis::Client> = OnceLock::new();
 
-pub async fn init(){
-    let connstring = dotenv::var("REDIS_URL").expect("请设置REDIS连接地址REDIS_URL");
-    let client = redis::Client::open(connstring).expect("连接redis失败......");
+pub async fn init(config: &RedisConfig){
+    let client = redis::Client::open(config.url.as_str()).expect("连接redis失败......");
     POOL.set(client).expect("保存Redis客户端失败")
 }
 
@@ -0,0 +1,33 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::session::ReadCursor;
+use crate::repository::db;
+
+//已读位置只前进不后退，按(时间戳, 消息id)比较，并发标记时以最靠后的位置为准
+pub async fn advance(cursor: &ReadCursor) -> Result<()> {
+    let connection = db::connection().await?;
+    //MySQL按顺序执行赋值，时间戳以消息id是否已更新为准
+    let sql = db::backend().pick(r#"
+        INSERT INTO read_cursors (uid, sid, last_read_message_id, last_read_timestamp) VALUES (?, ?, ?, ?)
+        ON DUPLICATE KEY UPDATE
+            last_read_message_id = IF((last_read_timestamp, last_read_message_id) < (VALUES(last_read_timestamp), VALUES(last_read_message_id)),
+                VALUES(last_read_message_id), last_read_message_id),
+            last_read_timestamp = IF(last_read_message_id = VALUES(last_read_message_id), VALUES(last_read_timestamp), last_read_timestamp)
+        "#, r#"
+        INSERT INTO read_cursors (uid, sid, last_read_message_id, last_read_timestamp) VALUES (?, ?, ?, ?)
+        ON CONFLICT (uid, sid) DO UPDATE SET
+            last_read_message_id = excluded.last_read_message_id,
+            last_read_timestamp = excluded.last_read_timestamp
+        WHERE (read_cursors.last_read_timestamp, read_cursors.last_read_message_id) < (excluded.last_read_timestamp, excluded.last_read_message_id)
+        "#);
+    dispatch!(connection, sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(cursor.uid)
+            .bind(cursor.sid)
+            .bind(cursor.last_read_message_id)
+            .bind(cursor.last_read_timestamp)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
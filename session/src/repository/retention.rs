@@ -0,0 +1,90 @@
+use anyhow::Result;
+use core::db::Backend;
+use core::dispatch;
+use sqlx::{Connection, Database, Encode, QueryBuilder, Type};
+use crate::model::session::Session;
+use crate::repository::db;
+
+//按ID顺序分页遍历全部会话
+pub async fn find_sessions(after: i64, limit: u32) -> Result<Vec<Session>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM sessions WHERE id > ? ORDER BY id LIMIT ?", |sql, conn| {
+        sqlx::query_as::<_, Session>(sql)
+            .bind(after)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//查询会话中早于指定时间戳的消息ID
+pub async fn find_expired(sid: i64, before: i64, limit: u32) -> Result<Vec<i64>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT id FROM messages WHERE session = ? AND timestamp < ? ORDER BY timestamp LIMIT ?", |sql, conn| {
+        sqlx::query_scalar::<_, i64>(sql)
+            .bind(sid)
+            .bind(before)
+            .bind(limit as i64)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//删除消息，archive为true时先复制到归档表，返回删除的行数
+pub async fn purge(ids: &[i64], archive: bool) -> Result<u64> {
+    if ids.is_empty() {
+        return Ok(0);
+    }
+    let backend = db::backend();
+    let connection = db::connection().await?;
+    let deleted = dispatch!(connection, |conn| {
+        let mut tx = conn.begin().await?;
+        if archive {
+            let mut builder = QueryBuilder::new(backend.pick("INSERT IGNORE", "INSERT"));
+            builder.push(
+                " INTO messages_archive (id, sender, session, mtype, content, timestamp, uname, createtime, payload, version) \
+                 SELECT id, sender, session, mtype, content, timestamp, uname, createtime, payload, version FROM messages WHERE id IN ("
+            );
+            push_ids(&mut builder, ids);
+            if backend != Backend::MySql {
+                builder.push(" ON CONFLICT DO NOTHING");
+            }
+            builder.build().execute(&mut *tx).await?;
+        }
+        let mut builder = QueryBuilder::new("DELETE FROM messages WHERE id IN (");
+        push_ids(&mut builder, ids);
+        let deleted = builder.build().execute(&mut *tx).await?.rows_affected();
+        //被清理的消息同时取消置顶
+        let mut builder = QueryBuilder::new("DELETE FROM pinned_messages WHERE mid IN (");
+        push_ids(&mut builder, ids);
+        builder.build().execute(&mut *tx).await?;
+        tx.commit().await?;
+        deleted
+    });
+    Ok(deleted)
+}
+
+fn push_ids<DB: Database>(builder: &mut QueryBuilder<DB>, ids: &[i64])
+where
+    for<'a> i64: Encode<'a, DB> + Type<DB>,
+{
+    let mut separated = builder.separated(", ");
+    for id in ids {
+        separated.push_bind(*id);
+    }
+    separated.push_unseparated(")");
+}
+
+pub async fn update_days(sid: i64, days: Option<i32>) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "UPDATE sessions SET retention_days = ? WHERE id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(days)
+            .bind(sid)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use anyhow::Result;
+use core::db::Backend;
+use core::dispatch;
+use crate::model::scheduled::Scheduled;
+use crate::repository::db;
+
+const COLUMNS: &str = "id, sid, sender, uname, content, send_at, status";
+
+pub async fn save(scheduled: &Scheduled) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "INSERT INTO scheduled_messages (id, sid, sender, uname, content, send_at, status) VALUES (?, ?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(scheduled.id)
+            .bind(scheduled.sid)
+            .bind(scheduled.sender)
+            .bind(&scheduled.uname)
+            .bind(&scheduled.content)
+            .bind(scheduled.send_at)
+            .bind(scheduled.status as i32)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn find_by_id(sid: i64, id: i64) -> Result<Option<Scheduled>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &format!("SELECT {} FROM scheduled_messages WHERE sid = ? AND id = ?", COLUMNS), |sql, conn| {
+        sqlx::query_as::<_, Scheduled>(sql)
+            .bind(sid)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//查询用户在会话中待发送的定时消息，按发送时间排序
+pub async fn find_pending(sid: i64, sender: i64) -> Result<Vec<Scheduled>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &format!("SELECT {} FROM scheduled_messages WHERE sid = ? AND sender = ? AND status = ? ORDER BY send_at, id", COLUMNS), |sql, conn| {
+        sqlx::query_as::<_, Scheduled>(sql)
+            .bind(sid)
+            .bind(sender)
+            .bind(Scheduled::PENDING as i32)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//修改待发送的定时消息，消息已被认领或不是待发送状态时返回false
+pub async fn update(scheduled: &Scheduled) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE scheduled_messages SET content = ?, send_at = ? WHERE id = ? AND status = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(&scheduled.content)
+            .bind(scheduled.send_at)
+            .bind(scheduled.id)
+            .bind(Scheduled::PENDING as i32)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//更新状态，只有处于from状态的消息会被更新
+pub async fn transit(id: i64, from: i8, to: i8) -> Result<bool> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE scheduled_messages SET status = ? WHERE id = ? AND status = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(to as i32)
+            .bind(id)
+            .bind(from as i32)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result == 1)
+}
+
+//以一次原子更新认领到期的待发送消息并返回，多个实例同时调度时每条消息只会被一个批次认领
+pub async fn claim(batch: i64, now: i64, limit: u32) -> Result<Vec<Scheduled>> {
+    //SQLite和Postgres的UPDATE不支持ORDER BY和LIMIT，通过子查询选择要认领的消息，外层再次校验状态避免并发认领
+    let backend = db::backend();
+    let sql = backend.pick(
+        "UPDATE scheduled_messages SET status = ?, claimed_by = ?, claimed_at = ? WHERE status = ? AND send_at <= ? ORDER BY send_at LIMIT ?",
+        "UPDATE scheduled_messages SET status = ?, claimed_by = ?, claimed_at = ? WHERE status = ? AND id IN (SELECT id FROM scheduled_messages WHERE status = ? AND send_at <= ? ORDER BY send_at LIMIT ?)",
+    );
+    let connection = db::connection().await?;
+    dispatch!(connection, sql, |sql, conn| {
+        let mut query = sqlx::query(sql)
+            .bind(Scheduled::SENDING as i32)
+            .bind(batch)
+            .bind(now)
+            .bind(Scheduled::PENDING as i32);
+        if backend != Backend::MySql {
+            query = query.bind(Scheduled::PENDING as i32);
+        }
+        query.bind(now)
+            .bind(limit as i64)
+            .execute(conn)
+            .await?;
+    });
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, &format!("SELECT {} FROM scheduled_messages WHERE claimed_by = ? AND status = ? ORDER BY send_at, id", COLUMNS), |sql, conn| {
+        sqlx::query_as::<_, Scheduled>(sql)
+            .bind(batch)
+            .bind(Scheduled::SENDING as i32)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//释放认领超时的消息，用于认领后实例异常退出的情况，返回释放的数量
+pub async fn release(before: i64) -> Result<u64> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "UPDATE scheduled_messages SET status = ?, claimed_by = NULL, claimed_at = NULL WHERE status = ? AND claimed_at < ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(Scheduled::PENDING as i32)
+            .bind(Scheduled::SENDING as i32)
+            .bind(before)
+            .execute(conn)
+            .await?
+            .rows_affected()
+    });
+    Ok(result)
+}
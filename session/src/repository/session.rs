@@ -1,69 +1,185 @@
 use anyhow::Result;
-use crate::model::session::{Session, UserSession};
+use core::dispatch;
+use core::request::Position;
+use sqlx::{Connection, QueryBuilder};
+use crate::model::session::{Session, SessionSummary, UserSession};
 use crate::repository::db;
 
 pub async fn create_session(session: &Session) -> Result<Session> {
-    let mut connection = db::connection().await?;
+    let connection = db::connection().await?;
     // 插入会话记录
-    sqlx::query("INSERT INTO sessions (id, name, creator, createtime, updatetime) VALUES (?, ?, ?, ?, ?)")
-        .bind(&session.id)
-        .bind(&session.name)
-        .bind(&session.creator)
-        .bind(&session.createtime)
-        .bind(&session.updatetime)
-        .execute(connection.as_mut())
-        .await?;
-        
+    dispatch!(connection, "INSERT INTO sessions (id, name, creator, createtime, updatetime, oid, kind) VALUES (?, ?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(session.id)
+            .bind(&session.name)
+            .bind(session.creator)
+            .bind(session.createtime)
+            .bind(session.updatetime)
+            .bind(session.oid)
+            .bind(session.kind as i32)
+            .execute(conn)
+            .await?;
+    });
     Ok(session.clone())
 }
 
 pub async fn create_user_session(user_session: &UserSession) -> Result<UserSession> {
-    let mut connection = db::connection().await?;
-    
+    let connection = db::connection().await?;
     // 插入用户会话关联记录
-    sqlx::query("INSERT INTO user_sessions (id, uid, uname, sid, role, jointime) VALUES (?, ?, ?, ?, ?, ?)")
-        .bind(&user_session.id)
-        .bind(&user_session.uid)
-        .bind(&user_session.uname)
-        .bind(&user_session.sid)
-        .bind(&user_session.role)
-        .bind(&user_session.jointime)
-        .execute(connection.as_mut())
-        .await?;
-        
+    dispatch!(connection, "INSERT INTO user_sessions (id, uid, uname, sid, role, jointime) VALUES (?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(user_session.id)
+            .bind(user_session.uid)
+            .bind(&user_session.uname)
+            .bind(user_session.sid)
+            .bind(user_session.role as i32)
+            .bind(user_session.jointime)
+            .execute(conn)
+            .await?;
+    });
     Ok(user_session.clone())
 }
 
 pub async fn find_session_by_id(id: i64) -> Result<Option<Session>> {
-    let mut connection = db::connection().await?;
-    let result = sqlx::query_as::<_, Session>("SELECT * FROM sessions WHERE id = ?")
-        .bind(id)
-        .fetch_optional(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM sessions WHERE id = ?", |sql, conn| {
+        sqlx::query_as::<_, Session>(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
     Ok(result)
 }
 
-pub async fn find_sessions_by_user(user_id: i64) -> Result<Vec<Session>> {
-    let mut connection = db::connection().await?;
-    let result = sqlx::query_as::<_, Session>(
+//查询用户所在的会话及每个会话中他人发送的未读消息数
+pub async fn find_sessions_by_user(user_id: i64) -> Result<Vec<SessionSummary>> {
+    let connection = db::connection().await?;
+    let sql = format!(
         r#"
-        SELECT s.* FROM sessions s 
-        JOIN user_sessions us ON s.id = us.sid 
+        SELECT s.*, (
+            SELECT COUNT(1) FROM messages m
+            WHERE m.session = s.id AND m.sender <> us.uid
+            AND (rc.uid IS NULL
+                OR m.timestamp > rc.last_read_timestamp
+                OR (m.timestamp = rc.last_read_timestamp AND m.id > rc.last_read_message_id))
+        ) AS unread, COALESCE(ss.notify, 0) AS notify, ss.muted_until
+        FROM sessions s
+        JOIN user_sessions us ON s.id = us.sid
+        LEFT JOIN read_cursors rc ON rc.uid = us.uid AND rc.sid = s.id
+        LEFT JOIN session_settings ss ON ss.uid = us.uid AND ss.sid = s.id
         WHERE us.uid = ?
-        ORDER BY s.updatetime DESC
-        "#
-    )
-    .bind(user_id)
-    .fetch_all(connection.as_mut())
-    .await?;
+        ORDER BY COALESCE(s.last_message_at, {}) DESC, s.id DESC
+        "#,
+        db::backend().epoch_millis("s.createtime")
+    );
+    let result = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as::<_, SessionSummary>(sql)
+            .bind(user_id)
+            .fetch_all(conn)
+            .await?
+    });
     Ok(result)
 }
 
+//更新会话的最后一条消息，只接受比当前更新的位置，避免乱序消息回退
+pub async fn update_last_message(sid: i64, position: Position) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, r#"
+        UPDATE sessions SET last_message_id = ?, last_message_at = ?
+        WHERE id = ? AND (last_message_at IS NULL
+            OR last_message_at < ?
+            OR (last_message_at = ? AND last_message_id < ?))
+        "#, |sql, conn| {
+        sqlx::query(sql)
+            .bind(position.id)
+            .bind(position.timestamp)
+            .bind(sid)
+            .bind(position.timestamp)
+            .bind(position.timestamp)
+            .bind(position.id)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
 pub async fn find_user_sessions_by_session(sid: i64) -> Result<Vec<UserSession>> {
-    let mut connection = db::connection().await?;
-    let result = sqlx::query_as::<_, UserSession>("SELECT * FROM user_sessions WHERE sid = ?")
-        .bind(sid)
-        .fetch_all(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM user_sessions WHERE sid = ?", |sql, conn| {
+        sqlx::query_as::<_, UserSession>(sql)
+            .bind(sid)
+            .fetch_all(conn)
+            .await?
+    });
     Ok(result)
 }
+
+pub async fn find_user_session(sid: i64, uid: i64) -> Result<Option<UserSession>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT * FROM user_sessions WHERE sid = ? AND uid = ?", |sql, conn| {
+        sqlx::query_as::<_, UserSession>(sql)
+            .bind(sid)
+            .bind(uid)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+pub async fn delete_user_session(sid: i64, uid: i64) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "DELETE FROM user_sessions WHERE sid = ? AND uid = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(sid)
+            .bind(uid)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn update_role(sid: i64, uid: i64, role: i8) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "UPDATE user_sessions SET role = ? WHERE sid = ? AND uid = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(role as i32)
+            .bind(sid)
+            .bind(uid)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+pub async fn update_name(sid: i64, name: &str) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, "UPDATE sessions SET name = ? WHERE id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(name)
+            .bind(sid)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
+
+//转让群主，新群主同时成为管理员
+pub async fn transfer_owner(sid: i64, uid: i64) -> Result<()> {
+    let connection = db::connection().await?;
+    dispatch!(connection, |conn| {
+        let mut tx = conn.begin().await?;
+        QueryBuilder::new("UPDATE sessions SET creator = ").push_bind(uid)
+            .push(" WHERE id = ").push_bind(sid)
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        QueryBuilder::new("UPDATE user_sessions SET role = ").push_bind(UserSession::ADMIN as i32)
+            .push(" WHERE sid = ").push_bind(sid)
+            .push(" AND uid = ").push_bind(uid)
+            .build()
+            .execute(&mut *tx)
+            .await?;
+        tx.commit().await?
+    });
+    Ok(())
+}
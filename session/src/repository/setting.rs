@@ -0,0 +1,47 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::model::session::SessionSetting;
+use crate::repository::db;
+
+pub async fn find(uid: i64, sid: i64) -> Result<Option<SessionSetting>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT uid, sid, notify, muted_until FROM session_settings WHERE uid = ? AND sid = ?", |sql, conn| {
+        sqlx::query_as::<_, SessionSetting>(sql)
+            .bind(uid)
+            .bind(sid)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+//会话中所有成员保存过的通知设置
+pub async fn find_by_session(sid: i64) -> Result<Vec<SessionSetting>> {
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, "SELECT uid, sid, notify, muted_until FROM session_settings WHERE sid = ?", |sql, conn| {
+        sqlx::query_as::<_, SessionSetting>(sql)
+            .bind(sid)
+            .fetch_all(conn)
+            .await?
+    });
+    Ok(result)
+}
+
+pub async fn save(setting: &SessionSetting) -> Result<()> {
+    let connection = db::connection().await?;
+    let sql = db::backend().upsert(
+        "INTO session_settings (uid, sid, notify, muted_until) VALUES (?, ?, ?, ?)",
+        &["uid", "sid"],
+        &["notify", "muted_until"],
+    );
+    dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(setting.uid)
+            .bind(setting.sid)
+            .bind(setting.notify as i32)
+            .bind(setting.muted_until)
+            .execute(conn)
+            .await?;
+    });
+    Ok(())
+}
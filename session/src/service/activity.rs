@@ -0,0 +1,51 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use core::request::Position;
+use crate::model::message::Message;
+use crate::repository::session as session_repo;
+
+//会话最后一条消息的刷新间隔，间隔内同一会话的多条消息只写一次库
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+//待刷新的会话最后一条消息，key为会话ID
+static PENDING: OnceLock<Mutex<HashMap<i64, Position>>> = OnceLock::new();
+
+fn pending() -> &'static Mutex<HashMap<i64, Position>> {
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+//启动后台刷新任务
+pub fn init() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(FLUSH_INTERVAL);
+        loop {
+            interval.tick().await;
+            flush().await;
+        }
+    });
+}
+
+//记录消息，只保留每个会话中最新的位置
+pub fn record(message: &Message) {
+    let position = Position::new(message.timestamp, message.id);
+    merge(&mut pending().lock().unwrap(), message.session, position);
+}
+
+pub fn merge(pending: &mut HashMap<i64, Position>, sid: i64, position: Position) {
+    pending.entry(sid)
+        .and_modify(|p| *p = (*p).max(position))
+        .or_insert(position);
+}
+
+pub async fn flush() {
+    let batch = std::mem::take(&mut *pending().lock().unwrap());
+    for (sid, position) in batch {
+        if let Err(e) = session_repo::update_last_message(sid, position).await {
+            log::error!("Failed to update last message of session {}: {}", sid, e);
+            //写入失败的放回队列，下次刷新时重试
+            merge(&mut pending().lock().unwrap(), sid, position);
+        }
+    }
+}
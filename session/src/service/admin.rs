@@ -0,0 +1,40 @@
+use anyhow::Result;
+use core::api::types::message::{DeadLetter, DeadLetterRequest};
+use core::auth::{User, SUPER_ADMIN};
+use core::queue::DEAD_LETTER_SUFFIX;
+use core::response::ApiErr;
+use crate::common::config;
+use crate::queue::dead_letter;
+
+const DEFAULT_LIMIT: u32 = 20;
+const MAX_LIMIT: u32 = 100;
+
+fn authorize(claims: &User) -> Result<()> {
+    if claims.id != SUPER_ADMIN {
+        return Err(ApiErr::Bad(403, "只有超级管理员可以操作死信队列".to_string()).into());
+    }
+    Ok(())
+}
+
+//未指定队列时使用当前服务的死信队列，只允许操作死信队列
+fn target(request: &DeadLetterRequest) -> Result<(String, u32)> {
+    let queue = request.queue.clone()
+        .filter(|q| !q.trim().is_empty())
+        .unwrap_or_else(|| config::get().rabbitmq.dead_letter_queue.clone());
+    if !queue.ends_with(DEAD_LETTER_SUFFIX) {
+        return Err(ApiErr::Bad(400, format!("只能操作死信队列(以{}结尾)", DEAD_LETTER_SUFFIX)).into());
+    }
+    Ok((queue, request.limit.unwrap_or(DEFAULT_LIMIT).clamp(1, MAX_LIMIT)))
+}
+
+pub async fn get_dead_letters(claims: &User, request: DeadLetterRequest) -> Result<Vec<DeadLetter>> {
+    authorize(claims)?;
+    let (queue, limit) = target(&request)?;
+    dead_letter::list(&queue, limit).await
+}
+
+pub async fn replay_dead_letters(claims: &User, request: DeadLetterRequest) -> Result<u32> {
+    authorize(claims)?;
+    let (queue, limit) = target(&request)?;
+    dead_letter::replay(&queue, limit).await
+}
@@ -0,0 +1,60 @@
+use core::api::types::session::{ChannelResponse, SessionResponse};
+use core::auth::User;
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use crate::model::session::{Role, Session, UserSession};
+use crate::repository::{channel as channel_repo, session as session_repo};
+use crate::service::member::{load, sync};
+
+//列出用户所在组织的频道
+pub async fn get_channels(claims: &User) -> Result<Vec<ChannelResponse>> {
+    Ok(channel_repo::find_by_org(claims.oid, claims.id).await?
+        .into_iter()
+        .map(|c| ChannelResponse {
+            id: c.session.id,
+            name: c.session.name,
+            subscribers: c.subscribers,
+            subscribed: c.subscribed,
+        })
+        .collect())
+}
+
+async fn load_channel(sid: i64) -> Result<Session> {
+    session_repo::find_session_by_id(sid).await?
+        .filter(|s| s.kind == Session::CHANNEL)
+        .ok_or(ApiErr::Bad(404, "频道不存在".to_string()).into())
+}
+
+//订阅频道，只能订阅所在组织的频道，已订阅时直接返回
+//订阅者众多，订阅变更只同步成员，不发送系统消息
+pub async fn subscribe(claims: &User, sid: i64) -> Result<SessionResponse> {
+    let session = load_channel(sid).await?;
+    if session.oid != claims.oid {
+        return Err(ApiErr::Bad(403, "只能订阅所在组织的频道".to_string()).into());
+    }
+    if session_repo::find_user_session(sid, claims.id).await?.is_none() {
+        session_repo::create_user_session(&UserSession {
+            id: snowflaker::next_id()? as i64,
+            uid: claims.id,
+            uname: claims.name.clone(),
+            sid,
+            role: UserSession::MEMBER,
+            jointime: Utc::now().naive_utc(),
+        }).await?;
+        sync(&session).await?;
+    }
+    Ok(session.into())
+}
+
+pub async fn unsubscribe(claims: &User, sid: i64) -> Result<()> {
+    load_channel(sid).await?;
+    let (session, me) = load(sid, claims.id).await?;
+    if me.role_in(&session) == Role::Owner {
+        return Err(ApiErr::Bad(400, "频道所有者不能退订，请先转让频道".to_string()).into());
+    }
+    session_repo::delete_user_session(sid, claims.id).await?;
+    sync(&session).await?;
+    Ok(())
+}
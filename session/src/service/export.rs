@@ -0,0 +1,272 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use bytes::Bytes;
+use chrono::{DateTime, Utc};
+use futures::Stream;
+use serde::Serialize;
+use core::request::Position;
+use core::response::ApiErr;
+use crate::model::message::Message;
+use crate::model::session::Session;
+use crate::repository::message as message_repo;
+use crate::service::message;
+use core::api::types::message::Payload;
+
+//每次从数据库读取的消息数
+const PAGE_SIZE: u32 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Markdown,
+    Html,
+}
+
+impl Format {
+    pub fn parse(format: Option<&str>) -> Result<Self> {
+        match format.map(|f| f.trim().to_lowercase()).as_deref() {
+            None | Some("") | Some("json") => Ok(Self::Json),
+            Some("md") | Some("markdown") => Ok(Self::Markdown),
+            Some("html") => Ok(Self::Html),
+            Some(f) => Err(ApiErr::Bad(400, format!("不支持的导出格式: {}，可选json、md、html", f)).into()),
+        }
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Json => "application/json; charset=utf-8",
+            Self::Markdown => "text/markdown; charset=utf-8",
+            Self::Html => "text/html; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Json => "json",
+            Self::Markdown => "md",
+            Self::Html => "html",
+        }
+    }
+}
+
+//消息中引用的文件，available为false时文件已过期或不存在
+#[derive(Debug, Clone, Serialize)]
+pub struct FileRef {
+    pub id: i64,
+    pub name: String,
+    pub size: Option<i64>,
+    pub exp: Option<String>,
+    pub path: Option<String>,
+    pub available: bool,
+}
+
+//导出的单条消息
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    pub id: i64,
+    pub sender: i64,
+    pub uname: String,
+    pub mtype: i32,
+    pub timestamp: i64,
+    pub time: String,
+    pub content: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<FileRef>,
+}
+
+pub fn time(timestamp: i64) -> String {
+    DateTime::<Utc>::from_timestamp_millis(timestamp)
+        .map(|t| t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        .unwrap_or_default()
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+pub fn header(format: Format, session: &Session, exported_at: &str) -> String {
+    match format {
+        Format::Json => format!(
+            "{{\"session\":{{\"id\":{},\"name\":{}}},\"exported_at\":{},\"messages\":[",
+            session.id,
+            serde_json::to_string(&session.name).unwrap_or_default(),
+            serde_json::to_string(exported_at).unwrap_or_default(),
+        ),
+        Format::Markdown => format!("# {}\n\n> 导出时间: {}\n\n", session.name, exported_at),
+        Format::Html => format!(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{name}</title>\n<style>\
+            body{{font-family:sans-serif;max-width:960px;margin:auto;}}\
+            .message{{padding:6px 0;border-bottom:1px solid #eee;}}\
+            .sender{{font-weight:bold;}}\
+            time{{color:#888;margin-left:8px;font-size:0.9em;}}\
+            .content{{white-space:pre-wrap;}}\
+            .summary{{color:#888;}}\
+            .error{{color:#c00;}}\
+            </style>\n</head>\n<body>\n<h1>{name}</h1>\n<p>导出时间: {exported_at}</p>\n",
+            name = escape(&session.name),
+            exported_at = escape(exported_at),
+        ),
+    }
+}
+
+pub fn entry(format: Format, entry: &Entry, first: bool) -> String {
+    match format {
+        Format::Json => format!(
+            "{}\n{}",
+            if first { "" } else { "," },
+            serde_json::to_string(entry).unwrap_or_default(),
+        ),
+        Format::Markdown => {
+            let content = match &entry.file {
+                Some(FileRef { name, path: Some(path), size, .. }) => format!("[文件] [{}]({}) ({} bytes)", name, path, size.unwrap_or_default()),
+                Some(FileRef { name, .. }) => format!("[文件] {} (已过期)", name),
+                None => entry.content.clone(),
+            };
+            format!("**{}** · {}\n\n{}\n\n", entry.uname, entry.time, content)
+        }
+        Format::Html => {
+            let content = match &entry.file {
+                Some(FileRef { name, path: Some(path), size, .. }) => format!(
+                    "[文件] <a href=\"{}\">{}</a> ({} bytes)",
+                    escape(path), escape(name), size.unwrap_or_default(),
+                ),
+                Some(FileRef { name, .. }) => format!("[文件] {} (已过期)", escape(name)),
+                None => escape(&entry.content),
+            };
+            format!(
+                "<div class=\"message\"><span class=\"sender\">{}</span><time>{}</time><div class=\"content\">{}</div></div>\n",
+                escape(&entry.uname), escape(&entry.time), content,
+            )
+        }
+    }
+}
+
+//结尾注明导出的消息数，complete为false时表示读取消息失败，导出的内容不完整
+pub fn footer(format: Format, count: u64, complete: bool) -> String {
+    let summary = if complete {
+        format!("共导出{}条消息", count)
+    } else {
+        format!("导出中断，仅导出了前{}条消息", count)
+    };
+    match format {
+        Format::Json => format!("\n],\"count\":{},\"complete\":{}}}\n", count, complete),
+        Format::Markdown => format!("---\n\n> {}\n", summary),
+        Format::Html => format!(
+            "<p class=\"{}\">{}</p>\n</body>\n</html>\n",
+            if complete { "summary" } else { "error" }, summary,
+        ),
+    }
+}
+
+enum Stage {
+    Header,
+    Body,
+    Done,
+}
+
+struct Exporter {
+    session: Session,
+    format: Format,
+    token: String,
+    after: Position,
+    //已导出的消息数
+    count: u64,
+    stage: Stage,
+    //同一文件只查询一次
+    files: HashMap<i64, FileRef>,
+}
+
+impl Exporter {
+    async fn file(&mut self, name: String, id: i64) -> FileRef {
+        if let Some(file) = self.files.get(&id) {
+            return file.clone();
+        }
+        let token = self.token.clone();
+        let blob = tokio::task::spawn_blocking(move || core::api::client::blob::get_blob(&token, id)).await;
+        let file = match blob {
+            Ok(Ok(blob)) => FileRef { id, name: blob.name, size: Some(blob.size), exp: blob.exp, path: Some(blob.path), available: true },
+            Ok(Err(e)) => {
+                log::warn!("Failed to resolve file {} for export: {}", id, e);
+                FileRef { id, name, size: None, exp: None, path: None, available: false }
+            }
+            Err(e) => {
+                log::error!("Failed to resolve file {} for export: {}", id, e);
+                FileRef { id, name, size: None, exp: None, path: None, available: false }
+            }
+        };
+        self.files.insert(id, file.clone());
+        file
+    }
+
+    async fn entry(&mut self, m: Message) -> Entry {
+        let file = match message::payload(&m) {
+            Some(Payload::Blob(blob)) => Some(self.file(blob.name, blob.id as i64).await),
+            _ => None,
+        };
+        Entry {
+            id: m.id,
+            sender: m.sender,
+            uname: m.uname,
+            mtype: m.mtype,
+            timestamp: m.timestamp,
+            time: time(m.timestamp),
+            content: m.content,
+            file,
+        }
+    }
+
+    async fn next(&mut self) -> Result<Option<Bytes>> {
+        match self.stage {
+            Stage::Header => {
+                self.stage = Stage::Body;
+                Ok(Some(Bytes::from(header(self.format, &self.session, &time(Utc::now().timestamp_millis())))))
+            }
+            Stage::Body => {
+                //响应已经开始输出，读取失败时以结尾标明导出不完整，不能再返回错误状态码
+                let page = match message_repo::find_by_session(self.session.id, None, Some(self.after), PAGE_SIZE).await {
+                    Ok(page) => page,
+                    Err(e) => {
+                        log::error!("Failed to export session {}: {}", self.session.id, e);
+                        self.stage = Stage::Done;
+                        return Ok(Some(Bytes::from(footer(self.format, self.count, false))));
+                    }
+                };
+                let end = (page.len() as u32) < PAGE_SIZE;
+                let mut chunk = String::new();
+                for m in page {
+                    self.after = Position::new(m.timestamp, m.id);
+                    let e = self.entry(m).await;
+                    chunk.push_str(&entry(self.format, &e, self.count == 0));
+                    self.count += 1;
+                }
+                if end {
+                    chunk.push_str(&footer(self.format, self.count, true));
+                    self.stage = Stage::Done;
+                }
+                Ok(Some(Bytes::from(chunk)))
+            }
+            Stage::Done => Ok(None),
+        }
+    }
+}
+
+//按时间正序分页读取会话的全部消息，边读边输出
+pub fn export(session: Session, format: Format, token: String) -> impl Stream<Item = Result<Bytes>> {
+    let exporter = Exporter {
+        session,
+        format,
+        token,
+        after: Position::new(i64::MIN, i64::MIN),
+        count: 0,
+        stage: Stage::Header,
+        files: HashMap::new(),
+    };
+    futures::stream::try_unfold(exporter, |mut exporter| async move {
+        Ok(exporter.next().await?.map(|chunk| (chunk, exporter)))
+    })
+}
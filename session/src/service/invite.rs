@@ -0,0 +1,127 @@
+use core::api::types::session::{CreateInviteRequest, InviteResponse, SessionResponse};
+use core::auth::User;
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use crate::model::invite::{Invite, InviteClaims};
+use crate::model::session::{Role, Session, UserSession};
+use crate::repository::{invite as invite_repo, session as session_repo};
+use crate::service::member::{changed, load, require};
+
+//邀请链接默认有效期7天，最长30天
+const DEFAULT_TTL_SECS: u64 = 7 * 24 * 3600;
+const MAX_TTL_SECS: u64 = 30 * 24 * 3600;
+
+//校验邀请请求，返回有效期(秒)及最多使用次数
+pub fn check(payload: &CreateInviteRequest) -> Result<(u64, Option<u32>)> {
+    let ttl = payload.ttl_secs.unwrap_or(DEFAULT_TTL_SECS);
+    if ttl == 0 || ttl > MAX_TTL_SECS {
+        return Err(ApiErr::Bad(400, format!("邀请有效期应为1-{}秒", MAX_TTL_SECS)).into());
+    }
+    if payload.max_uses == Some(0) || payload.max_uses.is_some_and(|m| m > i32::MAX as u32) {
+        return Err(ApiErr::Bad(400, "邀请使用次数不合法".to_string()).into());
+    }
+    Ok((ttl, payload.max_uses))
+}
+
+//邀请是否仍可使用
+pub fn usable(invite: &Invite, now: i64) -> Result<()> {
+    if invite.revoked {
+        return Err(ApiErr::Bad(410, "邀请链接已被撤销".to_string()).into());
+    }
+    if invite.exp <= now {
+        return Err(ApiErr::Bad(410, "邀请链接已过期".to_string()).into());
+    }
+    if invite.max_uses.is_some_and(|m| invite.uses >= m) {
+        return Err(ApiErr::Bad(410, "邀请链接使用次数已达上限".to_string()).into());
+    }
+    Ok(())
+}
+
+//单聊会话的id由两个成员的id计算得出，不支持邀请
+pub fn direct(sid: i64, members: &[UserSession]) -> bool {
+    members.len() == 2
+        && core::api::client::session::calc_session_id(members[0].uid, members[1].uid) as i64 == sid
+}
+
+//签发邀请令牌，exp为邀请记录的过期时间戳(毫秒)
+pub fn token(iid: i64, sid: i64, exp: i64) -> Result<String> {
+    core::auth::sign(&InviteClaims { iid, sid, exp: exp / 1000, aud: InviteClaims::AUDIENCE.to_string() })
+}
+
+//校验邀请令牌的签名、有效期及用途
+pub fn open(token: &str) -> Result<InviteClaims> {
+    core::auth::open(token, InviteClaims::AUDIENCE)
+        .map_err(|_| ApiErr::Bad(404, "邀请链接无效".to_string()).into())
+}
+
+pub async fn create_invite(claims: &User, sid: i64, payload: CreateInviteRequest) -> Result<InviteResponse> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以创建邀请链接")?;
+    let (ttl, max_uses) = check(&payload)?;
+    if session.kind != Session::GROUP {
+        return Err(ApiErr::Bad(400, "只有群聊支持邀请链接".to_string()).into());
+    }
+    let id = snowflaker::next_id()? as i64;
+    let now = Utc::now();
+    let exp = now.timestamp_millis() + (ttl * 1000) as i64;
+    let invite = Invite {
+        id,
+        sid,
+        token: token(id, sid, exp)?,
+        creator: claims.id,
+        uname: claims.name.clone(),
+        max_uses: max_uses.map(|m| m as i32),
+        uses: 0,
+        exp,
+        revoked: false,
+        createtime: now.naive_utc(),
+    };
+    invite_repo::save(&invite).await?;
+    Ok(invite.into())
+}
+
+pub async fn get_invites(claims: &User, sid: i64) -> Result<Vec<InviteResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以查看邀请链接")?;
+    Ok(invite_repo::find_by_session(sid).await?.into_iter().map(|i| i.into()).collect())
+}
+
+pub async fn revoke(claims: &User, sid: i64, id: i64) -> Result<()> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以撤销邀请链接")?;
+    if !invite_repo::revoke(sid, id).await? {
+        return Err(ApiErr::Bad(404, "邀请链接不存在或已撤销".to_string()).into());
+    }
+    Ok(())
+}
+
+//通过邀请令牌加入会话，已是成员时直接返回会话且不占用次数
+pub async fn join(claims: &User, token: &str) -> Result<SessionResponse> {
+    let invite_claims = open(token)?;
+    let invite = invite_repo::find_by_id(invite_claims.iid).await?
+        .filter(|i| i.sid == invite_claims.sid && i.token == token)
+        .ok_or(ApiErr::Bad(404, "邀请链接无效".to_string()))?;
+    let session = session_repo::find_session_by_id(invite.sid).await?
+        .ok_or(ApiErr::Bad(404, "会话不存在".to_string()))?;
+    if session_repo::find_user_session(session.id, claims.id).await?.is_some() {
+        return Ok(session.into());
+    }
+    let now = Utc::now();
+    usable(&invite, now.timestamp_millis())?;
+    //并发加入时以数据库的原子更新为准
+    if !invite_repo::consume(invite.id, now.timestamp_millis()).await? {
+        return Err(ApiErr::Bad(410, "邀请链接已失效".to_string()).into());
+    }
+    session_repo::create_user_session(&UserSession {
+        id: snowflaker::next_id()? as i64,
+        uid: claims.id,
+        uname: claims.name.clone(),
+        sid: session.id,
+        role: UserSession::MEMBER,
+        jointime: now.naive_utc(),
+    }).await?;
+    changed(&session, &format!("{} 通过 {} 的邀请链接加入了会话", claims.name, invite.uname), vec![]).await;
+    Ok(session.into())
+}
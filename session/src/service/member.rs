@@ -0,0 +1,234 @@
+use core::api::types::contact::ContactResponse;
+use core::api::types::session::{MemberResponse, SessionResponse, SessionState};
+use core::auth::{User, SUPER_ADMIN};
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use pubchat::core::message::{chrt, message::Content, ChatType, Chrt, Members, Message, Text, Type};
+use crate::model::session::{Role, Session, UserSession};
+use crate::queue;
+use crate::repository::{membership, session as session_repo};
+use crate::service::{moderation, setting};
+
+const MAX_NAME_LEN: usize = 100;
+
+//加载会话及操作者的成员身份，非成员返回403
+pub(crate) async fn load(sid: i64, uid: i64) -> Result<(Session, UserSession)> {
+    let session = session_repo::find_session_by_id(sid).await?
+        .ok_or(ApiErr::Bad(404, "会话不存在".to_string()))?;
+    let member = session_repo::find_user_session(sid, uid).await?
+        .ok_or(ApiErr::Bad(403, "不是该会话的成员".to_string()))?;
+    Ok((session, member))
+}
+
+async fn load_target(session: &Session, uid: i64) -> Result<UserSession> {
+    session_repo::find_user_session(session.id, uid).await?
+        .ok_or(ApiErr::Bad(404, "该用户不是会话成员".to_string()).into())
+}
+
+pub(crate) fn require(actual: Role, required: Role, message: &str) -> Result<()> {
+    if actual < required {
+        return Err(ApiErr::Bad(403, message.to_string()).into());
+    }
+    Ok(())
+}
+
+//管理员只能移除普通成员，群主可以移除除自己以外的任何人
+pub fn can_remove(actor: Role, target: Role) -> bool {
+    actor >= Role::Admin && actor > target
+}
+
+pub async fn get_members(claims: &User, sid: i64) -> Result<Vec<MemberResponse>> {
+    let (session, _) = load(sid, claims.id).await?;
+    members(&session).await
+}
+
+async fn members(session: &Session) -> Result<Vec<MemberResponse>> {
+    let members = session_repo::find_user_sessions_by_session(session.id).await?;
+    Ok(members.into_iter().map(|m| MemberResponse {
+        id: m.uid,
+        role: m.role_in(session) as i8,
+        name: m.uname,
+    }).collect())
+}
+
+pub async fn add_members(claims: &User, sid: i64, contacts: Vec<ContactResponse>) -> Result<Vec<MemberResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以添加成员")?;
+    let now = Utc::now().naive_utc();
+    let mut added = vec![];
+    for contact in contacts {
+        if session_repo::find_user_session(sid, contact.id).await?.is_some() {
+            continue;
+        }
+        let user_session = UserSession {
+            id: snowflaker::next_id()? as i64,
+            uid: contact.id,
+            uname: contact.name,
+            sid,
+            role: UserSession::MEMBER,
+            jointime: now,
+        };
+        session_repo::create_user_session(&user_session).await?;
+        added.push(user_session.uname);
+    }
+    if !added.is_empty() {
+        changed(&session, &format!("{} 邀请 {} 加入了会话", claims.name, added.join("、")), vec![]).await;
+    }
+    members(&session).await
+}
+
+pub async fn remove_member(claims: &User, sid: i64, uid: i64) -> Result<Vec<MemberResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    let target = load_target(&session, uid).await?;
+    if uid == claims.id {
+        return Err(ApiErr::Bad(400, "不能移除自己，请使用退出会话".to_string()).into());
+    }
+    if !can_remove(me.role_in(&session), target.role_in(&session)) {
+        return Err(ApiErr::Bad(403, "无权移除该成员".to_string()).into());
+    }
+    session_repo::delete_user_session(sid, uid).await?;
+    //被移除的成员也需要收到通知
+    changed(&session, &format!("{} 将 {} 移出了会话", claims.name, target.uname), vec![uid as u64]).await;
+    members(&session).await
+}
+
+pub async fn leave(claims: &User, sid: i64) -> Result<()> {
+    let (session, me) = load(sid, claims.id).await?;
+    if me.role_in(&session) == Role::Owner {
+        let members = session_repo::find_user_sessions_by_session(sid).await?;
+        if members.len() > 1 {
+            return Err(ApiErr::Bad(400, "群主请先转让会话后再退出".to_string()).into());
+        }
+    }
+    session_repo::delete_user_session(sid, claims.id).await?;
+    changed(&session, &format!("{} 退出了会话", claims.name), vec![]).await;
+    Ok(())
+}
+
+pub async fn rename(claims: &User, sid: i64, name: String) -> Result<SessionResponse> {
+    let (mut session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以修改会话名称")?;
+    let name = name.trim().to_string();
+    if name.is_empty() || name.chars().count() > MAX_NAME_LEN {
+        return Err(ApiErr::Bad(400, format!("会话名称长度应为1-{}个字符", MAX_NAME_LEN)).into());
+    }
+    session_repo::update_name(sid, &name).await?;
+    changed(&session, &format!("{} 将会话名称修改为 {}", claims.name, name), vec![]).await;
+    session.name = name;
+    Ok(session.into())
+}
+
+pub async fn set_admin(claims: &User, sid: i64, uid: i64, admin: bool) -> Result<Vec<MemberResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Owner, "只有群主可以设置管理员")?;
+    let target = load_target(&session, uid).await?;
+    if target.role_in(&session) == Role::Owner {
+        return Err(ApiErr::Bad(400, "不能修改群主的身份".to_string()).into());
+    }
+    let role = if admin { UserSession::ADMIN } else { UserSession::MEMBER };
+    if target.role != role {
+        session_repo::update_role(sid, uid, role).await?;
+        let text = if admin {
+            format!("{} 将 {} 设为管理员", claims.name, target.uname)
+        } else {
+            format!("{} 取消了 {} 的管理员身份", claims.name, target.uname)
+        };
+        changed(&session, &text, vec![]).await;
+    }
+    members(&session).await
+}
+
+pub async fn transfer(claims: &User, sid: i64, uid: i64) -> Result<Vec<MemberResponse>> {
+    let (mut session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Owner, "只有群主可以转让会话")?;
+    let target = load_target(&session, uid).await?;
+    if uid == claims.id {
+        return Err(ApiErr::Bad(400, "不能转让给自己".to_string()).into());
+    }
+    session_repo::transfer_owner(sid, uid).await?;
+    session.creator = uid;
+    changed(&session, &format!("{} 将群主转让给 {}", claims.name, target.uname), vec![]).await;
+    members(&session).await
+}
+
+//会话的最新成员列表、会话类型及管理员
+async fn snapshot(session: &Session) -> Result<Members> {
+    let all = session_repo::find_user_sessions_by_session(session.id).await?;
+    let admins = all.iter()
+        .filter(|m| m.role_in(session) >= Role::Admin)
+        .map(|m| m.uid as u64)
+        .collect();
+    Ok(Members {
+        session: session.id as u64,
+        members: all.into_iter().map(|m| m.uid as u64).collect(),
+        kind: session.kind as i32,
+        admins,
+        oid: session.oid as u64,
+    })
+}
+
+//同步最新成员列表、会话类型及管理员给连接服务，返回当前成员
+pub async fn sync(session: &Session) -> Result<Vec<u64>> {
+    membership::evict(session.id).await;
+    let members = snapshot(session).await?;
+    let current = members.members.clone();
+    let message = Message {
+        id: snowflaker::next_id()?,
+        ts: Utc::now().timestamp_millis() as u64,
+        mtype: Type::Memb as i32,
+        content: Some(Content::Members(members)),
+    };
+    queue::publish(&message, "member").await?;
+    Ok(current)
+}
+
+//连接服务的成员缓存未命中时(如重启后)按会话加载成员、通知设置及所属组织的审核规则，只加载请求的会话
+//连接服务以超级管理员身份请求
+pub async fn state(claims: &User, sid: i64) -> Result<SessionState> {
+    if claims.id != SUPER_ADMIN {
+        return Err(ApiErr::Bad(403, "只有超级管理员可以加载会话状态".to_string()).into());
+    }
+    let session = session_repo::find_session_by_id(sid).await?
+        .ok_or(ApiErr::Bad(404, "会话不存在".to_string()))?;
+    Ok(SessionState {
+        members: snapshot(&session).await?,
+        moderation: moderation::rules(session.oid).await?,
+        settings: setting::find_by_session(sid).await?,
+    })
+}
+
+//成员变更后同步成员列表，并向会话发送系统消息
+//extra为除当前成员外需要收到消息的用户，如被移出的成员
+//频道的订阅者可能很多，系统消息只携带extra，由连接服务按同步过的成员投递
+pub(crate) async fn changed(session: &Session, text: &str, extra: Vec<u64>) {
+    let result: Result<()> = async {
+        let members = sync(session).await?;
+        let receivers = if session.kind == Session::CHANNEL {
+            extra
+        } else {
+            members.into_iter().chain(extra).collect()
+        };
+        let now = Utc::now().timestamp_millis() as u64;
+        let message = Message {
+            id: snowflaker::next_id()?,
+            ts: now,
+            mtype: Type::Chrt as i32,
+            content: Some(Content::Chrt(Chrt {
+                sender: 0,
+                uname: "SYSTEM".to_string(),
+                session: session.id as u64,
+                receivers,
+                ctype: ChatType::System as i32,
+                ts: now,
+                message: Some(chrt::Message::Text(Text { text: text.to_string() })),
+                flag: None,
+            })),
+        };
+        queue::publish(&message, "member").await
+    }.await;
+    if let Err(e) = result {
+        log::error!("Failed to publish member change of session {}: {}", session.id, e);
+    }
+}
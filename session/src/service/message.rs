@@ -1,23 +1,231 @@
+use std::time::Duration;
+
 use anyhow::Result;
+use core::lease;
 use crate::model::message::Message;
-use crate::repository::message;
-use core::api::types::message::Message as MessageResponse;
+use crate::repository::{db, message};
+use core::api::types::message::{Message as MessageResponse, MessageHit, Payload, SearchMessageRequest, PAYLOAD_VERSION};
+use pubchat::core::message::{Blob, Text};
+use core::request::{Cursor, Position};
+use core::response::{ApiErr, Cursors};
+
+
+pub async fn save_message(message: &Message) -> Result<bool> {
+    message::save(message).await
+}
 
+//按游标分页获取会话消息，返回按时间倒序排列的消息及向前、向后翻页的游标
+pub async fn get_messages_by_session(session: i64, cursor: Cursor) -> Result<(Vec<MessageResponse>, Cursors)> {
+    let before = parse(cursor.before.as_deref(), true)?;
+    let after = parse(cursor.after.as_deref(), false)?;
+    let ps = cursor.ps();
+    //after单独使用时向更新的消息翻页，查询结果为正序，其余情况为倒序
+    let forward = after.is_some() && before.is_none();
+    //多查一条用于判断查询方向上是否还有更多消息
+    let mut m = message::find_by_session(session, before, after, ps + 1).await?;
+    let more = m.len() > ps as usize;
+    m.truncate(ps as usize);
+    if forward {
+        m.reverse();
+    }
+    let (Some(newest), Some(oldest)) = (m.first(), m.last()) else {
+        return Ok((vec![], Cursors::default()));
+    };
+    let newest = Position::new(newest.timestamp, newest.id);
+    let oldest = Position::new(oldest.timestamp, oldest.id);
+    //查询方向以外的另一侧单独查询是否存在消息，未指定游标时返回的是最新一页
+    let older = if forward { exists(session, Some(oldest), None).await? } else { more };
+    let newer = if forward { more } else { before.is_some() && exists(session, None, Some(newest)).await? };
+    let cursors = Cursors {
+        before: older.then(|| oldest.encode()),
+        after: newer.then(|| newest.encode()),
+    };
+    Ok((m.into_iter().map(response).collect(), cursors))
+}
 
-pub async fn save_message(message: Message) -> Result<()> {
-    message::save(&message).await
+async fn exists(session: i64, before: Option<Position>, after: Option<Position>) -> Result<bool> {
+    Ok(!message::find_by_session(session, before, after, 1).await?.is_empty())
 }
 
-pub async fn get_messages_by_session(session: i64, limit: u32) -> Result<Vec<MessageResponse>> {
-    let m = message::find_by_session(session, limit).await?;
-    Ok(m.into_iter().map(|m| MessageResponse {
+pub fn response(m: Message) -> MessageResponse {
+    MessageResponse {
         id: m.id,
         sender: m.sender,
         receiver: 0,
         session: m.session,
         mtype: m.mtype,
+        payload: payload(&m),
+        version: m.version as i32,
         uname: m.uname,
         content: m.content,
         timestamp: m.timestamp,
-    }).collect())
+    }
+}
+
+//每批转换的旧消息数
+const UPGRADE_BATCH: u32 = 500;
+//转换任务的租约名称，多实例部署时只有一个实例执行转换
+const UPGRADE_LEASE: &str = "message_upgrade";
+//每批转换前续期，实例在转换过程中退出时租约到期后其他实例可以继续转换
+const UPGRADE_TTL: Duration = Duration::from_secs(300);
+//旧格式文件消息的前缀，见pubchat::core::message::chrt::Message的Display实现
+const FILE_PREFIX: &str = "[File] ";
+
+pub fn encode(payload: &Payload) -> Result<String> {
+    Ok(serde_json::to_string(payload)?)
+}
+
+//读取消息的结构化内容，旧数据从content文本中解析
+pub fn payload(m: &Message) -> Option<Payload> {
+    match (m.version, &m.payload) {
+        (0, _) | (_, None) => Some(legacy(&m.content)),
+        (_, Some(payload)) => match serde_json::from_str(payload) {
+            Ok(payload) => Some(payload),
+            Err(e) => {
+                log::error!("Invalid payload of message {}: {}", m.id, e);
+                None
+            }
+        },
+    }
+}
+
+//解析旧格式的消息内容，文件消息格式为`[File] 文件名 (size: 大小, exp: 过期时间, download id: base62编码的ID)`
+pub fn legacy(content: &str) -> Payload {
+    let blob = || -> Option<Blob> {
+        let rest = content.strip_prefix(FILE_PREFIX)?;
+        let name_end = rest.rfind(" (size: ")?;
+        let fields = rest[name_end + " (size: ".len()..].strip_suffix(')')?;
+        let (size, fields) = fields.split_once(", exp: ")?;
+        let (exp, id) = fields.rsplit_once(", download id: ")?;
+        Some(Blob {
+            id: base62::decode(id).ok()? as u64,
+            name: rest[..name_end].to_string(),
+            size: size.to_string(),
+            exp: if exp == "never" { None } else { Some(exp.to_string()) },
+        })
+    };
+    match blob() {
+        Some(blob) => Payload::Blob(blob),
+        None => Payload::Text(Text { text: content.to_string() }),
+    }
+}
+
+//将消息表及归档表中旧格式的消息分批转换为结构化内容，其他实例正在转换时跳过
+pub async fn upgrade() -> Result<u64> {
+    let pool = db::get().await?;
+    let mut total = 0;
+    for table in message::TABLES {
+        loop {
+            if !lease::acquire(pool, UPGRADE_LEASE, UPGRADE_TTL).await? {
+                log::info!("Legacy messages are being upgraded by another instance, skipped");
+                return Ok(total);
+            }
+            let messages = message::find_legacy(table, UPGRADE_BATCH).await?;
+            if messages.is_empty() {
+                break;
+            }
+            for m in messages {
+                message::update_payload(table, m.id, &encode(&legacy(&m.content))?, PAYLOAD_VERSION as i8).await?;
+                total += 1;
+            }
+        }
+    }
+    lease::release(pool, UPGRADE_LEASE).await?;
+    if total > 0 {
+        log::info!("Upgraded {} legacy messages to payload version {}", total, PAYLOAD_VERSION);
+    }
+    Ok(total)
+}
+
+fn parse(cursor: Option<&str>, before: bool) -> Result<Option<Position>> {
+    match cursor {
+        None => Ok(None),
+        Some(c) if c.trim().is_empty() => Ok(None),
+        Some(c) => Position::parse(c, before)
+            .map(Some)
+            .ok_or(ApiErr::Bad(400, format!("游标格式不合法: {}", c)).into()),
+    }
+}
+
+//ngram解析器默认的分词长度，短于该长度的关键词无法命中全文索引
+const MIN_TERM_LEN: usize = 2;
+const MAX_QUERY_LEN: usize = 100;
+const SNIPPET_RADIUS: usize = 20;
+
+pub async fn search_messages(uid: i64, request: SearchMessageRequest) -> Result<(Vec<MessageHit>, i64)> {
+    if request.q.chars().count() > MAX_QUERY_LEN {
+        return Err(ApiErr::Bad(400, format!("搜索关键词不能超过{}个字符", MAX_QUERY_LEN)).into());
+    }
+    let terms = terms(&request.q);
+    if terms.is_empty() {
+        return Err(ApiErr::Bad(400, format!("搜索关键词至少需要{}个字符", MIN_TERM_LEN)).into());
+    }
+    if let (Some(from), Some(to)) = (request.from, request.to) && from > to {
+        return Err(ApiErr::Bad(400, "开始时间不能晚于结束时间".to_string()).into());
+    }
+    let ps = request.ps.unwrap_or(Cursor::DEFAULT_SIZE).clamp(1, Cursor::MAX_SIZE);
+    let pn = request.pn.unwrap_or(1).max(1);
+    let (m, count) = message::search(uid, &boolean_query(&terms), &terms, &request, ps, (pn - 1) * ps).await?;
+    Ok((m.into_iter().map(|m| MessageHit {
+        id: m.id,
+        sender: m.sender,
+        session: m.session,
+        mtype: m.mtype,
+        snippet: snippet(&m.content, &terms, SNIPPET_RADIUS),
+        uname: m.uname,
+        timestamp: m.timestamp,
+    }).collect(), count))
+}
+
+//拆分关键词，去掉全文检索的布尔运算符
+pub fn terms(q: &str) -> Vec<String> {
+    q.split_whitespace()
+        .map(|t| t.chars().filter(|c| !"+-<>()~*\"@".contains(*c)).collect::<String>())
+        .filter(|t| t.chars().count() >= MIN_TERM_LEN)
+        .collect()
+}
+
+//所有关键词都需命中，每个关键词按短语匹配
+pub fn boolean_query(terms: &[String]) -> String {
+    terms.iter().map(|t| format!("+\"{}\"", t)).collect::<Vec<_>>().join(" ")
+}
+
+//截取第一个命中关键词附近的内容，命中部分以**包裹
+pub fn snippet(content: &str, terms: &[String], radius: usize) -> String {
+    let lower = |c: &char| c.to_lowercase().next().unwrap_or(*c);
+    let chars: Vec<char> = content.chars().collect();
+    let folded: Vec<char> = chars.iter().map(lower).collect();
+    let mut hit = vec![false; chars.len()];
+    for term in terms {
+        let term: Vec<char> = term.chars().map(|c| lower(&c)).collect();
+        let mut i = 0;
+        while !term.is_empty() && i + term.len() <= folded.len() {
+            if folded[i..i + term.len()] == term[..] {
+                hit[i..i + term.len()].fill(true);
+                i += term.len();
+            } else {
+                i += 1;
+            }
+        }
+    }
+    let first = hit.iter().position(|h| *h).unwrap_or(0);
+    let start = first.saturating_sub(radius);
+    let end = (first + radius * 2).min(chars.len());
+    let mut result = String::new();
+    if start > 0 {
+        result.push_str("...");
+    }
+    for i in start..end {
+        if hit[i] && (i == start || !hit[i - 1]) {
+            result.push_str("**");
+        }
+        result.push(chars[i]);
+        if hit[i] && (i + 1 == end || !hit[i + 1]) {
+            result.push_str("**");
+        }
+    }
+    if end < chars.len() {
+        result.push_str("...");
+    }
+    result
 }
@@ -0,0 +1,155 @@
+use core::api::types::moderation::{Moderator, ReviewRequest, ReviewResponse, RuleRequest, RuleResponse};
+use core::auth::{User, SUPER_ADMIN};
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use pubchat::core::message::{message::Content, Chrt, Message, Moderation, Rule, Type};
+use crate::model::moderation::{ModerationRule, Review};
+use crate::queue;
+use crate::repository::{message as message_repo, moderation as moderation_repo, pin as pin_repo, session as session_repo};
+use crate::service::member::changed;
+
+const MAX_RULES: i64 = 200;
+const DEFAULT_LIMIT: u32 = 100;
+
+fn authorize(claims: &User) -> Result<()> {
+    if claims.id != SUPER_ADMIN {
+        return Err(ApiErr::Bad(403, "只有超级管理员可以管理内容审核".to_string()).into());
+    }
+    Ok(())
+}
+
+//校验规则能否编译，返回规范化后的规则
+pub fn check(payload: &RuleRequest) -> Result<Rule> {
+    let rule = Rule {
+        id: 0,
+        kind: payload.kind as i32,
+        pattern: payload.pattern.trim().to_string(),
+        max_len: payload.max_len,
+        action: payload.action as i32,
+    };
+    Moderator::new(std::slice::from_ref(&rule))?;
+    Ok(rule)
+}
+
+//组织当前的审核器，规则在写入时已校验，这里跳过无法编译的规则
+pub async fn moderator(oid: i64) -> Result<Moderator> {
+    let rules = moderation_repo::find_rules(oid).await?;
+    Ok(Moderator::lenient(&rules.iter().map(Rule::from).collect::<Vec<_>>()))
+}
+
+pub async fn get_rules(claims: &User, oid: i64) -> Result<Vec<RuleResponse>> {
+    authorize(claims)?;
+    Ok(moderation_repo::find_rules(oid).await?.into_iter().map(|r| r.into()).collect())
+}
+
+pub async fn add_rule(claims: &User, payload: RuleRequest) -> Result<RuleResponse> {
+    authorize(claims)?;
+    let rule = check(&payload)?;
+    if moderation_repo::count_rules(payload.oid).await? >= MAX_RULES {
+        return Err(ApiErr::Bad(400, format!("每个组织最多配置{}条审核规则", MAX_RULES)).into());
+    }
+    let rule = ModerationRule {
+        id: snowflaker::next_id()? as i64,
+        oid: payload.oid,
+        kind: rule.kind as i8,
+        pattern: rule.pattern,
+        max_len: rule.max_len,
+        action: rule.action as i8,
+        creator: claims.id,
+    };
+    moderation_repo::save_rule(&rule).await?;
+    sync(rule.oid).await;
+    Ok(rule.into())
+}
+
+pub async fn delete_rule(claims: &User, id: i64) -> Result<()> {
+    authorize(claims)?;
+    let rule = moderation_repo::find_rule(id).await?
+        .ok_or(ApiErr::Bad(404, "审核规则不存在".to_string()))?;
+    if moderation_repo::delete_rule(id).await? {
+        sync(rule.oid).await;
+    }
+    Ok(())
+}
+
+//组织的全部审核规则
+pub(crate) async fn rules(oid: i64) -> Result<Moderation> {
+    let rules = moderation_repo::find_rules(oid).await?;
+    Ok(Moderation {
+        oid: oid as u64,
+        rules: rules.iter().map(Rule::from).collect(),
+    })
+}
+
+async fn publish(oid: i64) -> Result<()> {
+    let message = Message {
+        id: snowflaker::next_id()?,
+        ts: Utc::now().timestamp_millis() as u64,
+        mtype: Type::Modr as i32,
+        content: Some(Content::Moderation(rules(oid).await?)),
+    };
+    queue::publish(&message, "moderation").await
+}
+
+//将组织的全部规则同步给连接服务，失败不影响规则保存，连接服务缓存未命中时会重新加载
+pub(crate) async fn sync(oid: i64) {
+    if let Err(e) = publish(oid).await {
+        log::error!("Failed to publish moderation rules of org {}: {}", oid, e);
+    }
+}
+
+//记录连接服务标记为需要人工审核的消息
+pub async fn flag(id: i64, chat: &Chrt, reason: &str) -> Result<()> {
+    let oid = session_repo::find_session_by_id(chat.session as i64).await?
+        .map(|s| s.oid)
+        .unwrap_or_default();
+    let review = Review {
+        mid: id,
+        sid: chat.session as i64,
+        oid,
+        sender: chat.sender as i64,
+        uname: chat.uname.clone(),
+        content: chat.message.as_ref().map(|m| m.to_string()).unwrap_or_default(),
+        reason: reason.to_string(),
+        status: Review::PENDING,
+        reviewer: None,
+        timestamp: chat.ts as i64,
+    };
+    moderation_repo::save_review(&review).await?;
+    Ok(())
+}
+
+pub async fn get_reviews(claims: &User, payload: ReviewRequest) -> Result<Vec<ReviewResponse>> {
+    authorize(claims)?;
+    let status = payload.status.unwrap_or(Review::PENDING);
+    Ok(moderation_repo::find_reviews(payload.oid, status, DEFAULT_LIMIT).await?.into_iter().map(|r| r.into()).collect())
+}
+
+async fn resolve(claims: &User, mid: i64, status: i8) -> Result<Review> {
+    authorize(claims)?;
+    let review = moderation_repo::find_review(mid).await?
+        .ok_or(ApiErr::Bad(404, "审核记录不存在".to_string()))?;
+    if !moderation_repo::resolve(mid, status, claims.id).await? {
+        return Err(ApiErr::Bad(409, "该消息已审核".to_string()).into());
+    }
+    Ok(review)
+}
+
+//审核通过，消息保持不变
+pub async fn approve(claims: &User, mid: i64) -> Result<()> {
+    resolve(claims, mid, Review::APPROVED).await?;
+    Ok(())
+}
+
+//删除违规消息，并通知会话成员
+pub async fn remove(claims: &User, mid: i64) -> Result<()> {
+    let review = resolve(claims, mid, Review::REMOVED).await?;
+    message_repo::delete(review.sid, mid).await?;
+    pin_repo::delete(review.sid, mid).await?;
+    if let Some(session) = session_repo::find_session_by_id(review.sid).await? {
+        changed(&session, &format!("{} 的一条消息因违反内容规则已被删除", review.uname), vec![]).await;
+    }
+    Ok(())
+}
@@ -0,0 +1,104 @@
+use core::api::types::notice::{CreateNoticeRequest, NoticeResponse};
+use core::auth::{User, SUPER_ADMIN};
+use core::response::ApiErr;
+
+use anyhow::Result;
+use pubchat::core::message::{message::Content, Message, Notice as NoticeFrame, Scope, Severity, Type};
+use crate::model::notice::Notice;
+use crate::queue;
+use crate::repository::{notice as notice_repo, session as session_repo};
+
+const MAX_TEXT_LEN: usize = 1000;
+const MAX_ACTIVE_NOTICES: u32 = 20;
+
+//校验公告请求，返回规范化后的级别与范围
+pub fn check(payload: &CreateNoticeRequest, now: i64) -> Result<(Severity, Scope)> {
+    let severity = Severity::try_from(payload.severity)
+        .map_err(|_| ApiErr::Bad(400, "公告级别不合法".to_string()))?;
+    let scope = Scope::try_from(payload.scope)
+        .map_err(|_| ApiErr::Bad(400, "公告范围不合法".to_string()))?;
+    if payload.text.trim().is_empty() {
+        return Err(ApiErr::Bad(400, "公告内容不能为空".to_string()).into());
+    }
+    if payload.text.chars().count() > MAX_TEXT_LEN {
+        return Err(ApiErr::Bad(400, format!("公告内容不能超过{}个字符", MAX_TEXT_LEN)).into());
+    }
+    if scope != Scope::All && payload.target <= 0 {
+        return Err(ApiErr::Bad(400, "请指定公告的组织或会话".to_string()).into());
+    }
+    if payload.exp.is_some_and(|exp| exp <= now) {
+        return Err(ApiErr::Bad(400, "过期时间必须晚于当前时间".to_string()).into());
+    }
+    Ok((severity, scope))
+}
+
+//全员及组织公告只允许超级管理员发布，会话公告允许会话管理员发布
+async fn authorize(claims: &User, scope: Scope, target: i64) -> Result<Vec<u64>> {
+    match scope {
+        Scope::All | Scope::Org => {
+            if claims.id != SUPER_ADMIN {
+                return Err(ApiErr::Bad(403, "无权发布该范围的公告".to_string()).into());
+            }
+            Ok(vec![])
+        }
+        Scope::Session => {
+            let members = session_repo::find_user_sessions_by_session(target).await?;
+            if members.is_empty() {
+                return Err(ApiErr::Bad(404, "会话不存在".to_string()).into());
+            }
+            let admin = members.iter().any(|m| m.uid == claims.id && m.role == 1);
+            if !admin && claims.id != SUPER_ADMIN {
+                return Err(ApiErr::Bad(403, "只有会话管理员可以发布会话公告".to_string()).into());
+            }
+            Ok(members.iter().map(|m| m.uid as u64).collect())
+        }
+    }
+}
+
+pub async fn publish(claims: &User, payload: CreateNoticeRequest) -> Result<NoticeResponse> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let (severity, scope) = check(&payload, now)?;
+    let target = if scope == Scope::All { 0 } else { payload.target };
+    let receivers = authorize(claims, scope, target).await?;
+    let notice = Notice {
+        id: snowflaker::next_id()? as i64,
+        severity: severity as i8,
+        scope: scope as i8,
+        target,
+        text: payload.text.trim().to_string(),
+        exp: payload.exp,
+        sender: claims.id,
+        uname: claims.name.clone(),
+        timestamp: now,
+    };
+    //先持久化，保证之后上线的用户也能拉取到
+    notice_repo::save(&notice).await?;
+    let message = Message {
+        id: notice.id as u64,
+        ts: now as u64,
+        mtype: Type::Noti as i32,
+        content: Some(Content::Notice(NoticeFrame {
+            id: notice.id as u64,
+            severity: severity as i32,
+            scope: scope as i32,
+            target: target as u64,
+            text: notice.text.clone(),
+            exp: notice.exp.map(|e| e as u64),
+            sender: claims.id as u64,
+            uname: claims.name.clone(),
+            ts: now as u64,
+            receivers,
+        })),
+    };
+    if let Err(e) = queue::publish(&message, "notice").await {
+        //已持久化的公告仍可被拉取，推送失败只记录日志
+        log::error!("Failed to publish notice {}: {}", notice.id, e);
+    }
+    Ok(notice.into())
+}
+
+pub async fn get_active_notices(claims: &User) -> Result<Vec<NoticeResponse>> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let notices = notice_repo::find_active(claims.id, claims.oid, now, MAX_ACTIVE_NOTICES).await?;
+    Ok(notices.into_iter().map(|n| n.into()).collect())
+}
@@ -0,0 +1,69 @@
+use core::api::types::message::PinResponse;
+use core::auth::User;
+use core::response::ApiErr;
+
+use std::collections::HashMap;
+use anyhow::Result;
+use chrono::Utc;
+use crate::model::pin::Pin;
+use crate::model::session::Role;
+use crate::repository::{message as message_repo, pin as pin_repo};
+use crate::service::member::{changed, load, require};
+use crate::service::message::response;
+use crate::service::session::preview;
+
+//每个会话最多置顶的消息数
+const MAX_PINS: i64 = 50;
+
+//按置顶时间倒序列出会话的置顶消息，已被清理的消息不再返回
+pub async fn get_pins(sid: i64) -> Result<Vec<PinResponse>> {
+    let pins = pin_repo::find_by_session(sid).await?;
+    let ids: Vec<i64> = pins.iter().map(|p| p.mid).collect();
+    let mut messages: HashMap<i64, _> = message_repo::find_by_ids(&ids).await?
+        .into_iter()
+        .map(|m| (m.id, m))
+        .collect();
+    Ok(pins.into_iter().filter_map(|pin| {
+        messages.remove(&pin.mid).map(|m| PinResponse {
+            message: response(m),
+            pinned_by: pin.uid,
+            pinned_name: pin.uname,
+            pinned_at: pin.pintime,
+        })
+    }).collect())
+}
+
+pub async fn pin(claims: &User, sid: i64, mid: i64) -> Result<Vec<PinResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以置顶消息")?;
+    let message = message_repo::find_by_id(sid, mid).await?
+        .ok_or(ApiErr::Bad(404, "消息不存在".to_string()))?;
+    if pin_repo::count(sid).await? >= MAX_PINS {
+        return Err(ApiErr::Bad(400, format!("每个会话最多置顶{}条消息", MAX_PINS)).into());
+    }
+    let pinned = pin_repo::save(&Pin {
+        sid,
+        mid,
+        uid: claims.id,
+        uname: claims.name.clone(),
+        pintime: Utc::now().timestamp_millis(),
+    }).await?;
+    if pinned {
+        changed(&session, &format!("{} 置顶了消息: {}", claims.name, preview(message).content), vec![]).await;
+    }
+    get_pins(sid).await
+}
+
+pub async fn unpin(claims: &User, sid: i64, mid: i64) -> Result<Vec<PinResponse>> {
+    let (session, me) = load(sid, claims.id).await?;
+    require(me.role_in(&session), Role::Admin, "只有管理员可以取消置顶")?;
+    if !pin_repo::delete(sid, mid).await? {
+        return Err(ApiErr::Bad(404, "该消息未置顶".to_string()).into());
+    }
+    let text = match message_repo::find_by_id(sid, mid).await? {
+        Some(message) => format!("{} 取消置顶了消息: {}", claims.name, preview(message).content),
+        None => format!("{} 取消置顶了一条消息", claims.name),
+    };
+    changed(&session, &text, vec![]).await;
+    get_pins(sid).await
+}
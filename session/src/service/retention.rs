@@ -0,0 +1,151 @@
+use std::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use chrono::Utc;
+use core::api::types::session::RetentionResponse;
+use core::auth::User;
+use core::config::RetentionConfig;
+use core::lease;
+use core::response::ApiErr;
+use crate::common::config;
+use crate::model::session::{Role, Session};
+use crate::repository::{db, retention as retention_repo};
+use crate::service::member;
+
+//会话可设置的最大保留天数
+const MAX_DAYS: u32 = 36500;
+//每次遍历的会话数
+const SESSION_PAGE: u32 = 100;
+const DAY_MILLIS: i64 = 24 * 60 * 60 * 1000;
+//清理任务的租约名称
+const PURGE_LEASE: &str = "retention_purge";
+
+//清理任务的统计指标
+static RUNS: AtomicU64 = AtomicU64::new(0);
+static PURGED: AtomicU64 = AtomicU64::new(0);
+static ARCHIVED: AtomicU64 = AtomicU64::new(0);
+static FAILURES: AtomicU64 = AtomicU64::new(0);
+static LAST_RUN: AtomicI64 = AtomicI64::new(0);
+
+//会话实际生效的保留天数及其来源，优先级：会话设置 > 组织配置 > 全局默认
+//组织配置了保留天数时，会话设置只能缩短，不能延长或设为永久保留
+pub fn effective(session: &Session, config: &RetentionConfig) -> (u32, &'static str) {
+    let org = config.org(session.oid);
+    if let Some(days) = session.retention_days {
+        let days = days.max(0) as u32;
+        return match org {
+            Some(limit) if limit > 0 && (days == 0 || days > limit) => (limit, "org"),
+            _ => (days, "session"),
+        };
+    }
+    if let Some(days) = org {
+        return (days, "org");
+    }
+    (config.days, "default")
+}
+
+//启动后台清理任务，多实例部署时只有持有租约的实例清理
+//租约为两个执行间隔，持有者每次执行时续期，异常退出后由其他实例接替
+pub fn init(config: &'static RetentionConfig) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(Duration::from_secs(config.interval_secs));
+        let ttl = Duration::from_secs(config.interval_secs * 2);
+        loop {
+            interval.tick().await;
+            let result = async {
+                if lease::acquire(db::get().await?, PURGE_LEASE, ttl).await? {
+                    purge(config).await?;
+                }
+                anyhow::Ok(())
+            }.await;
+            if let Err(e) = result {
+                FAILURES.fetch_add(1, Ordering::Relaxed);
+                log::error!("Failed to purge expired messages: {}", e);
+            }
+        }
+    });
+}
+
+//遍历全部会话，分批删除或归档过期消息
+pub async fn purge(config: &RetentionConfig) -> Result<u64> {
+    let start = Instant::now();
+    let now = Utc::now().timestamp_millis();
+    let mut total = 0;
+    let mut after = 0;
+    loop {
+        let sessions = retention_repo::find_sessions(after, SESSION_PAGE).await?;
+        let Some(last) = sessions.last() else {
+            break;
+        };
+        after = last.id;
+        for session in &sessions {
+            let (days, _) = effective(session, config);
+            if days == 0 {
+                continue;
+            }
+            let before = now - days as i64 * DAY_MILLIS;
+            loop {
+                let ids = retention_repo::find_expired(session.id, before, config.batch_size).await?;
+                let purged = retention_repo::purge(&ids, config.archive).await?;
+                PURGED.fetch_add(purged, Ordering::Relaxed);
+                if config.archive {
+                    ARCHIVED.fetch_add(purged, Ordering::Relaxed);
+                }
+                total += purged;
+                if (ids.len() as u32) < config.batch_size {
+                    break;
+                }
+            }
+        }
+    }
+    RUNS.fetch_add(1, Ordering::Relaxed);
+    LAST_RUN.store(now, Ordering::Relaxed);
+    log::info!("retention purge finished: purged={} archive={} elapsed={:?}", total, config.archive, start.elapsed());
+    Ok(total)
+}
+
+//Prometheus文本格式的清理指标
+pub fn metrics() -> String {
+    let metrics = [
+        ("pubchat_retention_runs_total", "counter", "Completed retention purge runs", RUNS.load(Ordering::Relaxed) as i64),
+        ("pubchat_retention_failures_total", "counter", "Failed retention purge runs", FAILURES.load(Ordering::Relaxed) as i64),
+        ("pubchat_retention_purged_total", "counter", "Messages deleted by retention purge", PURGED.load(Ordering::Relaxed) as i64),
+        ("pubchat_retention_archived_total", "counter", "Messages archived by retention purge", ARCHIVED.load(Ordering::Relaxed) as i64),
+        ("pubchat_retention_last_run_timestamp_ms", "gauge", "Start time of the last completed purge run", LAST_RUN.load(Ordering::Relaxed)),
+    ];
+    metrics.iter()
+        .map(|(name, kind, help, value)| format!("# HELP {name} {help}\n# TYPE {name} {kind}\n{name} {value}\n"))
+        .collect()
+}
+
+fn response(session: &Session) -> RetentionResponse {
+    let (effective, source) = effective(session, &config::get().retention);
+    RetentionResponse {
+        days: session.retention_days.map(|d| d.max(0) as u32),
+        effective,
+        source: source.to_string(),
+    }
+}
+
+pub async fn get_retention(claims: &User, sid: i64) -> Result<RetentionResponse> {
+    let (session, _) = member::load(sid, claims.id).await?;
+    Ok(response(&session))
+}
+
+pub async fn set_retention(claims: &User, sid: i64, days: Option<u32>) -> Result<RetentionResponse> {
+    let (mut session, me) = member::load(sid, claims.id).await?;
+    member::require(me.role_in(&session), Role::Admin, "只有管理员可以设置消息保留时间")?;
+    if let Some(days) = days && days > MAX_DAYS {
+        return Err(ApiErr::Bad(400, format!("保留天数不能超过{}天", MAX_DAYS)).into());
+    }
+    session.retention_days = days.map(|d| d as i32);
+    retention_repo::update_days(sid, session.retention_days).await?;
+    let retention = response(&session);
+    let text = match retention.effective {
+        0 => format!("{} 将消息设置为永久保留", claims.name),
+        d => format!("{} 将消息保留时间设置为 {} 天", claims.name, d),
+    };
+    member::changed(&session, &text, vec![]).await;
+    Ok(retention)
+}
@@ -0,0 +1,212 @@
+use std::time::Duration;
+
+use core::api::types::message::{ScheduleRequest, ScheduledResponse};
+use core::auth::User;
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use pubchat::core::message::{chrt, message::Content, ChatType, Chrt, Message, Text, Type};
+use crate::model::scheduled::Scheduled;
+use crate::model::session::{Role, Session};
+use crate::queue;
+use crate::repository::{scheduled as scheduled_repo, session as session_repo};
+use crate::service::member::load;
+use crate::service::moderation;
+
+//调度任务轮询间隔
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+//每次认领的到期消息数
+const BATCH_SIZE: u32 = 100;
+//认领后超过该时长仍未完成的消息视为实例异常退出，重新放回待发送
+const CLAIM_LEASE_MILLIS: i64 = 60 * 1000;
+const MAX_TEXT_LEN: usize = 4000;
+//最多提前一年定时
+const MAX_DELAY_MILLIS: i64 = 365 * 24 * 60 * 60 * 1000;
+
+//校验定时消息的内容和发送时间
+pub fn check(text: &str, send_at: i64, now: i64) -> Result<()> {
+    if text.trim().is_empty() || text.chars().count() > MAX_TEXT_LEN {
+        return Err(ApiErr::Bad(400, format!("消息长度应为1-{}个字符", MAX_TEXT_LEN)).into());
+    }
+    if send_at <= now {
+        return Err(ApiErr::Bad(400, "发送时间必须晚于当前时间".to_string()).into());
+    }
+    if send_at - now > MAX_DELAY_MILLIS {
+        return Err(ApiErr::Bad(400, "发送时间不能晚于一年后".to_string()).into());
+    }
+    Ok(())
+}
+
+//用户是否可以在会话中发言，频道只有管理员可以发言
+fn can_post(session: &Session, role: Role) -> bool {
+    session.kind != Session::CHANNEL || role >= Role::Admin
+}
+
+async fn load_poster(claims: &User, sid: i64) -> Result<Session> {
+    let (session, me) = load(sid, claims.id).await?;
+    if !can_post(&session, me.role_in(&session)) {
+        return Err(ApiErr::Bad(403, "只有管理员可以在频道中发言".to_string()).into());
+    }
+    Ok(session)
+}
+
+//按会话所属组织的审核规则校验消息，拒绝时返回原因，命中屏蔽规则时返回屏蔽后的文本
+async fn moderate(session: &Session, text: String) -> Result<String> {
+    let verdict = moderation::moderator(session.oid).await?.check(&text);
+    if let Some(reason) = verdict.rejected {
+        return Err(ApiErr::Bad(400, reason).into());
+    }
+    Ok(verdict.masked.unwrap_or(text))
+}
+
+//只能操作自己的定时消息
+async fn load_own(claims: &User, sid: i64, id: i64) -> Result<Scheduled> {
+    scheduled_repo::find_by_id(sid, id).await?
+        .filter(|s| s.sender == claims.id)
+        .ok_or(ApiErr::Bad(404, "定时消息不存在".to_string()).into())
+}
+
+pub async fn create(claims: &User, sid: i64, payload: ScheduleRequest) -> Result<ScheduledResponse> {
+    let session = load_poster(claims, sid).await?;
+    let text = payload.text.unwrap_or_default();
+    let send_at = payload.send_at.ok_or(ApiErr::Bad(400, "缺少发送时间".to_string()))?;
+    check(&text, send_at, Utc::now().timestamp_millis())?;
+    let text = moderate(&session, text).await?;
+    let scheduled = Scheduled {
+        id: snowflaker::next_id()? as i64,
+        sid,
+        sender: claims.id,
+        uname: claims.name.clone(),
+        content: text,
+        send_at,
+        status: Scheduled::PENDING,
+    };
+    scheduled_repo::save(&scheduled).await?;
+    Ok(scheduled.into())
+}
+
+pub async fn get_scheduled(claims: &User, sid: i64) -> Result<Vec<ScheduledResponse>> {
+    load(sid, claims.id).await?;
+    Ok(scheduled_repo::find_pending(sid, claims.id).await?.into_iter().map(|s| s.into()).collect())
+}
+
+pub async fn update(claims: &User, sid: i64, id: i64, payload: ScheduleRequest) -> Result<ScheduledResponse> {
+    let session = load_poster(claims, sid).await?;
+    let mut scheduled = load_own(claims, sid, id).await?;
+    if let Some(text) = payload.text {
+        scheduled.content = moderate(&session, text).await?;
+    }
+    if let Some(send_at) = payload.send_at {
+        scheduled.send_at = send_at;
+    }
+    check(&scheduled.content, scheduled.send_at, Utc::now().timestamp_millis())?;
+    if scheduled.status != Scheduled::PENDING || !scheduled_repo::update(&scheduled).await? {
+        return Err(ApiErr::Bad(409, "定时消息已发送或已取消".to_string()).into());
+    }
+    Ok(scheduled.into())
+}
+
+pub async fn cancel(claims: &User, sid: i64, id: i64) -> Result<()> {
+    load(sid, claims.id).await?;
+    let scheduled = load_own(claims, sid, id).await?;
+    if !scheduled_repo::transit(scheduled.id, Scheduled::PENDING, Scheduled::CANCELLED).await? {
+        return Err(ApiErr::Bad(409, "定时消息已发送或已取消".to_string()).into());
+    }
+    Ok(())
+}
+
+//启动调度任务，定时消息保存在数据库中，重启后继续发送
+pub fn init() {
+    tokio::spawn(async {
+        let mut interval = tokio::time::interval(POLL_INTERVAL);
+        loop {
+            interval.tick().await;
+            if let Err(e) = dispatch().await {
+                log::error!("Failed to dispatch scheduled messages: {}", e);
+            }
+        }
+    });
+}
+
+//认领并发布到期的定时消息，返回发布的数量
+pub async fn dispatch() -> Result<usize> {
+    let now = Utc::now().timestamp_millis();
+    let released = scheduled_repo::release(now - CLAIM_LEASE_MILLIS).await?;
+    if released > 0 {
+        log::warn!("Released {} stale scheduled message claims", released);
+    }
+    let mut sent = 0;
+    loop {
+        let batch = snowflaker::next_id()? as i64;
+        let due = scheduled_repo::claim(batch, now, BATCH_SIZE).await?;
+        for scheduled in &due {
+            match publish(scheduled).await {
+                Ok(true) => {
+                    scheduled_repo::transit(scheduled.id, Scheduled::SENDING, Scheduled::SENT).await?;
+                    sent += 1;
+                }
+                Ok(false) => {
+                    log::warn!("Scheduled message {} of user {} in session {} can no longer be sent, dropped", scheduled.id, scheduled.sender, scheduled.sid);
+                    scheduled_repo::transit(scheduled.id, Scheduled::SENDING, Scheduled::FAILED).await?;
+                }
+                Err(e) => {
+                    //发布失败时放回待发送，下次轮询重试
+                    log::error!("Failed to publish scheduled message {}: {}", scheduled.id, e);
+                    scheduled_repo::transit(scheduled.id, Scheduled::SENDING, Scheduled::PENDING).await?;
+                }
+            }
+        }
+        if (due.len() as u32) < BATCH_SIZE {
+            break;
+        }
+    }
+    Ok(sent)
+}
+
+//像客户端发送的聊天消息一样发布到消息交换机，由连接服务投递、会话服务保存
+//发送者已不在会话中、无权发言或消息被审核规则拒绝时返回false
+async fn publish(scheduled: &Scheduled) -> Result<bool> {
+    let Some(session) = session_repo::find_session_by_id(scheduled.sid).await? else {
+        return Ok(false);
+    };
+    let Some(me) = session_repo::find_user_session(scheduled.sid, scheduled.sender).await? else {
+        return Ok(false);
+    };
+    if !can_post(&session, me.role_in(&session)) {
+        return Ok(false);
+    }
+    //发送前按最新的审核规则重新审核
+    let verdict = moderation::moderator(session.oid).await?.check(&scheduled.content);
+    if verdict.rejected.is_some() {
+        return Ok(false);
+    }
+    let receivers = if session.kind == Session::CHANNEL {
+        vec![]
+    } else {
+        session_repo::find_user_sessions_by_session(session.id).await?
+            .into_iter()
+            .filter(|m| m.uid != scheduled.sender)
+            .map(|m| m.uid as u64)
+            .collect()
+    };
+    let now = Utc::now().timestamp_millis() as u64;
+    let message = Message {
+        //使用定时消息的id，认领超时后重复发布时消息只会保存一次
+        id: scheduled.id as u64,
+        ts: now,
+        mtype: Type::Chrt as i32,
+        content: Some(Content::Chrt(Chrt {
+            sender: scheduled.sender as u64,
+            uname: scheduled.uname.clone(),
+            session: scheduled.sid as u64,
+            receivers,
+            ctype: ChatType::Text as i32,
+            ts: now,
+            message: Some(chrt::Message::Text(Text { text: verdict.masked.unwrap_or_else(|| scheduled.content.clone()) })),
+            flag: verdict.flagged,
+        })),
+    };
+    queue::publish(&message, "scheduled").await?;
+    Ok(true)
+}
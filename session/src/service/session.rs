@@ -1,13 +1,21 @@
 use core::api::types::contact::ContactResponse;
+use core::auth::User;
 use core::response::ApiErr;
 
 use anyhow::{Ok, Result};
 use core::api::types::session::{CreateSessionRequest, SessionDetailResponse};
-use crate::model::session::{Session, UserSession};
+use crate::model::message::Message;
+use crate::model::session::{ReadCursor, Role, Session, UserSession};
+use crate::repository::{message as message_repo, read as read_repo};
+use core::api::types::session::{MessagePreview, SessionResponse, SessionSettings};
 use crate::repository::session as session_repo;
+use crate::service::member;
+use crate::service::invite::direct;
 use chrono::Utc;
+use std::collections::HashMap;
 
-pub async fn create_session(creator_id: i64, payload: CreateSessionRequest) -> Result<Session> {
+pub async fn create_session(creator: &User, payload: CreateSessionRequest) -> Result<Session> {
+    let creator_id = creator.id;
     if payload.id <= 0 {
         return Err(ApiErr::Bad(400, "会话ID不能小于等于0".to_string()).into());
     }
@@ -17,6 +25,18 @@ pub async fn create_session(creator_id: i64, payload: CreateSessionRequest) -> R
     }
     let now = Utc::now().naive_utc();
     let session_id = payload.id;
+    let mut members = vec![];
+    for member in payload.members {
+        members.push(UserSession {
+            id: snowflaker::next_id()? as i64,
+            uid: member.id,
+            uname: member.name,
+            sid: session_id,
+            role: if member.id == creator_id { 1 } else { 0 }, // 创建者为管理员
+            jointime: now,
+        });
+    }
+    let kind = kind_of(payload.kind, session_id, &members)?;
     // 创建会话
     let session = Session {
         id: session_id,
@@ -24,28 +44,86 @@ pub async fn create_session(creator_id: i64, payload: CreateSessionRequest) -> R
         creator: creator_id,
         createtime: now,
         updatetime: now,
+        last_message_id: None,
+        last_message_at: None,
+        oid: creator.oid,
+        retention_days: None,
+        kind,
     };
     
     // 保存会话
     session_repo::create_session(&session).await?;
     
     // 添加会话成员（包括创建者）
-    for member in payload.members {
-        let user_session = UserSession {
-            id: snowflaker::next_id()? as i64,
-            uid: member.id,
-            uname: member.name,
-            sid: session.id,
-            role: if member.id == creator_id { 1 } else { 0 }, // 创建者为管理员
-            jointime: now,
-        };
-        session_repo::create_user_session(&user_session).await?;
+    for user_session in &members {
+        session_repo::create_user_session(user_session).await?;
+    }
+    if let Err(e) = member::sync(&session).await {
+        log::error!("Failed to sync members of session {}: {}", session.id, e);
     }
     Ok(session)
 }
 
-pub async fn get_sessions_by_user(user_id: i64) -> Result<Vec<Session>> {
-    session_repo::find_sessions_by_user(user_id).await
+//确定新建会话的类型，频道需显式指定，单聊和群聊按会话id和成员推断
+pub fn kind_of(kind: Option<i8>, sid: i64, members: &[UserSession]) -> Result<i8> {
+    match kind {
+        Some(Session::CHANNEL) => Ok(Session::CHANNEL),
+        None | Some(Session::DIRECT) | Some(Session::GROUP) if direct(sid, members) => Ok(Session::DIRECT),
+        None | Some(Session::DIRECT) | Some(Session::GROUP) => Ok(Session::GROUP),
+        Some(_) => Err(ApiErr::Bad(400, "会话类型不合法".to_string()).into()),
+    }
+}
+
+//预览内容的最大字符数
+const PREVIEW_LEN: usize = 50;
+
+pub async fn get_sessions_by_user(user_id: i64) -> Result<Vec<SessionResponse>> {
+    let summaries = session_repo::find_sessions_by_user(user_id).await?;
+    let ids: Vec<i64> = summaries.iter().filter_map(|s| s.session.last_message_id).collect();
+    let mut last: HashMap<i64, Message> = message_repo::find_by_ids(&ids).await?
+        .into_iter()
+        .map(|m| (m.id, m))
+        .collect();
+    Ok(summaries.into_iter().map(|summary| {
+        let last_message = summary.session.last_message_id.and_then(|id| last.remove(&id));
+        let mut session: SessionResponse = summary.session.into();
+        session.unread = summary.unread;
+        session.settings = SessionSettings { notify: summary.notify, muted_until: summary.muted_until };
+        session.last_message = last_message.map(preview);
+        session
+    }).collect())
+}
+
+pub fn preview(m: Message) -> MessagePreview {
+    let mut content: String = m.content.chars().take(PREVIEW_LEN).collect();
+    if m.content.chars().count() > PREVIEW_LEN {
+        content.push_str("...");
+    }
+    MessagePreview {
+        id: m.id,
+        sender: m.sender,
+        uname: m.uname,
+        content,
+        timestamp: m.timestamp,
+    }
+}
+
+//标记已读，message为空时标记到会话最新的消息，已读位置只前进不后退
+pub async fn mark_read(uid: i64, sid: i64, message: Option<i64>) -> Result<()> {
+    let target = match message {
+        Some(id) => message_repo::find_by_id(sid, id).await?
+            .ok_or(ApiErr::Bad(404, "消息不存在".to_string()))?,
+        None => match message_repo::find_by_session(sid, None, None, 1).await?.into_iter().next() {
+            Some(m) => m,
+            None => return Ok(()),
+        },
+    };
+    read_repo::advance(&ReadCursor {
+        uid,
+        sid,
+        last_read_message_id: target.id,
+        last_read_timestamp: target.timestamp,
+    }).await
 }
 
 pub async fn get_session_by_id(session_id: i64) -> Result<SessionDetailResponse> {
@@ -54,11 +132,15 @@ pub async fn get_session_by_id(session_id: i64) -> Result<SessionDetailResponse>
         return Err(ApiErr::Bad(404, "会话不存在".to_string()).into());
     }
     let session = session.unwrap();
-    // 获取会话成员列表
-    let members = session_repo::find_user_sessions_by_session(session_id).await?;
+    // 获取会话成员列表，频道只返回管理员
+    let mut members = session_repo::find_user_sessions_by_session(session_id).await?;
+    if session.kind == Session::CHANNEL {
+        members.retain(|m| m.role_in(&session) >= Role::Admin);
+    }
     let detail = SessionDetailResponse {
         id: session.id,
         name: session.name,
+        kind: session.kind,
         members: members.into_iter().map(|m| ContactResponse {
             id: m.uid,
             name: m.uname,
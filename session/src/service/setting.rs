@@ -0,0 +1,69 @@
+use core::api::types::session::SessionSettings;
+use core::response::ApiErr;
+
+use anyhow::Result;
+use chrono::Utc;
+use pubchat::core::message::{message::Content, Message, Notify, Setting, Type};
+use crate::model::session::SessionSetting;
+use crate::queue;
+use crate::repository::setting as setting_repo;
+
+//获取用户在会话中的通知设置，没有设置时返回默认值
+pub async fn get_settings(uid: i64, sid: i64) -> Result<SessionSettings> {
+    Ok(setting_repo::find(uid, sid).await?
+        .map(|s| SessionSettings { notify: s.notify, muted_until: s.muted_until })
+        .unwrap_or_default())
+}
+
+//校验通知设置，已过期的静音视为未静音
+pub fn check(settings: SessionSettings, now: i64) -> Result<SessionSettings> {
+    let notify = Notify::try_from(settings.notify as i32)
+        .map_err(|_| ApiErr::Bad(400, "通知级别不合法".to_string()))?;
+    Ok(SessionSettings {
+        notify: notify as i8,
+        muted_until: settings.muted_until.filter(|until| *until > now),
+    })
+}
+
+fn to_message(setting: &SessionSetting) -> Setting {
+    Setting {
+        session: setting.sid as u64,
+        uid: setting.uid as u64,
+        notify: setting.notify as i32,
+        muted_until: setting.muted_until.map(|t| t as u64),
+    }
+}
+
+//会话中成员的非默认通知设置，已过期的静音不再影响提醒，按未静音处理
+pub(crate) async fn find_by_session(sid: i64) -> Result<Vec<Setting>> {
+    let now = Utc::now().timestamp_millis();
+    Ok(setting_repo::find_by_session(sid).await?
+        .into_iter()
+        .filter(|s| s.notify != SessionSettings::NOTIFY_ALL || s.muted_until.is_some_and(|until| until > now))
+        .map(|s| to_message(&s))
+        .collect())
+}
+
+//保存通知设置并同步给连接服务
+pub async fn set_settings(uid: i64, sid: i64, settings: SessionSettings) -> Result<SessionSettings> {
+    let now = Utc::now().timestamp_millis();
+    let settings = check(settings, now)?;
+    let setting = SessionSetting {
+        uid,
+        sid,
+        notify: settings.notify,
+        muted_until: settings.muted_until,
+    };
+    setting_repo::save(&setting).await?;
+    let message = Message {
+        id: snowflaker::next_id()?,
+        ts: now as u64,
+        mtype: Type::Sett as i32,
+        content: Some(Content::Setting(to_message(&setting))),
+    };
+    //同步失败时连接服务不会标记静默消息，客户端仍按自身保存的设置处理，不影响保存结果
+    if let Err(e) = queue::publish(&message, "setting").await {
+        log::error!("Failed to publish settings of user {} in session {}: {}", uid, sid, e);
+    }
+    Ok(settings)
+}
@@ -26,4 +26,531 @@ pub mod tests{
     //     let g : String = Gender::M.to_string();
     //     assert_eq!(g, "M".to_string());
     // }
-}
\ No newline at end of file
+
+    use core::api::types::notice::CreateNoticeRequest;
+    use pubchat::core::message::{Scope, Severity};
+    use crate::service::notice;
+
+    fn notice_request(scope: i32, target: i64, exp: Option<i64>) -> CreateNoticeRequest {
+        CreateNoticeRequest { severity: 1, scope, target, text: "今晚22点停机维护".to_string(), exp }
+    }
+
+    #[test]
+    pub fn test_notice_check(){
+        let (severity, scope) = notice::check(&notice_request(0, 0, Some(2000)), 1000).unwrap();
+        assert_eq!(severity, Severity::Warn);
+        assert_eq!(scope, Scope::All);
+        assert!(notice::check(&notice_request(2, 10, None), 1000).is_ok());
+    }
+
+    #[test]
+    pub fn test_notice_check_invalid(){
+        //会话公告必须指定会话
+        assert!(notice::check(&notice_request(2, 0, None), 1000).is_err());
+        //已过期
+        assert!(notice::check(&notice_request(0, 0, Some(500)), 1000).is_err());
+        //未知范围
+        assert!(notice::check(&notice_request(9, 0, None), 1000).is_err());
+        let mut empty = notice_request(0, 0, None);
+        empty.text = "  ".to_string();
+        assert!(notice::check(&empty, 1000).is_err());
+    }
+
+    #[test]
+    pub fn test_search_terms(){
+        use crate::service::message::{boolean_query, terms};
+        let t = terms("部署 +\"发布\" a -(x)y");
+        assert_eq!(t, vec!["部署".to_string(), "发布".to_string(), "xy".to_string()]);
+        assert_eq!(boolean_query(&t), "+\"部署\" +\"发布\" +\"xy\"");
+        assert!(terms("a b").is_empty());
+    }
+
+    #[test]
+    pub fn test_search_snippet(){
+        use crate::service::message::snippet;
+        let terms = vec!["release".to_string()];
+        assert_eq!(snippet("The Release is ready", &terms, 20), "The **Release** is ready");
+        let long = format!("{}今晚发布新版本{}", "前".repeat(30), "后".repeat(30));
+        let s = snippet(&long, &["发布".to_string()], 5);
+        assert_eq!(s, "...前前前今晚**发布**新版本后后后后后...");
+        //未命中时返回开头部分
+        assert_eq!(snippet("hello world", &["xyz".to_string()], 2), "hell...");
+    }
+
+    //测试用的会话，创建者为用户10
+    fn session(id: i64, name: &str, oid: i64, kind: i8) -> crate::model::session::Session {
+        let now = chrono::Utc::now().naive_utc();
+        crate::model::session::Session {
+            id, name: name.to_string(), creator: 10, createtime: now, updatetime: now,
+            last_message_id: None, last_message_at: None, oid, retention_days: None, kind,
+        }
+    }
+
+    #[test]
+    pub fn test_member_roles(){
+        use crate::model::session::{Role, Session, UserSession};
+        use crate::service::member::can_remove;
+        let now = chrono::Utc::now().naive_utc();
+        let session = session(1, "group", 0, Session::GROUP);
+        let member = |uid: i64, role: i8| UserSession { id: uid, uid, uname: uid.to_string(), sid: 1, role, jointime: now };
+        assert_eq!(member(10, UserSession::ADMIN).role_in(&session), Role::Owner);
+        assert_eq!(member(11, UserSession::ADMIN).role_in(&session), Role::Admin);
+        assert_eq!(member(12, UserSession::MEMBER).role_in(&session), Role::Member);
+
+        assert!(can_remove(Role::Owner, Role::Admin));
+        assert!(can_remove(Role::Admin, Role::Member));
+        assert!(!can_remove(Role::Admin, Role::Admin));
+        assert!(!can_remove(Role::Admin, Role::Owner));
+        assert!(!can_remove(Role::Member, Role::Member));
+    }
+
+    #[test]
+    pub fn test_session_preview(){
+        use crate::model::message::Message;
+        use crate::service::session::preview;
+        let message = |content: String| Message { id: 1, sender: 2, session: 3, mtype: 0, content, timestamp: 4, uname: "tom".to_string(), payload: None, version: 0 };
+        assert_eq!(preview(message("hello".to_string())).content, "hello");
+        let p = preview(message("长".repeat(60)));
+        assert_eq!(p.content, format!("{}...", "长".repeat(50)));
+        assert_eq!(p.uname, "tom");
+    }
+
+    #[test]
+    pub fn test_activity_merge(){
+        use std::collections::HashMap;
+        use core::request::Position;
+        use crate::service::activity::merge;
+        let mut pending = HashMap::new();
+        merge(&mut pending, 1, Position::new(100, 5));
+        merge(&mut pending, 1, Position::new(90, 9));
+        merge(&mut pending, 1, Position::new(100, 7));
+        merge(&mut pending, 2, Position::new(50, 1));
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[&1], Position::new(100, 7));
+        assert_eq!(pending[&2], Position::new(50, 1));
+    }
+
+    #[test]
+    pub fn test_retention_effective(){
+        use core::config::RetentionConfig;
+        use crate::model::session::Session;
+        use crate::service::retention::effective;
+        let mut session = session(1, "group", 7, Session::GROUP);
+        let mut config = RetentionConfig { days: 30, ..Default::default() };
+        assert_eq!(effective(&session, &config), (30, "default"));
+        config.orgs.insert("7".to_string(), 90);
+        assert_eq!(effective(&session, &config), (90, "org"));
+        //会话设置只能缩短组织配置的保留天数
+        session.retention_days = Some(0);
+        assert_eq!(effective(&session, &config), (90, "org"));
+        session.retention_days = Some(365);
+        assert_eq!(effective(&session, &config), (90, "org"));
+        session.retention_days = Some(7);
+        assert_eq!(effective(&session, &config), (7, "session"));
+        //组织及全局都未限制时可以设为永久保留
+        config.orgs.clear();
+        config.days = 0;
+        session.retention_days = Some(0);
+        assert_eq!(effective(&session, &config), (0, "session"));
+    }
+
+    #[test]
+    pub fn test_setting_check(){
+        use core::api::types::session::SessionSettings;
+        use crate::service::setting::check;
+        let now = 1700000000000;
+        let settings = check(SessionSettings { notify: 1, muted_until: Some(now + 1000) }, now).unwrap();
+        assert_eq!(settings, SessionSettings { notify: 1, muted_until: Some(now + 1000) });
+        //已过期的静音视为未静音
+        assert_eq!(check(SessionSettings { notify: 0, muted_until: Some(now) }, now).unwrap().muted_until, None);
+        assert!(check(SessionSettings { notify: 5, muted_until: None }, now).is_err());
+    }
+
+    #[test]
+    pub fn test_scheduled_check(){
+        use crate::service::scheduled::check;
+        let now = 1700000000000;
+        assert!(check("hello", now + 60 * 1000, now).is_ok());
+        assert!(check("  ", now + 60 * 1000, now).is_err());
+        //发送时间必须在未来且不超过一年
+        assert!(check("hello", now, now).is_err());
+        assert!(check("hello", now + 366 * 24 * 60 * 60 * 1000, now).is_err());
+    }
+
+    #[test]
+    pub fn test_invite_rules(){
+        use core::api::types::session::CreateInviteRequest;
+        use crate::model::invite::Invite;
+        use crate::model::session::UserSession;
+        use crate::service::invite::{check, direct, usable};
+        assert_eq!(check(&CreateInviteRequest::default()).unwrap(), (7 * 24 * 3600, None));
+        assert!(check(&CreateInviteRequest { ttl_secs: Some(0), max_uses: None }).is_err());
+        assert!(check(&CreateInviteRequest { ttl_secs: Some(3600), max_uses: Some(0) }).is_err());
+        let now = chrono::Utc::now();
+        let mut invite = Invite { id: 1, sid: 100, token: String::new(), creator: 10, uname: "admin".to_string(), max_uses: Some(2), uses: 1, exp: now.timestamp_millis() + 1000, revoked: false, createtime: now.naive_utc() };
+        assert!(usable(&invite, now.timestamp_millis()).is_ok());
+        assert!(usable(&invite, invite.exp).is_err());
+        invite.uses = 2;
+        assert!(usable(&invite, now.timestamp_millis()).is_err());
+        invite.max_uses = None;
+        invite.revoked = true;
+        assert!(usable(&invite, now.timestamp_millis()).is_err());
+        let member = |uid| UserSession { id: uid, uid, uname: String::new(), sid: 0, role: UserSession::MEMBER, jointime: now.naive_utc() };
+        let sid = core::api::client::session::calc_session_id(1, 2) as i64;
+        assert!(direct(sid, &[member(1), member(2)]));
+        assert!(!direct(sid, &[member(1), member(2), member(3)]));
+        assert!(!direct(100, &[member(1), member(2)]));
+    }
+
+    #[test]
+    pub fn test_session_kind(){
+        use crate::model::session::{Session, UserSession};
+        use crate::service::session::kind_of;
+        let now = chrono::Utc::now().naive_utc();
+        let member = |uid| UserSession { id: uid, uid, uname: String::new(), sid: 0, role: UserSession::MEMBER, jointime: now };
+        let sid = core::api::client::session::calc_session_id(1, 2) as i64;
+        //单聊和群聊按会话id和成员推断，频道需显式指定
+        assert_eq!(kind_of(None, sid, &[member(2), member(1)]).unwrap(), Session::DIRECT);
+        assert_eq!(kind_of(Some(Session::GROUP), sid, &[member(1), member(2)]).unwrap(), Session::DIRECT);
+        assert_eq!(kind_of(Some(Session::DIRECT), 100, &[member(1), member(2), member(3)]).unwrap(), Session::GROUP);
+        assert_eq!(kind_of(Some(Session::CHANNEL), sid, &[member(1), member(2)]).unwrap(), Session::CHANNEL);
+        assert!(kind_of(Some(9), 100, &[member(1)]).is_err());
+    }
+
+    #[test]
+    pub fn test_legacy_payload(){
+        use core::api::types::message::Payload;
+        use pubchat::core::message::{Blob, Text};
+        use crate::service::message::{encode, legacy};
+        let content = format!("[File] report (final).pdf (size: 1024, exp: 2025-01-01, download id: {})", base62::encode(123456789u64));
+        let blob = Blob { id: 123456789, name: "report (final).pdf".to_string(), size: "1024".to_string(), exp: Some("2025-01-01".to_string()) };
+        assert_eq!(legacy(&content), Payload::Blob(blob.clone()));
+        //解析结果与当前的展示文本一致
+        assert_eq!(legacy(&content).to_string(), content);
+        let never = Payload::Blob(Blob { exp: None, ..blob });
+        assert_eq!(legacy(&never.to_string()), never);
+        assert_eq!(legacy("hello"), Payload::Text(Text { text: "hello".to_string() }));
+        assert_eq!(legacy("[File] broken"), Payload::Text(Text { text: "[File] broken".to_string() }));
+        assert_eq!(encode(&legacy("hi")).unwrap(), r#"{"text":{"text":"hi"}}"#);
+    }
+
+    #[test]
+    pub fn test_export_render(){
+        use crate::model::session::Session;
+        use crate::service::export::{entry, footer, header, Entry, Format};
+        let session = session(1, "a<b>", 0, Session::GROUP);
+        let message = |id: i64, content: &str| Entry { id, sender: 2, uname: "tom".to_string(), mtype: 0, timestamp: 0, time: "1970-01-01 00:00:00 UTC".to_string(), content: content.to_string(), file: None };
+        let mut json = header(Format::Json, &session, "now");
+        json.push_str(&entry(Format::Json, &message(1, "hi"), true));
+        json.push_str(&entry(Format::Json, &message(2, "\"quoted\""), false));
+        json.push_str(&footer(Format::Json, 2, true));
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["session"]["name"], "a<b>");
+        assert_eq!(value["messages"][1]["content"], "\"quoted\"");
+        assert_eq!((value["count"].as_u64(), value["complete"].as_bool()), (Some(2), Some(true)));
+        //读取中断时结尾标明导出不完整，JSON仍然合法
+        let mut partial = header(Format::Json, &session, "now");
+        partial.push_str(&footer(Format::Json, 0, false));
+        let value: serde_json::Value = serde_json::from_str(&partial).unwrap();
+        assert_eq!(value["complete"], false);
+        assert!(footer(Format::Markdown, 0, false).contains("导出中断"));
+        assert!(footer(Format::Html, 3, false).contains("class=\"error\""));
+        let html = entry(Format::Html, &message(1, "<script>"), true);
+        assert!(html.contains("&lt;script&gt;"));
+        assert!(header(Format::Html, &session, "now").contains("<title>a&lt;b&gt;</title>"));
+        assert_eq!(entry(Format::Markdown, &message(1, "hi"), true), "**tom** · 1970-01-01 00:00:00 UTC\n\nhi\n\n");
+        assert!(Format::parse(Some("pdf")).is_err());
+        assert_eq!(Format::parse(None).unwrap(), Format::Json);
+    }
+
+    #[test]
+    pub fn test_dead_letter_retries(){
+        use core::api::types::message::DeadLetterRequest;
+        use core::queue::can_retry;
+        use crate::service::admin::get_dead_letters;
+        //失败次数记录在消息头中，超过重试次数后转入死信队列
+        assert!(can_retry(1, 2));
+        assert!(can_retry(2, 2));
+        assert!(!can_retry(3, 2));
+        assert!(!can_retry(1, 0));
+        //管理接口只能操作死信队列
+        let admin = core::auth::User { id: 0, name: "admin".to_string(), oid: 0, exp: 0 };
+        let request = DeadLetterRequest { queue: Some("pubchat_session_message_queue".to_string()), limit: None };
+        let result = tokio::runtime::Runtime::new().unwrap().block_on(get_dead_letters(&admin, request));
+        assert!(matches!(result.unwrap_err().downcast_ref::<core::response::ApiErr>(), Some(core::response::ApiErr::Bad(400, _))));
+    }
+
+    #[test]
+    pub fn test_dead_letter_headers(){
+        use lapin::types::{AMQPValue, FieldTable, LongString};
+        use crate::queue::dead_letter::{header, strip};
+        let mut headers = FieldTable::default();
+        headers.insert("x-pubchat-source".into(), AMQPValue::LongString(LongString::from("q")));
+        headers.insert("x-pubchat-reason".into(), AMQPValue::LongString(LongString::from("boom")));
+        headers.insert("trace".into(), AMQPValue::LongString(LongString::from("t1")));
+        assert_eq!(header(Some(&headers), "x-pubchat-source"), Some("q".to_string()));
+        assert_eq!(header(None, "x-pubchat-source"), None);
+        let stripped = strip(Some(&headers));
+        assert_eq!(stripped.inner().len(), 1);
+        assert_eq!(header(Some(&stripped), "trace"), Some("t1".to_string()));
+    }
+
+    //初始化JWT密钥，各测试共用
+    fn auth() {
+        static AUTH: std::sync::Once = std::sync::Once::new();
+        AUTH.call_once(|| core::auth::init(&core::config::AuthConfig {
+            jwt_secret: "test".to_string(),
+            token_ttl_secs: 60,
+        }));
+    }
+
+    //邀请令牌的exp为秒，与登录令牌互不通用
+    #[test]
+    pub fn test_invite_token(){
+        use crate::service::invite::{open, token};
+        auth();
+        let exp = chrono::Utc::now().timestamp_millis() + 3600 * 1000;
+        let invite = open(&token(1, 100, exp).unwrap()).unwrap();
+        assert_eq!((invite.iid, invite.sid, invite.exp), (1, 100, exp / 1000));
+        //超过校验的时间误差后过期
+        let expired = chrono::Utc::now().timestamp_millis() - 3600 * 1000;
+        assert_eq!(code(open(&token(1, 100, expired).unwrap())), 404);
+        let login = core::auth::issue(1, "tom".to_string(), 0).unwrap();
+        assert_eq!(code(open(&login)), 404);
+        assert!(core::auth::verify(&token(1, 100, exp).unwrap()).is_err());
+    }
+
+    //依赖数据库的测试共用一个运行时，内存数据库的连接池不能跨运行时使用
+    fn runtime() -> &'static tokio::runtime::Runtime {
+        static RUNTIME: std::sync::OnceLock<tokio::runtime::Runtime> = std::sync::OnceLock::new();
+        RUNTIME.get_or_init(|| tokio::runtime::Runtime::new().unwrap())
+    }
+
+    //初始化SQLite内存数据库并执行迁移，各测试使用不同的会话及用户id
+    async fn setup() {
+        use core::config::DatabaseConfig;
+        use crate::repository::db;
+        static DB: tokio::sync::OnceCell<()> = tokio::sync::OnceCell::const_new();
+        DB.get_or_init(|| async {
+            db::init(&DatabaseConfig { url: "sqlite::memory:".to_string(), ..Default::default() }).await;
+        }).await;
+    }
+
+    //请求失败时的状态码，非ApiErr::Bad的错误返回0
+    fn code<T>(result: anyhow::Result<T>) -> u16 {
+        match result.err().map(|e| e.downcast::<core::response::ApiErr>()) {
+            Some(Ok(core::response::ApiErr::Bad(code, _))) => code,
+            _ => 0,
+        }
+    }
+
+    //在SQLite内存数据库上执行迁移及仓储层的方言相关语句
+    #[test]
+    pub fn test_sqlite_repositories(){
+        use core::api::types::message::SearchMessageRequest;
+        use core::lease;
+        use crate::model::{message::Message, pin::Pin, scheduled::Scheduled, session::{ReadCursor, Session, UserSession}};
+        use crate::repository::{db, message, pin, read, retention, scheduled, session};
+        let now = chrono::Utc::now().naive_utc();
+        let chat = |id: i64, timestamp: i64, content: &str| Message {
+            id, sender: 2, session: 10, mtype: 0, content: content.to_string(), timestamp,
+            uname: "jerry".to_string(), payload: None, version: 1,
+        };
+        runtime().block_on(async {
+            setup().await;
+            session::create_session(&Session { creator: 1, ..session(10, "test", 0, Session::GROUP) }).await.unwrap();
+            for uid in [1, 2] {
+                session::create_user_session(&UserSession {
+                    id: uid, uid, uname: format!("user{}", uid), sid: 10, role: UserSession::MEMBER, jointime: now,
+                }).await.unwrap();
+            }
+            //重复写入的消息只保存一次
+            assert!(message::save(&chat(100, 1000, "Hello World")).await.unwrap());
+            assert!(!message::save(&chat(100, 1000, "Hello World")).await.unwrap());
+            assert!(message::save(&chat(101, 2000, "100%_done")).await.unwrap());
+            //已读位置只前进不后退
+            let cursor = |id: i64, timestamp: i64| ReadCursor { uid: 1, sid: 10, last_read_message_id: id, last_read_timestamp: timestamp };
+            read::advance(&cursor(0, 0)).await.unwrap();
+            read::advance(&cursor(100, 1000)).await.unwrap();
+            read::advance(&cursor(0, 0)).await.unwrap();
+            read::advance(&cursor(99, 1000)).await.unwrap();
+            let summaries = session::find_sessions_by_user(1).await.unwrap();
+            assert_eq!((summaries.len(), summaries[0].unread, summaries[0].notify), (1, 1, 0));
+            assert_eq!(summaries[0].session.kind, Session::GROUP);
+            session::transfer_owner(10, 2).await.unwrap();
+            assert_eq!(session::find_user_session(10, 2).await.unwrap().unwrap().role, UserSession::ADMIN);
+            //LIKE匹配不区分大小写，通配符按原文匹配
+            let request = SearchMessageRequest::default();
+            let (found, count) = message::search(1, "", &["world".to_string()], &request, 10, 0).await.unwrap();
+            assert_eq!((found[0].id, count), (100, 1));
+            let (_, count) = message::search(1, "", &["0%_".to_string()], &request, 10, 0).await.unwrap();
+            assert_eq!(count, 1);
+            let (_, count) = message::search(1, "", &["1_0".to_string()], &request, 10, 0).await.unwrap();
+            assert_eq!(count, 0);
+            assert!(pin::save(&Pin { sid: 10, mid: 100, uid: 1, uname: "tom".to_string(), pintime: 1 }).await.unwrap());
+            assert!(!pin::save(&Pin { sid: 10, mid: 100, uid: 1, uname: "tom".to_string(), pintime: 2 }).await.unwrap());
+            assert_eq!(retention::purge(&[100], true).await.unwrap(), 1);
+            assert_eq!(pin::count(10).await.unwrap(), 0);
+            assert_eq!(message::find_by_session(10, None, None, 10).await.unwrap().len(), 1);
+            //到期的定时消息只会被认领一次
+            for (id, send_at) in [(200, 1000), (201, 5000)] {
+                scheduled::save(&Scheduled {
+                    id, sid: 10, sender: 1, uname: "tom".to_string(), content: "hi".to_string(), send_at, status: Scheduled::PENDING,
+                }).await.unwrap();
+            }
+            let claimed = scheduled::claim(1, 2000, 10).await.unwrap();
+            assert_eq!(claimed.iter().map(|s| s.id).collect::<Vec<_>>(), vec![200]);
+            assert!(scheduled::claim(2, 2000, 10).await.unwrap().is_empty());
+            //租约由其他实例持有且未到期时不能获取，持有者可以续期，释放后可以重新获取
+            let pool = db::get().await.unwrap();
+            let ttl = std::time::Duration::from_secs(60);
+            assert!(lease::acquire(pool, "purge", ttl).await.unwrap());
+            assert!(lease::acquire(pool, "purge", ttl).await.unwrap());
+            core::dispatch!(pool.acquire().await.unwrap(), "UPDATE leases SET holder = ? WHERE name = ?", |sql, conn| {
+                sqlx::query(sql).bind(lease::holder() + 1).bind("purge").execute(conn).await.unwrap();
+            });
+            assert!(!lease::acquire(pool, "purge", ttl).await.unwrap());
+            assert!(lease::acquire(pool, "other", ttl).await.unwrap());
+            lease::release(pool, "other").await.unwrap();
+            assert!(lease::acquire(pool, "other", ttl).await.unwrap());
+        });
+    }
+
+    #[test]
+    pub fn test_channel_subscriptions(){
+        use core::auth::User;
+        use crate::model::session::{Session, UserSession};
+        use crate::repository::{channel, session as session_repo};
+        use crate::service::channel::{subscribe, unsubscribe};
+        runtime().block_on(async {
+            setup().await;
+            let now = chrono::Utc::now().naive_utc();
+            session_repo::create_session(&session(20, "news", 5, Session::CHANNEL)).await.unwrap();
+            session_repo::create_session(&session(21, "group", 5, Session::GROUP)).await.unwrap();
+            for (uid, role) in [(10, UserSession::ADMIN), (31, UserSession::MEMBER)] {
+                session_repo::create_user_session(&UserSession {
+                    id: uid, uid, uname: format!("user{}", uid), sid: 20, role, jointime: now,
+                }).await.unwrap();
+            }
+            let user = |id: i64, oid: i64| User { id, name: format!("user{}", id), oid, exp: 0 };
+            //只能订阅所在组织的频道，群聊不能订阅，已订阅时直接返回
+            assert_eq!(code(subscribe(&user(32, 6), 20).await), 403);
+            assert_eq!(code(subscribe(&user(32, 5), 21).await), 404);
+            assert_eq!(subscribe(&user(31, 5), 20).await.unwrap().id, 20);
+            //所有者不能退订，未订阅时不能退订
+            assert_eq!(code(unsubscribe(&user(10, 5), 20).await), 400);
+            assert_eq!(code(unsubscribe(&user(32, 5), 20).await), 403);
+            assert_eq!(code(unsubscribe(&user(31, 5), 21).await), 404);
+            let channels = channel::find_by_org(5, 31).await.unwrap();
+            assert_eq!(channels.iter().map(|c| (c.session.id, c.subscribers, c.subscribed)).collect::<Vec<_>>(), vec![(20, 2, true)]);
+            assert!(!channel::find_by_org(5, 32).await.unwrap()[0].subscribed);
+        });
+    }
+
+    //游标分页返回两个方向的游标，向更新的消息翻页时也能继续向前翻页
+    #[test]
+    pub fn test_message_pages(){
+        use core::request::{Cursor, Position};
+        use core::response::Cursors;
+        use crate::model::message::Message;
+        use crate::repository::message as message_repo;
+        use crate::service::message::get_messages_by_session;
+        runtime().block_on(async {
+            setup().await;
+            for id in 300..305 {
+                message_repo::save(&Message {
+                    id, sender: 2, session: 30, mtype: 0, content: format!("m{}", id), timestamp: id * 10,
+                    uname: "jerry".to_string(), payload: None, version: 1,
+                }).await.unwrap();
+            }
+            let at = |id: i64| Some(Position::new(id * 10, id).encode());
+            let page = |before: Option<String>, after: Option<String>| async move {
+                let (messages, cursors) = get_messages_by_session(30, Cursor { ps: Some(2), before, after }).await.unwrap();
+                (messages.iter().map(|m| m.id).collect::<Vec<_>>(), cursors)
+            };
+            assert_eq!(page(None, None).await, (vec![304, 303], Cursors { before: at(303), after: None }));
+            assert_eq!(page(at(303), None).await, (vec![302, 301], Cursors { before: at(301), after: at(302) }));
+            assert_eq!(page(None, at(300)).await, (vec![302, 301], Cursors { before: at(301), after: at(302) }));
+            assert_eq!(page(None, at(302)).await, (vec![304, 303], Cursors { before: at(303), after: None }));
+            assert_eq!(page(at(300), None).await, (vec![], Cursors::default()));
+        });
+    }
+
+    //旧格式的消息在消息表和归档表中都会转换，转换前按行的实际版本返回
+    #[test]
+    pub fn test_message_upgrade(){
+        use core::api::types::message::PAYLOAD_VERSION;
+        use crate::model::message::Message;
+        use crate::repository::{message as message_repo, retention};
+        use crate::service::message::{response, upgrade};
+        runtime().block_on(async {
+            setup().await;
+            let legacy = |id: i64| Message {
+                id, sender: 2, session: 40, mtype: 0, content: format!("m{}", id), timestamp: id,
+                uname: "jerry".to_string(), payload: None, version: 0,
+            };
+            for id in [400, 401] {
+                message_repo::save(&legacy(id)).await.unwrap();
+            }
+            assert_eq!(retention::purge(&[400], true).await.unwrap(), 1);
+            assert_eq!(response(legacy(401)).version, 0);
+            assert_eq!(upgrade().await.unwrap(), 2);
+            for table in message_repo::TABLES {
+                assert!(message_repo::find_legacy(table, 10).await.unwrap().is_empty());
+            }
+            let upgraded = message_repo::find_by_id(40, 401).await.unwrap().unwrap();
+            assert_eq!(response(upgraded).version, PAYLOAD_VERSION);
+            assert_eq!(upgrade().await.unwrap(), 0);
+        });
+    }
+
+    mod guard {
+        use axum::body::Body;
+        use axum::http::{Request, StatusCode};
+        use tower::ServiceExt;
+        use crate::common::{guard, router};
+
+        //测试中只有用户1是任意会话的成员
+        fn setup() {
+            super::auth();
+            guard::init(|_sid, uid| Box::pin(async move { Ok(uid == 1) }));
+        }
+
+        //crate名core与标准库core冲突，无法使用#[tokio::test]
+        fn status(uid: i64, uri: &str) -> StatusCode {
+            setup();
+            let token = core::auth::issue(uid, format!("user{}", uid), 0).unwrap();
+            let request = Request::get(uri)
+                .header("Authorization", format!("Bearer {}", token))
+                .body(Body::empty())
+                .unwrap();
+            tokio::runtime::Runtime::new().unwrap().block_on(async {
+                router::init().unwrap().oneshot(request).await.unwrap().status()
+            })
+        }
+
+        #[test]
+        fn test_session_details_forbidden(){
+            assert_eq!(status(2, "/sessions/100"), StatusCode::FORBIDDEN);
+            assert_ne!(status(1, "/sessions/100"), StatusCode::FORBIDDEN);
+        }
+
+        #[test]
+        fn test_session_messages_forbidden(){
+            assert_eq!(status(2, "/100/messages"), StatusCode::FORBIDDEN);
+            assert_eq!(status(2, "/100/messages?before=1700000000000"), StatusCode::FORBIDDEN);
+            assert_ne!(status(1, "/100/messages"), StatusCode::FORBIDDEN);
+        }
+
+        #[test]
+        fn test_session_pins_forbidden(){
+            assert_eq!(status(2, "/sessions/100/pins"), StatusCode::FORBIDDEN);
+            assert_ne!(status(1, "/sessions/100/pins"), StatusCode::FORBIDDEN);
+        }
+
+        #[test]
+        fn test_invalid_session_id(){
+            assert_eq!(status(1, "/sessions/abc"), StatusCode::BAD_REQUEST);
+        }
+    }
+}
@@ -0,0 +1,355 @@
+-- 用户服务Postgres表结构及初始数据，对应MySQL迁移1-12
+-- MySQL中的ON UPDATE CURRENT_TIMESTAMP没有对应写法，updatetime/updated_at需由更新语句维护
+CREATE TABLE module (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL DEFAULT '',
+  code VARCHAR(45) NOT NULL DEFAULT '',
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  "desc" VARCHAR(45) DEFAULT NULL
+);
+
+CREATE TABLE org (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL DEFAULT '',
+  code VARCHAR(45) NOT NULL DEFAULT '',
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE org_user_relation (
+  id BIGINT NOT NULL PRIMARY KEY,
+  uid BIGINT NOT NULL DEFAULT 0,
+  oid BIGINT NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE role (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL DEFAULT '',
+  code VARCHAR(20) NOT NULL DEFAULT '',
+  builtin INTEGER NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  oid BIGINT NOT NULL DEFAULT 0,
+  creator BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE TABLE role_module_relation (
+  id BIGINT NOT NULL PRIMARY KEY,
+  rid BIGINT NOT NULL DEFAULT 0,
+  mid BIGINT NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT uk_rid_mid UNIQUE (rid, mid)
+);
+
+CREATE TABLE "user" (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL DEFAULT '',
+  password VARCHAR(45) NOT NULL DEFAULT '',
+  gender VARCHAR(1) NOT NULL DEFAULT 'U' CHECK (gender IN ('M', 'F', 'U')),
+  age INTEGER NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  open_id VARCHAR(128) NULL,
+  union_id VARCHAR(128) NULL,
+  avatar VARCHAR(256) NULL DEFAULT NULL,
+  CONSTRAINT uk_open_id UNIQUE (open_id)
+);
+
+CREATE TABLE user_role_relation (
+  id BIGINT NOT NULL PRIMARY KEY,
+  uid BIGINT NOT NULL DEFAULT 0,
+  rid BIGINT NOT NULL DEFAULT 0,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT uk_uid_rid UNIQUE (uid, rid)
+);
+CREATE INDEX idx_user_role_relation_rid ON user_role_relation (rid);
+
+CREATE TABLE food (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL DEFAULT '',
+  pick_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE tag (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(45) NOT NULL
+);
+
+CREATE TABLE food_tag (
+  id BIGINT NOT NULL PRIMARY KEY,
+  fid BIGINT NOT NULL,
+  tid BIGINT NOT NULL,
+  CONSTRAINT uk_f_t UNIQUE (tid, fid)
+);
+CREATE INDEX idx_food_tag_fid ON food_tag (fid);
+
+CREATE TABLE user_food_choice (
+  id BIGINT NOT NULL PRIMARY KEY,
+  uid BIGINT NOT NULL,
+  fid BIGINT NOT NULL,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE brands (
+  id BIGINT NOT NULL PRIMARY KEY,
+  name VARCHAR(255) NOT NULL,
+  category VARCHAR(50) NOT NULL,
+  rating NUMERIC(3,2) NOT NULL,
+  review_count INTEGER NOT NULL,
+  platform_scores JSONB NOT NULL,
+  social_scores JSONB NOT NULL,
+  is_top_brand BOOLEAN NOT NULL DEFAULT FALSE,
+  advantages JSONB NOT NULL,
+  market_share NUMERIC(5,2),
+  price_range JSONB,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE product_models (
+  id BIGINT NOT NULL PRIMARY KEY,
+  brand_id BIGINT NOT NULL REFERENCES brands (id),
+  name VARCHAR(255) NOT NULL,
+  release_date DATE NOT NULL,
+  price NUMERIC(10,2) NOT NULL,
+  rating NUMERIC(3,2) NOT NULL,
+  review_count INTEGER NOT NULL,
+  platform_scores JSONB NOT NULL,
+  social_scores JSONB NOT NULL,
+  specifications JSONB NOT NULL,
+  advantages JSONB NOT NULL,
+  disadvantages JSONB NOT NULL,
+  is_top_seller BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE user_ratings (
+  id BIGINT NOT NULL PRIMARY KEY,
+  user_id BIGINT NOT NULL,
+  model_id BIGINT NOT NULL REFERENCES product_models (id),
+  rating INTEGER NOT NULL CHECK (rating >= 1 AND rating <= 5),
+  review_text TEXT,
+  purchase_verified BOOLEAN NOT NULL DEFAULT FALSE,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT unique_user_model UNIQUE (user_id, model_id)
+);
+
+CREATE TABLE raw_data (
+  id BIGINT NOT NULL PRIMARY KEY,
+  keyword VARCHAR(255) NOT NULL,
+  timestamp TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  data JSONB NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE analyzed_data (
+  id BIGINT NOT NULL PRIMARY KEY,
+  raw_data_id BIGINT NOT NULL REFERENCES raw_data (id),
+  timestamp TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  analysis JSONB NOT NULL,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE ratings_history (
+  id BIGINT NOT NULL PRIMARY KEY,
+  model_id BIGINT NOT NULL REFERENCES product_models (id),
+  platform VARCHAR(50) NOT NULL,
+  rating NUMERIC(3,2) NOT NULL CHECK (rating >= 0 AND rating <= 5),
+  timestamp TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+);
+
+CREATE TABLE rating_weights (
+  id BIGINT NOT NULL PRIMARY KEY,
+  platform VARCHAR(50) NOT NULL,
+  weight NUMERIC(3,2) NOT NULL CHECK (weight >= 0 AND weight <= 1),
+  updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT unique_platform UNIQUE (platform)
+);
+
+CREATE INDEX idx_brands_category ON brands (category);
+CREATE INDEX idx_brands_rating ON brands (rating);
+CREATE INDEX idx_product_models_brand_id ON product_models (brand_id);
+CREATE INDEX idx_raw_data_keyword ON raw_data (keyword);
+CREATE INDEX idx_analyzed_data_raw_data_id ON analyzed_data (raw_data_id);
+CREATE INDEX idx_ratings_history_model_id ON ratings_history (model_id);
+CREATE INDEX idx_ratings_history_platform ON ratings_history (platform);
+
+CREATE TABLE contact (
+  id BIGINT NOT NULL PRIMARY KEY,
+  here BIGINT NOT NULL,
+  there BIGINT NOT NULL,
+  status INTEGER NOT NULL DEFAULT 1,
+  createtime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  updatetime TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+  CONSTRAINT uk_here_there UNIQUE (here, there)
+);
+CREATE INDEX idx_contact_here ON contact (here);
+CREATE INDEX idx_contact_there ON contact (there);
+
+-- 内置角色与测试用户
+INSERT INTO role (id, name, code, builtin, createtime, oid, creator) VALUES (0, '超级管理员', 'SM', 1, '2024-04-02 22:57:08', 0, 0);
+INSERT INTO role (id, name, code, builtin, createtime, oid, creator) VALUES (1, '组织管理员', 'OM', 1, '2024-04-02 22:57:08', 0, 0);
+INSERT INTO role (id, name, code, builtin, createtime, oid, creator) VALUES (2, '普通用户', 'NU', 1, '2024-04-02 22:57:08', 0, 0);
+INSERT INTO user_role_relation (id, uid, rid, createtime) VALUES (0, 0, 0, '2024-04-02 22:58:04');
+INSERT INTO "user" (id, name, password, gender, age, createtime) VALUES (0, 'admin', 'admin', 'M', 0, '2024-04-02 22:57:49');
+INSERT INTO "user" (id, name, password, gender, age, createtime) VALUES (1, 'alice', 'alice', 'F', 25, '2024-04-02 22:57:49');
+INSERT INTO "user" (id, name, password, gender, age, createtime) VALUES (2, 'bob', 'bob', 'M', 30, '2024-04-02 22:57:49');
+
+INSERT INTO contact (id, here, there, status) VALUES
+(1, 0, 1, 1),
+(2, 0, 2, 1),
+(3, 1, 0, 1),
+(4, 2, 0, 1);
+
+INSERT INTO brands (id, name, category, rating, review_count, platform_scores, social_scores, is_top_brand, advantages, market_share, price_range) VALUES
+(1, '华为', 'phones', 4.9, 10000, '{"jd": 5, "tmall": 5, "taobao": 5}', '{"zhihu": 5, "xiaohongshu": 5, "weibo": 5}', TRUE, '["adv 1", "adv 2"]', 4.9, '{"min": 1299.99, "max": 5999.99}'),
+(2, '小米', 'phones', 4.9, 10000, '{"jd": 5, "tmall": 5, "taobao": 5}', '{"zhihu": 5, "xiaohongshu": 5, "weibo": 5}', TRUE, '["adv 1", "adv 2"]', 4.9, '{"min": 1299.99, "max": 5999.99}');
+
+INSERT INTO rating_weights (id, platform, weight) VALUES
+(1, 'user_verified', 0.3),
+(2, 'user_unverified', 0.1),
+(3, 'jd', 0.2),
+(4, 'tmall', 0.2),
+(5, 'taobao', 0.2);
+
+-- 食物及分类
+insert into food values (1, '黄焖鸡米饭', 0);
+insert into food values (2, '炒饭', 0);
+insert into food values (3, '排骨饭', 0);
+insert into food values (4, '盖浇饭', 0);
+insert into food values (5, '烧腊饭', 0);
+insert into food values (6, '咖喱饭', 0);
+insert into food values (7, '煲仔饭', 0);
+insert into food values (8, '脆皮鸡饭', 0);
+insert into food values (9, '烤肉拌饭', 0);
+insert into food values (10, '木桶饭', 0);
+insert into food values (11, '牛肉饭', 0);
+insert into food values (12, '川湘菜', 0);
+insert into food values (13, '东北菜', 0);
+insert into food values (14, '粤菜', 0);
+insert into food values (15, '云南菜', 0);
+insert into food values (16, '江浙菜', 0);
+insert into food values (17, '鲁菜', 0);
+insert into food values (18, '西北菜', 0);
+insert into food values (19, '新疆菜', 0);
+insert into food values (20, '北京菜', 0);
+insert into food values (21, '本帮菜', 0);
+insert into food values (22, '海鲜', 0);
+insert into food values (23, '日本料理', 0);
+insert into food values (24, '日式简餐', 0);
+insert into food values (25, '日式烧烤', 0);
+insert into food values (26, '刺身寿司', 0);
+insert into food values (27, '马来西亚菜', 0);
+insert into food values (28, '越南菜', 0);
+insert into food values (29, '新加坡菜', 0);
+insert into food values (30, '泰国菜', 0);
+insert into food values (31, '印度菜', 0);
+insert into food values (32, '意大利菜', 0);
+insert into food values (33, '法国菜', 0);
+insert into food values (34, '西班牙菜', 0);
+insert into food values (35, '墨西哥菜', 0);
+insert into food values (36, '中东中亚菜', 0);
+insert into food values (37, '韩式料理', 0);
+insert into food values (38, '韩式烤肉', 0);
+insert into food values (39, '蒸鸡滋补鸡', 0);
+insert into tag values (1, '美味饭菜');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 39 and t.id = 1;
+
+insert into food values (40, '汉堡薯条', 0);
+insert into food values (41, '披萨意面', 0);
+insert into food values (42, '牛排', 0);
+insert into food values (43, '三明治', 0);
+insert into food values (44, '沙拉轻食', 0);
+insert into food values (45, '奶茶果汁', 0);
+insert into tag values (2, '汉堡披萨');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 45 and f.id >= 40 and t.id = 2;
+
+insert into food values (46, '酸辣粉', 0);
+insert into food values (47, '螺蛳粉', 0);
+insert into food values (48, '米粉米线', 0);
+insert into food values (49, '肠粉', 0);
+insert into food values (50, '热干面', 0);
+insert into food values (51, '重庆小面', 0);
+insert into food values (52, '兰州拉面', 0);
+insert into food values (53, '凉皮凉粉', 0);
+insert into food values (54, '粉丝汤', 0);
+insert into food values (55, '胡辣汤', 0);
+insert into food values (56, '牛肉汤', 0);
+insert into food values (57, '羊肉汤', 0);
+insert into food values (58, '沙茶面', 0);
+insert into food values (59, '北京炸酱面', 0);
+insert into food values (60, '瓦灌汤', 0);
+insert into food values (61, '花甲粉', 0);
+insert into food values (62, '烤冷面', 0);
+insert into tag values (3, '米粉面汤');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 62 and f.id >= 46 and t.id = 3;
+
+insert into food values (63, '烧烤', 0);
+insert into food values (64, '小龙虾', 0);
+insert into tag values (4, '龙虾烧烤');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 64 and f.id >= 63 and t.id = 4;
+
+insert into food values (65, '烧饼馅饼', 0);
+insert into food values (66, '煎饼', 0);
+insert into food values (67, '葱油饼', 0);
+insert into food values (68, '水饺', 0);
+insert into food values (69, '生煎', 0);
+insert into food values (70, '包子汤包', 0);
+insert into food values (71, '豆浆油条', 0);
+insert into food values (72, '驴肉火烧', 0);
+insert into food values (73, '泡馍', 0);
+insert into tag values (5, '粥食面点');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 73 and f.id >= 65 and t.id = 5;
+
+insert into food values (74, '贵州菜', 0);
+insert into food values (109, '徽菜', 0);
+insert into food values (75, '台湾菜', 0);
+insert into food values (76, '江西菜', 0);
+insert into food values (77, '闽菜', 0);
+insert into food values (78, '港菜', 0);
+insert into food values (79, '湖北菜', 0);
+insert into food values (80, '淮扬菜', 0);
+insert into food values (81, '河南菜', 0);
+insert into food values (82, '农家菜', 0);
+insert into tag values (6, '地方菜');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 82 and f.id >= 74 and t.id = 6;
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 21 and f.id >= 12 and t.id = 6;
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id = 109 and t.id = 6;
+
+insert into food values (83, '章鱼小丸子', 0);
+insert into food values (84, '炸鸡炸串', 0);
+insert into food values (85, '钵钵鸡', 0);
+insert into food values (86, '鸡架', 0);
+insert into food values (87, '卤煮', 0);
+insert into tag values (7, '特色小吃');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 87 and f.id >= 83 and t.id = 7;
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id in (46, 47, 48, 49, 50, 51, 53, 61, 62) and t.id = 7;
+
+insert into food values (88, '小火锅', 0);
+insert into food values (89, '羊蝎子', 0);
+insert into food values (90, '川渝火锅', 0);
+insert into food values (91, '闷锅', 0);
+insert into food values (92, '港式火锅', 0);
+insert into food values (93, '铜锅涮肉', 0);
+insert into food values (94, '麻辣香锅', 0);
+insert into food values (95, '干锅', 0);
+insert into food values (96, '鸡公煲', 0);
+insert into food values (97, '砂锅', 0);
+insert into food values (98, '烤鱼', 0);
+insert into food values (99, '麻辣烫', 0);
+insert into tag values (8, '麻辣烫香锅');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 99 and f.id >= 88 and t.id = 7;
+
+insert into food values (100, '卤味熟食', 0);
+insert into food values (101, '粉丝汤', 0);
+
+insert into food values (102, '咖啡', 0);
+insert into food values (103, '甜品', 0);
+insert into food values (104, '面包', 0);
+insert into food values (105, '泡面', 0);
+insert into food values (106, '蛋糕', 0);
+insert into food values (107, '冰淇淋', 0);
+insert into food values (108, '水果捞', 0);
+insert into tag values (9, '咖啡甜品');
+insert into food_tag select f.id * 100 + t.id, f.id, t.id from food f, tag t where f.id <= 108 and f.id >= 102 and t.id = 9;
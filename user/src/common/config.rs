@@ -0,0 +1,16 @@
+use std::sync::OnceLock;
+
+use core::config::UserConfig;
+
+pub static CONFIG: OnceLock<UserConfig> = OnceLock::new();
+
+//加载并校验配置，配置不合法时直接退出
+pub fn init() -> &'static UserConfig {
+    let config = core::config::load_or_exit::<UserConfig>("user");
+    core::config::print("user", &config);
+    CONFIG.get_or_init(|| config)
+}
+
+pub fn get() -> &'static UserConfig {
+    CONFIG.get().expect("配置未初始化")
+}
@@ -1,2 +1,3 @@
 pub mod router;
+pub mod config;
 pub mod enums;
\ No newline at end of file
@@ -12,13 +12,14 @@ pub async fn login<'a>(Json(request): Json<LoginRequest>) -> Result<ApiResponse<
     let password = request.password;
     log::info!("login username: {}, password: {}", username, password);
     let result = crate::service::auth::login(&username, &password).await;
-    _response(result?,username)
+    _response(result?,username).await
 }
 
 
-fn _response(id: i64, uname: String) -> Result<ApiResponse<Token>, ApiErr>{
-    let jwt = auth::issue(id, uname)?;
-    Ok(ApiResponse::One(Token{token: jwt, exp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() + 1800000u128}))
+async fn _response(id: i64, uname: String) -> Result<ApiResponse<Token>, ApiErr>{
+    let oid = crate::service::auth::org(id).await?;
+    let jwt = auth::issue(id, uname, oid)?;
+    Ok(ApiResponse::One(Token{token: jwt, exp: SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_millis() + auth::ttl()}))
 }
 
 pub async fn logout() -> ApiResponse<()>{
@@ -12,7 +12,7 @@ pub mod common;
 pub mod test;
 
 use crate::repository::db;
-use crate::common::router;
+use crate::common::{config, router};
 use crate::repository::rdb;
 
 
@@ -20,11 +20,12 @@ use crate::repository::rdb;
 async fn main() -> Result<(), String> {
     dotenv().ok();
     core::log::init(Some(".pubchat_user.log"));
-    rdb::init().await;
-    db::init().await;
-    auth::init();
+    let config = config::init();
+    rdb::init(&config.redis).await;
+    db::init(&config.database).await;
+    auth::init(&config.auth);
     let app = router::init().expect("路由模块初始化失败");
-    let listener = TcpListener::bind("0.0.0.0:3000").await.unwrap();
+    let listener = TcpListener::bind(&config.server.bind).await.unwrap();
     log::info!("listening on {}", listener.local_addr().unwrap());
     axum::serve(listener, app).await.unwrap();
     Ok(())
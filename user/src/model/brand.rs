@@ -1,6 +1,12 @@
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use sqlx::FromRow;
+use sqlx::{
+    error::BoxDynError,
+    mysql::{MySqlTypeInfo, MySqlValueRef},
+    postgres::{PgTypeInfo, PgValueRef},
+    sqlite::{SqliteTypeInfo, SqliteValueRef},
+    Decode, FromRow, MySql, Postgres, Sqlite, Type,
+};
 
 #[derive(Debug, Serialize, Deserialize, FromRow)]
 #[serde(rename_all = "camelCase")]
@@ -8,7 +14,7 @@ pub struct Brand {
     pub id: i64,
     pub name: String,
     pub category: String,
-    pub rating: Decimal,
+    pub rating: Score,
     pub review_count: i32,
     #[sqlx(json)]
     pub platform_scores: PlatformScores,
@@ -17,7 +23,7 @@ pub struct Brand {
     pub is_top_brand: bool,
     #[sqlx(json)]
     pub advantages: Vec<String>,
-    pub market_share: Option<Decimal>,
+    pub market_share: Option<Score>,
     #[sqlx(json)]
     pub price_range: PriceRange,
 }
@@ -50,4 +56,59 @@ pub struct SocialScores {
 pub struct PriceRange {
   pub min: Decimal,  // 最低价格
   pub max: Decimal,  // 最高价格
-}
\ No newline at end of file
+}
+
+//评分、市场份额等定点数，MySQL和Postgres中为DECIMAL，SQLite没有定点数类型，以REAL存储
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Score(pub Decimal);
+
+impl Type<MySql> for Score {
+    fn type_info() -> MySqlTypeInfo {
+        <Decimal as Type<MySql>>::type_info()
+    }
+
+    fn compatible(ty: &MySqlTypeInfo) -> bool {
+        <Decimal as Type<MySql>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, MySql> for Score {
+    fn decode(value: MySqlValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Score(<Decimal as Decode<MySql>>::decode(value)?))
+    }
+}
+
+impl Type<Postgres> for Score {
+    fn type_info() -> PgTypeInfo {
+        <Decimal as Type<Postgres>>::type_info()
+    }
+
+    fn compatible(ty: &PgTypeInfo) -> bool {
+        <Decimal as Type<Postgres>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for Score {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        Ok(Score(<Decimal as Decode<Postgres>>::decode(value)?))
+    }
+}
+
+impl Type<Sqlite> for Score {
+    fn type_info() -> SqliteTypeInfo {
+        <f64 as Type<Sqlite>>::type_info()
+    }
+
+    fn compatible(ty: &SqliteTypeInfo) -> bool {
+        <f64 as Type<Sqlite>>::compatible(ty)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for Score {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let value = <f64 as Decode<Sqlite>>::decode(value)?;
+        //REAL的二进制误差按两位小数修正，与DECIMAL(x,2)的精度一致
+        Ok(Score(Decimal::try_from(value)?.round_dp(2)))
+    }
+}
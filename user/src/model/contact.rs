@@ -6,6 +6,7 @@ pub struct Contact {
     pub id: i64,
     pub here: i64,
     pub there: i64,
+    #[sqlx(try_from = "i32")]
     pub status: i8, // 1-正常, 2-拉黑
     pub createtime: NaiveDateTime,
     pub updatetime: NaiveDateTime,
@@ -8,6 +8,7 @@ pub struct Role{
     pub name: String,
     pub code: String,
     ///是否内置角色
+    #[sqlx(try_from = "i32")]
     pub builtin: i8,
     ///组织id
     pub oid: i64,
@@ -8,6 +8,7 @@ pub struct User{
     pub name: String,
     pub password: String,
     pub gender: Gender,
+    #[sqlx(try_from = "i32")]
     pub age: i8,
     pub createtime: NaiveDateTime,
     pub open_id: Option<String>,
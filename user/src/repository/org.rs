@@ -0,0 +1,15 @@
+use anyhow::Result;
+use core::dispatch;
+use crate::repository::db;
+
+//用户最早加入的组织
+pub async fn find_oid_by_uid(uid: i64) -> Result<Option<i64>> {
+    let connection = db::connection().await?;
+    let oid = dispatch!(connection, "SELECT oid FROM org_user_relation WHERE uid = ? ORDER BY id LIMIT 1", |sql, conn| {
+        sqlx::query_scalar(sql)
+            .bind(uid)
+            .fetch_optional(conn)
+            .await?
+    });
+    Ok(oid)
+}
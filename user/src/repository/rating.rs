@@ -1,62 +1,69 @@
 use crate::model::rating::{CreateRating, Rating, RatingStats, PlainRatingStats};
 use anyhow::Result;
 use crate::repository::db;
+use core::dispatch;
 
 pub async fn create_rating(
     user_id: i64,
     input: CreateRating,
 ) -> Result<()> {
-    let mut pool = db::connection().await?;
-    sqlx::query(
-        r#"
-        INSERT INTO user_ratings (user_id, model_id, rating, review_text, purchase_verified)
-        VALUES ($1, $2, $3, $4, $5)
-        RETURNING *
-        "#)
-    .bind(user_id)
-    .bind(input.model_id)
-    .bind(input.rating)
-    .bind(input.review_text)
-    .bind(input.purchase_verified)
-    .execute(pool.as_mut())
-    .await?;
+    let connection = db::connection().await?;
+    let id = snowflaker::next_id()? as i64;
+    dispatch!(connection, r#"
+        INSERT INTO user_ratings (id, user_id, model_id, rating, review_text, purchase_verified)
+        VALUES (?, ?, ?, ?, ?, ?)
+        "#, |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .bind(user_id)
+            .bind(input.model_id)
+            .bind(input.rating)
+            .bind(input.review_text)
+            .bind(input.purchase_verified)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
 
 pub async fn get_model_ratings(
     model_id: i64,
 ) -> Result<Vec<Rating>> {
-    let mut pool = db::connection().await?;
-    let result : Vec<Rating> = sqlx::query_as(
-        r#"
+    let connection = db::connection().await?;
+    let result: Vec<Rating> = dispatch!(connection, r#"
         SELECT * FROM user_ratings
         WHERE model_id = ?
         ORDER BY created_at DESC
-        "#
-    )
-    .bind(model_id)
-    .fetch_all(pool.as_mut())
-    .await?;
+        "#, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(model_id)
+            .fetch_all(conn)
+            .await?
+    });
     Ok(result)
 }
 
 pub async fn get_rating_stats(
     model_id: i64,
 ) -> Result<RatingStats> {
-    let mut pool = db::connection().await?;
-    let stats: PlainRatingStats = sqlx::query_as(
-        r#"
+    let connection = db::connection().await?;
+    //AVG在MySQL和Postgres中返回DECIMAL/NUMERIC，统一转换为浮点数
+    let sql = format!(r#"
         SELECT 
-            AVG(rating::float8) as average_rating,
+            CAST(AVG(rating) AS {}) as average_rating,
             COUNT(*) as total_ratings,
-            GROUP_CONCAT(rating) as ratings
+            {} as ratings
         FROM user_ratings
         WHERE model_id = ?
-        "#
-    )
-    .bind(model_id)
-    .fetch_one(pool.as_mut())
-    .await?;
+        "#,
+        db::backend().pick("DOUBLE", "DOUBLE PRECISION"),
+        db::backend().pick("GROUP_CONCAT(rating)", "STRING_AGG(CAST(rating AS TEXT), ',')"));
+    let stats: PlainRatingStats = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(model_id)
+            .fetch_one(conn)
+            .await?
+    });
 
     let ratings:Vec<&str> = stats.ratings.split(",").collect();
     let mut dist = vec![(1, 0i64), (2, 0), (3, 0), (4, 0), (5, 0)];
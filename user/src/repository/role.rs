@@ -1,5 +1,6 @@
 use anyhow::Result;
-use sqlx::{MySql, QueryBuilder};
+use core::dispatch;
+use sqlx::{Database, Encode, QueryBuilder, Type};
 
 use core::request::Page;
 use crate::model::role::Role;
@@ -7,35 +8,41 @@ use crate::repository::db;
 use crate::vo::role::RoleListRequest;
 
 pub async fn select_roles(page: Page, param: RoleListRequest) -> Result<(Vec<Role>, i64)> {
-    let mut connection = db::connection().await?;
-    let mut builder: QueryBuilder<MySql> = QueryBuilder::new("SELECT * FROM role where 1 = 1 ");
-    build_sql(& param, &mut builder);
-    if let Some(fd) = &page.fd {
-        builder
-        .push(" ORDER BY ")
-        .push(fd)
-        .push(" ")
-        .push(&page.dir())
-        .push(" ");
-    }
-    builder.push(" LIMIT ")
-        .push_bind(page.ps)
-        .push(" OFFSET ")
-        .push_bind(page.offset());
-    let user:Vec<Role> = builder
-        .build_query_as()
-        .fetch_all(connection.as_mut())
-        .await?;
-    let mut builder: QueryBuilder<MySql> = QueryBuilder::new("SELECT COUNT(1) AS count FROM role where 1 = 1 ");
-    build_sql(&param, &mut builder);
-    let count:(i64,) = builder
-        .build_query_as()
-        .fetch_one(connection.as_mut())
-        .await?;
-    Ok((user, count.0))
+    let connection = db::connection().await?;
+    let result = dispatch!(connection, |conn| {
+        let mut builder = QueryBuilder::new("SELECT * FROM role where 1 = 1 ");
+        build_sql(&param, &mut builder);
+        if let Some(fd) = &page.fd {
+            builder
+            .push(" ORDER BY ")
+            .push(fd)
+            .push(" ")
+            .push(page.dir())
+            .push(" ");
+        }
+        builder.push(" LIMIT ")
+            .push_bind(page.ps as i64)
+            .push(" OFFSET ")
+            .push_bind(page.offset() as i64);
+        let roles: Vec<Role> = builder
+            .build_query_as()
+            .fetch_all(&mut *conn)
+            .await?;
+        let mut builder = QueryBuilder::new("SELECT COUNT(1) AS count FROM role where 1 = 1 ");
+        build_sql(&param, &mut builder);
+        let count: (i64,) = builder
+            .build_query_as()
+            .fetch_one(&mut *conn)
+            .await?;
+        (roles, count.0)
+    });
+    Ok(result)
 }
 
-fn build_sql(param: &RoleListRequest, builder: &mut QueryBuilder<'static, MySql>) {
+fn build_sql<'a, DB: Database>(param: &RoleListRequest, builder: &mut QueryBuilder<'a, DB>)
+where
+    String: Encode<'a, DB> + Type<DB>,
+{
     if let Some(name) = &param.name {
         builder.push(" AND name LIKE ").push_bind(format!("%{}%", name));
     }
@@ -45,24 +52,28 @@ fn build_sql(param: &RoleListRequest, builder: &mut QueryBuilder<'static, MySql>
 }
 
 pub async fn insert_role(role: Role) -> Result<()>{
-    let mut connection = db::connection().await?;
-    sqlx::query("INSERT INTO role (id, name, code, builtin, oid, creator) VALUES (?, ?, ?, ?, ?, ?)")
-        .bind(role.id)
-        .bind(role.name)
-        .bind(role.code)
-        .bind(role.builtin)
-        .bind(role.oid)
-        .bind(role.creator)
-        .execute(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    dispatch!(connection, "INSERT INTO role (id, name, code, builtin, oid, creator) VALUES (?, ?, ?, ?, ?, ?)", |sql, conn| {
+        sqlx::query(sql)
+            .bind(role.id)
+            .bind(role.name)
+            .bind(role.code)
+            .bind(role.builtin as i32)
+            .bind(role.oid)
+            .bind(role.creator)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
 
 pub async fn delete_role(id: i64) -> Result<()>{
-    let mut connection = db::connection().await?;
-    sqlx::query("DELETE FROM role WHERE id = ?")
-        .bind(id)
-        .execute(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    dispatch!(connection, "DELETE FROM role WHERE id = ?", |sql, conn| {
+        sqlx::query(sql)
+            .bind(id)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
\ No newline at end of file
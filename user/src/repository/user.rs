@@ -1,75 +1,99 @@
 use crate::model::user::User;
 use crate::repository::db;
 use anyhow::Result;
+use core::dispatch;
+
+//gender在MySQL中为ENUM('M','F','U')，其他后端为文本，统一转换为Gender的序号读取
+pub(crate) const GENDER: &str = "CASE gender WHEN 'M' THEN 1 WHEN 'F' THEN 2 ELSE 3 END AS gender";
+
+//user是Postgres的保留字，表名需要加引号
+pub(crate) fn table() -> &'static str {
+    db::backend().pick("user", "\"user\"")
+}
 
 pub async fn select_user_by_name(name: &str) -> Result<Option<User>> {
-    let mut connection = db::connection().await?;
-    let user
-        = sqlx::query_as("SELECT id, name, password, cast(gender as signed) as gender, \
+    let connection = db::connection().await?;
+    let sql = format!("SELECT id, name, password, {}, \
             age, createtime, open_id, union_id, avatar \
-            FROM user WHERE name = ?")
-        .bind(name)
-        .fetch_optional(connection.as_mut())
-        .await?;
+            FROM {} WHERE name = ?", GENDER, table());
+    let user = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(name)
+            .fetch_optional(conn)
+            .await?
+    });
     Ok(user)
 }
 
 pub async fn select_user_by_open_id(open_id: &str) -> Result<Option<User>> {
-    let mut connection = db::connection().await?;
-    let user 
-        = sqlx::query_as("SELECT id, name, password, cast(gender as signed) as gender, \
+    let connection = db::connection().await?;
+    let sql = format!("SELECT id, name, password, {}, \
             age, createtime, open_id, union_id, avatar \
-            FROM user WHERE open_id = ?")
-        .bind(open_id)
-        .fetch_optional(connection.as_mut())
-        .await?;
+            FROM {} WHERE open_id = ?", GENDER, table());
+    let user = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(open_id)
+            .fetch_optional(conn)
+            .await?
+    });
     Ok(user)
 }
 
 pub async fn insert_user(user: User) -> Result<i64> {
-    let mut connection = db::connection().await?;
-    sqlx::query("INSERT INTO user (id, name, password, gender, age, open_id, union_id, avatar) VALUES (?, ?, ?, ?, ?, ?, ?, ?)")
-        .bind(user.id)
-        .bind(user.name)
-        .bind(user.password)
-        .bind(user.gender.to_string())
-        .bind(user.age)
-        .bind(user.open_id)
-        .bind(user.union_id)
-        .bind(user.avatar)
-        .execute(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    let sql = format!("INSERT INTO {} (id, name, password, gender, age, open_id, union_id, avatar) VALUES (?, ?, ?, ?, ?, ?, ?, ?)", table());
+    dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(user.id)
+            .bind(user.name)
+            .bind(user.password)
+            .bind(user.gender.to_string())
+            .bind(user.age as i32)
+            .bind(user.open_id)
+            .bind(user.union_id)
+            .bind(user.avatar)
+            .execute(conn)
+            .await?;
+    });
     Ok(user.id)
 }
 
 pub async fn update_avatar<'a>(id: i64, avatar: &str) -> Result<()>{
-    let mut connection = db::connection().await?;
-    sqlx::query("UPDATE user SET avatar = ? WHERE id = ?")
-        .bind(avatar)
-        .bind(id)
-        .execute(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    let sql = format!("UPDATE {} SET avatar = ? WHERE id = ?", table());
+    dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(avatar)
+            .bind(id)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
 }
 
 pub(crate) async fn select_user_by_id(id: i64) -> Result<Option<User>>{
-    let mut connection = db::connection().await?;
-    let user
-        = sqlx::query_as("SELECT id, name, password, cast(gender as signed) as gender, \
+    let connection = db::connection().await?;
+    let sql = format!("SELECT id, name, password, {}, \
             age, createtime, open_id, union_id, avatar \
-            FROM user WHERE id = ?")
-        .bind(id)
-        .fetch_optional(connection.as_mut())
-        .await?;
+            FROM {} WHERE id = ?", GENDER, table());
+    let user = dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query_as(sql)
+            .bind(id)
+            .fetch_optional(conn)
+            .await?
+    });
     Ok(user)
 }
 
 pub(crate) async fn update_username(id: i64, name: String) -> Result<()>{
-    let mut connection = db::connection().await?;
-    sqlx::query("UPDATE user SET name = ? WHERE id = ?")
-        .bind(name)
-        .bind(id)
-        .execute(connection.as_mut())
-        .await?;
+    let connection = db::connection().await?;
+    let sql = format!("UPDATE {} SET name = ? WHERE id = ?", table());
+    dispatch!(connection, &sql, |sql, conn| {
+        sqlx::query(sql)
+            .bind(name)
+            .bind(id)
+            .execute(conn)
+            .await?;
+    });
     Ok(())
-}
\ No newline at end of file
+}
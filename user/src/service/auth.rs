@@ -1,4 +1,4 @@
-use crate::{common::enums::Gender, model::user::User, repository::user};
+use crate::{common::enums::Gender, model::user::User, repository::{org, user}};
 use snowflaker;
 use anyhow::Result;
 use core::{api::types::auth::RegisterRequest, response::ApiErr};
@@ -21,6 +21,11 @@ pub async fn login(username: &str, password: &str) -> Result<i64> {
 }
 
 
+//用户所在的组织，属于多个组织时取最早加入的组织，不属于任何组织时为0
+pub async fn org(uid: i64) -> Result<i64> {
+    Ok(org::find_oid_by_uid(uid).await?.unwrap_or(0))
+}
+
 pub async fn wx_signup(open_id: String, union_id: String) -> Result<i64> {
     let id = snowflaker::next_id().unwrap();
     let user = User{id: id as i64, name: "微信用户".to_string(),
@@ -7,14 +7,6 @@ use tokio::io::AsyncWriteExt;
 use core::auth::User;
 use core::response::ApiErr;
 
-#[cfg(target_os="linux")]
-const STORE_PATH: &str = "/home/data/images/";
-#[cfg(target_os="windows")]
-const STORE_PATH: &str = "D:/tmp/";
-
-//该路径对应nginx中location /images {}
-const ACCESS_PATH: &str = "/images/";
-
 pub async fn upload_avatar(claims: User, mut multipart: Multipart) -> Result<String>{
     if let Some(field) = multipart.inner.next_field().await? {
         let name = field.file_name();
@@ -29,13 +21,15 @@ pub async fn upload_avatar(claims: User, mut multipart: Multipart) -> Result<Str
         }
         let ext = ext.unwrap();
         let name = format!("{}.{}", snowflaker::next_id()?, ext.to_string_lossy());
-        let path = format!("{}{}", STORE_PATH, name);
+        //访问路径对应nginx中location /images {}
+        let storage = &crate::common::config::get().storage;
+        let path = format!("{}{}", storage.path, name);
         let data = field.bytes().await?;
         let mut file = File::create(&path).await?;
         file.write_all(data.as_ref()).await?;
         file.flush().await?;
         log::info!("Length of `{}` is {} bytes", path, data.len());
-        let path = format!("{}{}", ACCESS_PATH, name);
+        let path = format!("{}{}", storage.access_path, name);
         crate::repository::user::update_avatar(claims.id, &path).await?;
         return Ok(path);
     }
@@ -26,4 +26,41 @@ pub mod tests{
         let g : String = Gender::M.to_string();
         assert_eq!(g, "M".to_string());
     }
-}
\ No newline at end of file
+
+    #[test]
+    pub fn test_sqlite_repositories(){
+        use core::config::DatabaseConfig;
+        use core::request::Page;
+        use crate::repository::{brand, contact, db, food, org, role, user};
+        use crate::vo::{food::FoodRequest, role::RoleListRequest};
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            db::init(&DatabaseConfig { url: "sqlite::memory:".to_string(), ..Default::default() }).await;
+            //迁移脚本中的初始数据
+            let alice = user::select_user_by_name("alice").await.unwrap().unwrap();
+            assert_eq!(alice.gender, Gender::F);
+            assert_eq!(alice.age, 25);
+            //不属于任何组织
+            assert_eq!(org::find_oid_by_uid(alice.id).await.unwrap(), None);
+            let friends = contact::select_by_here(0).await.unwrap();
+            assert_eq!(friends.len(), 2);
+            assert_eq!(food::count().await.unwrap(), 109);
+            assert_eq!(food::count_by_tag(2).await.unwrap(), 6);
+            assert_eq!(food::select_one(1).await.unwrap().unwrap().id, 2);
+            let (foods, total) = food::select_page(Page::default(), FoodRequest { name: Some("饭".to_string()) }).await.unwrap();
+            assert_eq!(foods.len(), 10);
+            assert!(total > 10);
+            let (roles, total) = role::select_roles(Page::default(), RoleListRequest { name: None, code: Some("M".to_string()) }).await.unwrap();
+            assert_eq!((roles.len(), total), (2, 2));
+            let brands = brand::list_brands().await.unwrap();
+            assert_eq!(brands.len(), 2);
+            assert_eq!(brands[0].rating.0, rust_decimal::Decimal::new(49, 1));
+            assert_eq!(brands[0].platform_scores.jd, 5);
+            //写入后按性别序号读回
+            let id = snowflaker::next_id().unwrap() as i64;
+            user::insert_user(crate::model::user::User { id, name: "carol".to_string(), gender: Gender::U, age: 18, ..Default::default() }).await.unwrap();
+            user::update_username(id, "caroline".to_string()).await.unwrap();
+            let carol = user::select_user_by_id(id).await.unwrap().unwrap();
+            assert_eq!((carol.name.as_str(), carol.gender, carol.age), ("caroline", Gender::U, 18));
+        });
+    }
+}